@@ -0,0 +1,6 @@
+CREATE TABLE task_issues (
+    task_id INTEGER NOT NULL
+        REFERENCES tasks ON DELETE CASCADE,
+    issue INTEGER NOT NULL,
+    PRIMARY KEY (task_id, issue)
+);
@@ -0,0 +1,4 @@
+UPDATE log_entries
+    SET duration_seconds = duration_seconds / 60;
+ALTER TABLE log_entries
+    RENAME COLUMN duration_seconds TO duration_minutes;
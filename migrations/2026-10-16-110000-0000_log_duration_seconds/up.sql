@@ -0,0 +1,4 @@
+ALTER TABLE log_entries
+    RENAME COLUMN duration_minutes TO duration_seconds;
+UPDATE log_entries
+    SET duration_seconds = duration_seconds * 60;
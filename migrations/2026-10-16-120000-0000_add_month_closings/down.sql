@@ -0,0 +1 @@
+DROP TABLE month_closings;
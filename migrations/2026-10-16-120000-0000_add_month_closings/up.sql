@@ -0,0 +1,7 @@
+CREATE TABLE month_closings (
+    project_id INTEGER NOT NULL
+        REFERENCES projects ON DELETE CASCADE,
+    month INTEGER NOT NULL,
+    carryover_seconds INTEGER NOT NULL,
+    PRIMARY KEY (project_id, month)
+);
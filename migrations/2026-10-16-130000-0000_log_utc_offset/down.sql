@@ -0,0 +1,2 @@
+ALTER TABLE log_entries
+    DROP COLUMN utc_offset_seconds;
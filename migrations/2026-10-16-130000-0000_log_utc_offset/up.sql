@@ -0,0 +1,2 @@
+ALTER TABLE log_entries
+    ADD COLUMN utc_offset_seconds INTEGER;
@@ -0,0 +1,2 @@
+ALTER TABLE schedule_settings
+    DROP COLUMN weekly_minutes;
@@ -0,0 +1,2 @@
+ALTER TABLE schedule_settings
+    ADD COLUMN weekly_minutes INTEGER;
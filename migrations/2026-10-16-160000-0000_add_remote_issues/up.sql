@@ -0,0 +1,7 @@
+CREATE TABLE remote_issues (
+    project_id INTEGER NOT NULL
+        REFERENCES projects ON DELETE CASCADE,
+    number INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    PRIMARY KEY (project_id, number)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE imported_entries (
+    source TEXT NOT NULL,
+    source_id TEXT NOT NULL,
+    PRIMARY KEY (source, source_id)
+);
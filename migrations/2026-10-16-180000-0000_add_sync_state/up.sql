@@ -0,0 +1,13 @@
+CREATE TABLE sync_machine (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    machine_id TEXT NOT NULL
+);
+
+CREATE TABLE sync_state (
+    project_url TEXT NOT NULL,
+    task_name TEXT NOT NULL,
+    date DATE NOT NULL,
+    duration_seconds INTEGER NOT NULL,
+    updated_ms BIGINT NOT NULL,
+    PRIMARY KEY (project_url, task_name, date)
+);
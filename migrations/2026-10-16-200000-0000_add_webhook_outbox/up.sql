@@ -0,0 +1,4 @@
+CREATE TABLE webhook_outbox (
+    id INTEGER PRIMARY KEY NOT NULL,
+    payload TEXT NOT NULL
+);
@@ -0,0 +1,4 @@
+ALTER TABLE projects
+    DROP COLUMN client_id;
+
+DROP TABLE clients;
@@ -0,0 +1,7 @@
+CREATE TABLE clients (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL UNIQUE
+);
+
+ALTER TABLE projects
+    ADD COLUMN client_id INTEGER REFERENCES clients ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+DROP TRIGGER monthly_totals_delete;
+DROP TRIGGER monthly_totals_update;
+DROP TRIGGER monthly_totals_insert;
+DROP TABLE monthly_totals;
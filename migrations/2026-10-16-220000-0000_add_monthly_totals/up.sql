@@ -0,0 +1,53 @@
+-- Logged time per task and month (year * 12 + month), kept in sync with log entries by triggers
+CREATE TABLE monthly_totals (
+    task_id INTEGER NOT NULL
+        REFERENCES tasks ON DELETE CASCADE,
+    month INTEGER NOT NULL,
+    duration_seconds INTEGER NOT NULL,
+    PRIMARY KEY (task_id, month)
+);
+
+INSERT INTO monthly_totals (task_id, month, duration_seconds)
+SELECT task_id,
+       CAST(substr(date, 1, 4) AS INTEGER) * 12 + CAST(substr(date, 6, 2) AS INTEGER),
+       SUM(duration_seconds)
+FROM log_entries
+GROUP BY 1, 2;
+
+CREATE TRIGGER monthly_totals_insert AFTER INSERT ON log_entries
+BEGIN
+    INSERT INTO monthly_totals (task_id, month, duration_seconds)
+    VALUES (
+        NEW.task_id,
+        CAST(substr(NEW.date, 1, 4) AS INTEGER) * 12 + CAST(substr(NEW.date, 6, 2) AS INTEGER),
+        NEW.duration_seconds
+    )
+    ON CONFLICT (task_id, month)
+        DO UPDATE SET duration_seconds = duration_seconds + excluded.duration_seconds;
+END;
+
+CREATE TRIGGER monthly_totals_update AFTER UPDATE ON log_entries
+BEGIN
+    UPDATE monthly_totals
+    SET duration_seconds = duration_seconds - OLD.duration_seconds
+    WHERE task_id = OLD.task_id
+      AND month = CAST(substr(OLD.date, 1, 4) AS INTEGER) * 12 + CAST(substr(OLD.date, 6, 2) AS INTEGER);
+    INSERT INTO monthly_totals (task_id, month, duration_seconds)
+    VALUES (
+        NEW.task_id,
+        CAST(substr(NEW.date, 1, 4) AS INTEGER) * 12 + CAST(substr(NEW.date, 6, 2) AS INTEGER),
+        NEW.duration_seconds
+    )
+    ON CONFLICT (task_id, month)
+        DO UPDATE SET duration_seconds = duration_seconds + excluded.duration_seconds;
+    DELETE FROM monthly_totals WHERE duration_seconds = 0 AND task_id IN (OLD.task_id, NEW.task_id);
+END;
+
+CREATE TRIGGER monthly_totals_delete AFTER DELETE ON log_entries
+BEGIN
+    UPDATE monthly_totals
+    SET duration_seconds = duration_seconds - OLD.duration_seconds
+    WHERE task_id = OLD.task_id
+      AND month = CAST(substr(OLD.date, 1, 4) AS INTEGER) * 12 + CAST(substr(OLD.date, 6, 2) AS INTEGER);
+    DELETE FROM monthly_totals WHERE duration_seconds = 0 AND task_id = OLD.task_id;
+END;
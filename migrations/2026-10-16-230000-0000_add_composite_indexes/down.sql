@@ -0,0 +1,3 @@
+DROP INDEX tasks_project_issue;
+DROP INDEX tasks_project_name;
+DROP INDEX log_entries_task_date;
@@ -0,0 +1,3 @@
+CREATE INDEX log_entries_task_date ON log_entries (task_id, date);
+CREATE INDEX tasks_project_name ON tasks (project_id, name);
+CREATE INDEX tasks_project_issue ON tasks (project_id, issue);
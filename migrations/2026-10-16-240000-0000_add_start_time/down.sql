@@ -0,0 +1 @@
+ALTER TABLE log_entries DROP COLUMN start_time;
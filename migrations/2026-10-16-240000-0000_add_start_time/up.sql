@@ -0,0 +1 @@
+ALTER TABLE log_entries ADD COLUMN start_time TIME;
@@ -0,0 +1,2 @@
+ALTER TABLE projects
+    ADD COLUMN issue_prefix TEXT;
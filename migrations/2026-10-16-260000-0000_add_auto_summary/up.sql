@@ -0,0 +1,4 @@
+CREATE TABLE auto_summary (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    last_date DATE NOT NULL
+);
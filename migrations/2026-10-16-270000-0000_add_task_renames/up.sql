@@ -0,0 +1,9 @@
+CREATE TABLE task_renames (
+    id INTEGER PRIMARY KEY NOT NULL,
+    task_id INTEGER NOT NULL
+        REFERENCES tasks ON DELETE CASCADE,
+    changed_at TIMESTAMP NOT NULL,
+    change TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT
+);
@@ -0,0 +1,25 @@
+DROP TRIGGER entry_history_delete;
+DROP TRIGGER entry_history_update;
+DROP TRIGGER entry_timestamps_insert;
+DROP TABLE entry_history;
+
+DROP TRIGGER monthly_totals_update;
+CREATE TRIGGER monthly_totals_update AFTER UPDATE ON log_entries
+BEGIN
+    UPDATE monthly_totals
+    SET duration_seconds = duration_seconds - OLD.duration_seconds
+    WHERE task_id = OLD.task_id
+      AND month = CAST(substr(OLD.date, 1, 4) AS INTEGER) * 12 + CAST(substr(OLD.date, 6, 2) AS INTEGER);
+    INSERT INTO monthly_totals (task_id, month, duration_seconds)
+    VALUES (
+        NEW.task_id,
+        CAST(substr(NEW.date, 1, 4) AS INTEGER) * 12 + CAST(substr(NEW.date, 6, 2) AS INTEGER),
+        NEW.duration_seconds
+    )
+    ON CONFLICT (task_id, month)
+        DO UPDATE SET duration_seconds = duration_seconds + excluded.duration_seconds;
+    DELETE FROM monthly_totals WHERE duration_seconds = 0 AND task_id IN (OLD.task_id, NEW.task_id);
+END;
+
+ALTER TABLE log_entries DROP COLUMN updated_at;
+ALTER TABLE log_entries DROP COLUMN created_at;
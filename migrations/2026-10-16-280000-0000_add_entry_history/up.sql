@@ -0,0 +1,56 @@
+-- Timestamps are unknown for entries logged before this migration
+ALTER TABLE log_entries ADD COLUMN created_at TIMESTAMP;
+ALTER TABLE log_entries ADD COLUMN updated_at TIMESTAMP;
+
+-- Previous values of log entries, never updated or deleted. Not referencing tasks so that the
+-- history outlives removed tasks.
+CREATE TABLE entry_history (
+    id INTEGER PRIMARY KEY NOT NULL,
+    date DATE NOT NULL,
+    task_id INTEGER NOT NULL,
+    duration_seconds INTEGER NOT NULL,
+    recorded_at TIMESTAMP,
+    replaced_at TIMESTAMP NOT NULL,
+    removed BOOLEAN NOT NULL
+);
+
+-- Timestamp updates must not count towards monthly totals
+DROP TRIGGER monthly_totals_update;
+CREATE TRIGGER monthly_totals_update AFTER UPDATE OF date, task_id, duration_seconds ON log_entries
+BEGIN
+    UPDATE monthly_totals
+    SET duration_seconds = duration_seconds - OLD.duration_seconds
+    WHERE task_id = OLD.task_id
+      AND month = CAST(substr(OLD.date, 1, 4) AS INTEGER) * 12 + CAST(substr(OLD.date, 6, 2) AS INTEGER);
+    INSERT INTO monthly_totals (task_id, month, duration_seconds)
+    VALUES (
+        NEW.task_id,
+        CAST(substr(NEW.date, 1, 4) AS INTEGER) * 12 + CAST(substr(NEW.date, 6, 2) AS INTEGER),
+        NEW.duration_seconds
+    )
+    ON CONFLICT (task_id, month)
+        DO UPDATE SET duration_seconds = duration_seconds + excluded.duration_seconds;
+    DELETE FROM monthly_totals WHERE duration_seconds = 0 AND task_id IN (OLD.task_id, NEW.task_id);
+END;
+
+CREATE TRIGGER entry_timestamps_insert AFTER INSERT ON log_entries
+BEGIN
+    UPDATE log_entries
+    SET created_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+    WHERE date = NEW.date AND task_id = NEW.task_id;
+END;
+
+CREATE TRIGGER entry_history_update AFTER UPDATE OF date, task_id, duration_seconds ON log_entries
+BEGIN
+    INSERT INTO entry_history (date, task_id, duration_seconds, recorded_at, replaced_at, removed)
+    VALUES (OLD.date, OLD.task_id, OLD.duration_seconds, OLD.updated_at, CURRENT_TIMESTAMP, FALSE);
+    UPDATE log_entries
+    SET updated_at = CURRENT_TIMESTAMP
+    WHERE date = NEW.date AND task_id = NEW.task_id;
+END;
+
+CREATE TRIGGER entry_history_delete AFTER DELETE ON log_entries
+BEGIN
+    INSERT INTO entry_history (date, task_id, duration_seconds, recorded_at, replaced_at, removed)
+    VALUES (OLD.date, OLD.task_id, OLD.duration_seconds, OLD.updated_at, CURRENT_TIMESTAMP, TRUE);
+END;
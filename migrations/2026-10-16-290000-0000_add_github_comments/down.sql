@@ -0,0 +1,2 @@
+DROP TABLE github_comments;
+ALTER TABLE projects DROP COLUMN github_comments;
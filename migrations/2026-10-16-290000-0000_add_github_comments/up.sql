@@ -0,0 +1,13 @@
+ALTER TABLE projects
+    ADD COLUMN github_comments BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Weekly summaries for issues, kept after posting so that a week is only commented once
+CREATE TABLE github_comments (
+    id INTEGER PRIMARY KEY NOT NULL,
+    project_id INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    issue INTEGER NOT NULL,
+    week DATE NOT NULL,
+    body TEXT NOT NULL,
+    posted_at TIMESTAMP,
+    UNIQUE (project_id, issue, week)
+);
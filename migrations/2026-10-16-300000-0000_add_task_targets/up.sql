@@ -0,0 +1,4 @@
+CREATE TABLE task_targets (
+    task_id INTEGER PRIMARY KEY NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    weekly_seconds INTEGER NOT NULL
+);
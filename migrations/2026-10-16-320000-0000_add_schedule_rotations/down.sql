@@ -0,0 +1,4 @@
+ALTER TABLE schedule_settings
+    DROP COLUMN rotation_anchor;
+ALTER TABLE schedule_settings
+    DROP COLUMN rotation;
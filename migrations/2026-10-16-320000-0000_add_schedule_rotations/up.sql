@@ -0,0 +1,4 @@
+ALTER TABLE schedule_settings
+    ADD COLUMN rotation TEXT;
+ALTER TABLE schedule_settings
+    ADD COLUMN rotation_anchor DATE;
@@ -0,0 +1,3 @@
+-- Unknown for entries added before sources were tracked
+ALTER TABLE log_entries
+    ADD COLUMN source TEXT;
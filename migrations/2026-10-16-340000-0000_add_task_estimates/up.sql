@@ -0,0 +1,4 @@
+CREATE TABLE task_estimates (
+    task_id INTEGER PRIMARY KEY NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    seconds INTEGER NOT NULL
+);
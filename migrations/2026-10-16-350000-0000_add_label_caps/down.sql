@@ -0,0 +1 @@
+DROP TABLE label_caps;
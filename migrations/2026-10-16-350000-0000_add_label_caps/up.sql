@@ -0,0 +1,6 @@
+CREATE TABLE label_caps (
+    project_id INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    label TEXT NOT NULL,
+    monthly_seconds INTEGER NOT NULL,
+    PRIMARY KEY (project_id, label)
+);
@@ -0,0 +1,20 @@
+CREATE TABLE invoices (
+    id INTEGER PRIMARY KEY NOT NULL,
+    project_id INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    number INTEGER NOT NULL,
+    -- Month number since 1BCE, year * 12 + month
+    month INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    UNIQUE (project_id, number)
+);
+
+-- Snapshot of the invoiced time, entries may change or grow after invoicing
+CREATE TABLE invoice_items (
+    invoice_id INTEGER NOT NULL REFERENCES invoices (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    task_id INTEGER NOT NULL,
+    task_name TEXT NOT NULL,
+    issue INTEGER,
+    duration_seconds INTEGER NOT NULL,
+    PRIMARY KEY (invoice_id, date, task_id)
+);
@@ -0,0 +1,5 @@
+-- Time `show` was last run for the project, in UTC like entry timestamps
+CREATE TABLE last_shown (
+    project_id INTEGER PRIMARY KEY NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    shown_at TIMESTAMP NOT NULL
+);
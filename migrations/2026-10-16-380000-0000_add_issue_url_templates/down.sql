@@ -0,0 +1,2 @@
+ALTER TABLE projects
+    DROP COLUMN issue_url_template;
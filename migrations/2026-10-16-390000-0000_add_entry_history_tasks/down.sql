@@ -0,0 +1,21 @@
+DROP TRIGGER task_entries_delete;
+
+DROP TRIGGER entry_history_update;
+CREATE TRIGGER entry_history_update AFTER UPDATE OF date, task_id, duration_seconds ON log_entries
+BEGIN
+    INSERT INTO entry_history (date, task_id, duration_seconds, recorded_at, replaced_at, removed)
+    VALUES (OLD.date, OLD.task_id, OLD.duration_seconds, OLD.updated_at, CURRENT_TIMESTAMP, FALSE);
+    UPDATE log_entries
+    SET updated_at = CURRENT_TIMESTAMP
+    WHERE date = NEW.date AND task_id = NEW.task_id;
+END;
+
+DROP TRIGGER entry_history_delete;
+CREATE TRIGGER entry_history_delete AFTER DELETE ON log_entries
+BEGIN
+    INSERT INTO entry_history (date, task_id, duration_seconds, recorded_at, replaced_at, removed)
+    VALUES (OLD.date, OLD.task_id, OLD.duration_seconds, OLD.updated_at, CURRENT_TIMESTAMP, TRUE);
+END;
+
+ALTER TABLE entry_history DROP COLUMN task_name;
+ALTER TABLE entry_history DROP COLUMN project_id;
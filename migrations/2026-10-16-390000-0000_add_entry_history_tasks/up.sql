@@ -0,0 +1,39 @@
+-- Task of history entries is kept along with the ID, so that history of removed tasks can still be
+-- shown. Entries of tasks removed before this migration stay unattributed.
+ALTER TABLE entry_history ADD COLUMN project_id INTEGER;
+ALTER TABLE entry_history ADD COLUMN task_name TEXT;
+UPDATE entry_history
+SET project_id = (SELECT project_id FROM tasks WHERE tasks.id = entry_history.task_id),
+    task_name = (SELECT name FROM tasks WHERE tasks.id = entry_history.task_id);
+
+DROP TRIGGER entry_history_update;
+CREATE TRIGGER entry_history_update AFTER UPDATE OF date, task_id, duration_seconds ON log_entries
+BEGIN
+    INSERT INTO entry_history (
+        date, task_id, duration_seconds, recorded_at, replaced_at, removed, project_id, task_name
+    )
+    SELECT OLD.date, OLD.task_id, OLD.duration_seconds, OLD.updated_at, CURRENT_TIMESTAMP, FALSE,
+        project_id, name
+    FROM (SELECT NULL) LEFT JOIN tasks ON tasks.id = OLD.task_id;
+    UPDATE log_entries
+    SET updated_at = CURRENT_TIMESTAMP
+    WHERE date = NEW.date AND task_id = NEW.task_id;
+END;
+
+DROP TRIGGER entry_history_delete;
+CREATE TRIGGER entry_history_delete AFTER DELETE ON log_entries
+BEGIN
+    INSERT INTO entry_history (
+        date, task_id, duration_seconds, recorded_at, replaced_at, removed, project_id, task_name
+    )
+    SELECT OLD.date, OLD.task_id, OLD.duration_seconds, OLD.updated_at, CURRENT_TIMESTAMP, TRUE,
+        project_id, name
+    FROM (SELECT NULL) LEFT JOIN tasks ON tasks.id = OLD.task_id;
+END;
+
+-- Entries are removed before their task rather than by the cascade, which runs after the task is
+-- gone and would leave their history without a task
+CREATE TRIGGER task_entries_delete BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM log_entries WHERE task_id = OLD.id;
+END;
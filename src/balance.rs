@@ -0,0 +1,342 @@
+use crate::Config;
+use crate::error::WlogError;
+use crate::log_entries::{self, Period, month_index};
+use crate::projects::{Project, ProjectId};
+use crate::schema::month_closings;
+use crate::{caps, hooks, schedule};
+use diesel::prelude::*;
+use eyre::{Result, bail};
+use owo_colors::OwoColorize;
+use time::{Date, Duration};
+
+pub struct MonthBalance {
+    pub scheduled: Duration,
+    pub logged: Duration,
+    pub carryover: Duration,
+    /// Scheduled workdays without any log entries
+    pub missing: Vec<Date>,
+}
+
+impl MonthBalance {
+    pub fn balance(&self) -> Duration {
+        self.carryover + self.logged - self.scheduled
+    }
+}
+
+pub fn get(conn: &mut SqliteConnection, project: ProjectId, month: Date) -> Result<MonthBalance> {
+    let period = Period::month(month);
+    let weekly_target = schedule::get_weekly_target(conn, project)?;
+    let mut scheduled = Duration::ZERO;
+    let mut missing = Vec::new();
+    if let Some(target) = weekly_target {
+        // Weeks crossing month boundaries are split between months by number of days
+        let days = period.days().count() as i64;
+        scheduled = Duration::minutes(target.whole_minutes() * days / 7);
+    } else {
+        let totals = log_entries::get_daily_totals(conn, project, &period)?;
+        let days = schedule::scheduled_durations(conn, project, period.from, period.to)?
+            .unwrap_or_default();
+        for (date, day) in days {
+            if day.is_positive() && !totals.iter().any(|(d, _)| *d == date) {
+                missing.push(date);
+            }
+            scheduled += day;
+        }
+    }
+    let logged = log_entries::get_month_total(conn, project, month)?;
+    let carryover = get_carryover(conn, project, month_index(period.from) - 1)?;
+
+    Ok(MonthBalance {
+        scheduled,
+        logged,
+        carryover,
+        missing,
+    })
+}
+
+/// Scheduled time not logged yet on the date and in its ISO week, zero once exceeded
+pub fn left(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+) -> Result<(Duration, Duration)> {
+    let from = date - Duration::days(date.weekday().number_days_from_monday() as i64);
+    let week = Period {
+        from,
+        to: from + Duration::days(6),
+    };
+    let (logged_today, logged_week) =
+        log_entries::get_day_and_period_total(conn, project, date, &week)?;
+
+    let scheduled =
+        schedule::scheduled_durations(conn, project, week.from, week.to)?.unwrap_or_default();
+    let scheduled_today = scheduled
+        .iter()
+        .find(|(d, _)| *d == date)
+        .map(|(_, duration)| *duration)
+        .unwrap_or_default();
+    let scheduled_week = match schedule::get_weekly_target(conn, project)? {
+        Some(target) => target,
+        None => scheduled.iter().map(|(_, duration)| *duration).sum(),
+    };
+
+    Ok((
+        (scheduled_today - logged_today).max(Duration::ZERO),
+        (scheduled_week - logged_week).max(Duration::ZERO),
+    ))
+}
+
+pub struct Forecast {
+    /// Scheduled time of the month reduced by the carryover of the previous month
+    pub required: Duration,
+    pub logged: Duration,
+    /// Scheduled workdays from the date to the end of the month, the date included
+    pub workdays_left: i64,
+    /// Time logged by the end of the month if the pace of past workdays holds
+    pub projected: Duration,
+}
+
+impl Forecast {
+    pub fn remaining(&self) -> Duration {
+        (self.required - self.logged).max(Duration::ZERO)
+    }
+
+    /// Time to log on each remaining workday, `None` if there's time left but no workdays
+    pub fn per_day(&self) -> Option<Duration> {
+        if self.remaining().is_zero() {
+            Some(Duration::ZERO)
+        } else if self.workdays_left > 0 {
+            Some(self.remaining() / self.workdays_left as i32)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn forecast(conn: &mut SqliteConnection, project: ProjectId, date: Date) -> Result<Forecast> {
+    let balance = get(conn, project, date)?;
+    let period = Period::month(date);
+    let totals = log_entries::get_daily_totals(conn, project, &period)?;
+
+    let mut workdays_past = 0;
+    let mut workdays_left = 0;
+    let mut scheduled_left = Duration::ZERO;
+    for day in period.days() {
+        let scheduled = schedule::scheduled_duration(conn, project, day)?.unwrap_or_default();
+        if !scheduled.is_positive() {
+            continue;
+        }
+        if day < date {
+            workdays_past += 1;
+        } else {
+            workdays_left += 1;
+            scheduled_left += scheduled;
+        }
+    }
+    let logged_past: Duration = totals
+        .iter()
+        .filter(|(day, _)| *day < date)
+        .map(|(_, duration)| *duration)
+        .sum();
+    // Without past workdays there's no pace yet, assume the schedule is followed
+    let rest = if workdays_past > 0 {
+        logged_past / workdays_past * workdays_left
+    } else {
+        scheduled_left
+    };
+
+    Ok(Forecast {
+        required: balance.scheduled - balance.carryover,
+        logged: balance.logged,
+        workdays_left: workdays_left as i64,
+        projected: (logged_past + rest).max(balance.logged),
+    })
+}
+
+pub fn show_forecast(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    date: Date,
+) -> Result<()> {
+    let forecast = forecast(conn, project.id, date)?;
+
+    let mut table = config.table_style().table();
+    table.set_header([
+        "Required",
+        "Logged",
+        "Remaining",
+        "Workdays left",
+        "Per day",
+        "Projected",
+    ]);
+    table.add_row([
+        config.fmt_duration(forecast.required),
+        config.fmt_duration(forecast.logged),
+        config.fmt_duration(forecast.remaining()),
+        forecast.workdays_left.to_string(),
+        forecast
+            .per_day()
+            .map(|duration| config.fmt_duration(duration))
+            .unwrap_or_else(|| "-".into()),
+        config.fmt_duration(forecast.projected),
+    ]);
+    println!("{table}");
+
+    let shortfall = forecast.required - forecast.projected;
+    if forecast.remaining().is_zero() {
+        eprintln!("{} Monthly requirement reached", "Info:".cyan());
+    } else if forecast.per_day().is_none() {
+        eprintln!(
+            "{} No workdays left, the month ends {} short",
+            "Warning:".yellow().bold(),
+            config.fmt_duration(forecast.remaining())
+        );
+    } else if shortfall.is_positive() {
+        eprintln!(
+            "{} At the current pace the month ends {} short",
+            "Warning:".yellow().bold(),
+            config.fmt_duration(shortfall)
+        );
+    } else {
+        eprintln!(
+            "{} On track to reach the monthly requirement",
+            "Info:".cyan()
+        );
+    }
+    Ok(())
+}
+
+pub fn close(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    month: Date,
+    force: bool,
+) -> Result<()> {
+    let balance = get(conn, project.id, month)?;
+    if !balance.missing.is_empty() && !force {
+        let missing = balance
+            .missing
+            .iter()
+            .map(Date::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        bail!(WlogError::Validation(format!(
+            "Scheduled workdays without log entries: {missing}"
+        )));
+    }
+
+    diesel::insert_into(month_closings::table)
+        .values((
+            month_closings::project_id.eq(project.id),
+            month_closings::month.eq(month_index(month)),
+            month_closings::carryover_seconds.eq(balance.balance().whole_seconds() as i32),
+        ))
+        .on_conflict((month_closings::project_id, month_closings::month))
+        .do_update()
+        .set(month_closings::carryover_seconds.eq(balance.balance().whole_seconds() as i32))
+        .execute(conn)?;
+
+    eprintln!(
+        "{} {} {} closed with carryover {}",
+        "Success:".green().bold(),
+        month.month(),
+        month.year(),
+        fmt_balance(config, balance.balance())
+    );
+    hooks::run(
+        hooks::month_closed(config, project, month, balance.balance())
+            .into_iter()
+            .collect(),
+    );
+    Ok(())
+}
+
+pub fn show(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    month: Date,
+) -> Result<()> {
+    let balance = get(conn, project.id, month)?;
+
+    let mut table = config.table_style().table();
+    table.set_header(["Scheduled", "Logged", "Carryover", "Balance"]);
+    table.add_row([
+        config.fmt_duration(balance.scheduled),
+        config.fmt_duration(balance.logged),
+        fmt_balance(config, balance.carryover),
+        fmt_balance(config, balance.balance()),
+    ]);
+    println!("{table}");
+
+    if let Some(target) = schedule::get_weekly_target(conn, project.id)? {
+        show_weeks(conn, config, project.id, month, target)?;
+    }
+    caps::show(conn, config, project.id, month)?;
+
+    if !balance.missing.is_empty() {
+        eprintln!(
+            "{} {} scheduled workdays without log entries",
+            "Note:".cyan(),
+            balance.missing.len()
+        );
+    }
+    Ok(())
+}
+
+/// Print target compliance of every ISO week that overlaps the month
+fn show_weeks(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    month: Date,
+    target: Duration,
+) -> Result<()> {
+    let period = Period::month(month);
+    let mut table = config.table_style().table();
+    table.set_header(["Week", "Target", "Logged", "Difference"]);
+
+    let mut monday =
+        period.from - Duration::days(period.from.weekday().number_days_from_monday() as i64);
+    while monday <= period.to {
+        let week = Period {
+            from: monday,
+            to: monday + Duration::days(6),
+        };
+        let logged: Duration = log_entries::get_daily_totals(conn, project, &week)?
+            .into_iter()
+            .map(|(_, duration)| duration)
+            .sum();
+        let (year, number, _) = monday.to_iso_week_date();
+        table.add_row([
+            format!("{year}-W{number:02}"),
+            config.fmt_duration(target),
+            config.fmt_duration(logged),
+            fmt_balance(config, logged - target),
+        ]);
+        monday += Duration::days(7);
+    }
+    println!("{table}");
+    Ok(())
+}
+
+fn get_carryover(conn: &mut SqliteConnection, project: ProjectId, month: i32) -> Result<Duration> {
+    month_closings::table
+        .find((project, month))
+        .select(month_closings::carryover_seconds)
+        .get_result::<i32>(conn)
+        .optional()
+        .map(|seconds| Duration::seconds(seconds.unwrap_or(0) as i64))
+        .map_err(Into::into)
+}
+
+/// Duration with an explicit sign, "+" for zero as well
+pub fn fmt_balance(config: &Config, duration: Duration) -> String {
+    if duration.is_negative() {
+        config.fmt_duration(duration)
+    } else {
+        format!("+{}", config.fmt_duration(duration))
+    }
+}
@@ -0,0 +1,270 @@
+use crate::Config;
+use crate::balance::fmt_balance;
+use crate::error::WlogError;
+use crate::log_entries::Period;
+use crate::projects::ProjectId;
+use crate::schema::{label_caps, log_entries, task_labels, tasks};
+use crate::tasks::TaskId;
+use crate::utils::fmt_duration;
+use diesel::prelude::*;
+use eyre::{Result, bail};
+use owo_colors::OwoColorize;
+use std::collections::BTreeMap;
+use time::{Date, Duration};
+
+/// Time logged in a month to tasks with a capped label
+pub struct Usage {
+    pub label: String,
+    pub cap: Duration,
+    pub logged: Duration,
+}
+
+impl Usage {
+    pub fn is_exceeded(&self) -> bool {
+        self.logged > self.cap
+    }
+}
+
+/// Monthly caps of the project's labels, ordered by label
+pub fn get(conn: &mut SqliteConnection, project: ProjectId) -> Result<Vec<(String, Duration)>> {
+    let caps = label_caps::table
+        .filter(label_caps::project_id.eq(project.0))
+        .order_by(label_caps::label)
+        .select((label_caps::label, label_caps::monthly_seconds))
+        .load::<(String, i32)>(conn)?;
+    Ok(caps
+        .into_iter()
+        .map(|(label, seconds)| (label, Duration::seconds(seconds as i64)))
+        .collect())
+}
+
+/// Set or remove the monthly cap of time logged to the project's tasks with the label. Labels are
+/// normalized like task labels.
+pub fn set(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    label: &str,
+    cap: Option<Duration>,
+) -> Result<()> {
+    let label = label.trim().to_lowercase();
+    if label.is_empty() {
+        bail!(WlogError::Validation("Labels can't be empty".into()));
+    }
+    match cap {
+        Some(cap) if !cap.is_positive() => {
+            bail!(WlogError::Validation("Monthly cap must be positive".into()))
+        }
+        Some(cap) => {
+            let seconds = i32::try_from(cap.whole_seconds()).map_err(|_| {
+                WlogError::Validation(format!("Monthly cap of {} is too long", fmt_duration(cap)))
+            })?;
+            diesel::insert_into(label_caps::table)
+                .values((
+                    label_caps::project_id.eq(project.0),
+                    label_caps::label.eq(&label),
+                    label_caps::monthly_seconds.eq(seconds),
+                ))
+                .on_conflict((label_caps::project_id, label_caps::label))
+                .do_update()
+                .set(label_caps::monthly_seconds.eq(seconds))
+                .execute(conn)?;
+        }
+        None => {
+            diesel::delete(label_caps::table.find((project.0, &label))).execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Time logged to every capped label of the project in the month containing the date. With a
+/// task, only its labels are included.
+pub fn usage(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+    task: Option<TaskId>,
+) -> Result<Vec<Usage>> {
+    let mut caps = get(conn, project)?;
+    if let Some(task) = task {
+        let labels = crate::tasks::get_labels(conn, &[task])?
+            .remove(&task)
+            .unwrap_or_default();
+        caps.retain(|(label, _)| labels.contains(label));
+    }
+    if caps.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let period = Period::month(date);
+    let rows = log_entries::table
+        .inner_join(tasks::table.inner_join(task_labels::table))
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.between(period.from, period.to))
+        .filter(task_labels::label.eq_any(caps.iter().map(|(label, _)| label)))
+        .select((task_labels::label, log_entries::duration_seconds))
+        .load::<(String, i32)>(conn)?;
+    let mut logged = BTreeMap::<String, Duration>::new();
+    for (label, seconds) in rows {
+        *logged.entry(label).or_default() += Duration::seconds(seconds as i64);
+    }
+    Ok(caps
+        .into_iter()
+        .map(|(label, cap)| Usage {
+            logged: logged.get(&label).copied().unwrap_or_default(),
+            label,
+            cap,
+        })
+        .collect())
+}
+
+/// Caps of the task's labels exceeded in the month containing the date
+pub fn exceeded(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+    task: TaskId,
+) -> Result<Vec<Usage>> {
+    let mut usage = usage(conn, project, date, Some(task))?;
+    usage.retain(Usage::is_exceeded);
+    Ok(usage)
+}
+
+/// Warn about monthly caps of the task's labels exceeded in the month containing the date
+pub fn warn_exceeded(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    date: Date,
+    task: TaskId,
+) -> Result<()> {
+    for usage in exceeded(conn, project, date, task)? {
+        eprintln!(
+            "{} {} logged to \"{}\" in {} {}, over its monthly cap of {}",
+            "Warning:".yellow().bold(),
+            config.fmt_duration(usage.logged),
+            usage.label,
+            config.locale().month(date.month()),
+            date.year(),
+            config.fmt_duration(usage.cap)
+        );
+    }
+    Ok(())
+}
+
+/// Print time logged to capped labels in the month containing the date against their caps. This is
+/// the compliance section of `wlog month balance`, there's no separate stats command.
+pub fn show(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    month: Date,
+) -> Result<()> {
+    let usage = usage(conn, project, month, None)?;
+    if usage.is_empty() {
+        return Ok(());
+    }
+
+    let mut table = config.table_style().table();
+    table.set_header(["Label", "Cap", "Logged", "Left"]);
+    for usage in &usage {
+        table.add_row([
+            usage.label.clone(),
+            config.fmt_duration(usage.cap),
+            config.fmt_duration(usage.logged),
+            fmt_balance(config, usage.cap - usage.logged),
+        ]);
+    }
+    println!("{table}");
+
+    let exceeded = usage.iter().filter(|usage| usage.is_exceeded()).count();
+    if exceeded > 0 {
+        eprintln!(
+            "{} {exceeded} labels over their monthly caps",
+            "Warning:".yellow().bold()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data;
+    use crate::log_entries::{Limits, set_duration};
+    use time::Month;
+
+    fn day(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2026, month, day).unwrap()
+    }
+
+    #[test]
+    fn caps_are_normalized() {
+        let (mut conn, project) = data::open_memory_with_project();
+        set(&mut conn, project, " Meetings ", Some(Duration::hours(10))).unwrap();
+        assert_eq!(
+            get(&mut conn, project).unwrap(),
+            [("meetings".to_string(), Duration::hours(10))]
+        );
+        for (label, cap) in [
+            (" ", Some(Duration::hours(1))),
+            ("meetings", Some(Duration::ZERO)),
+            ("meetings", Some(Duration::seconds(i32::MAX as i64 + 1))),
+        ] {
+            let err = set(&mut conn, project, label, cap).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<WlogError>(),
+                Some(WlogError::Validation(_))
+            ));
+        }
+        set(&mut conn, project, "MEETINGS", None).unwrap();
+        assert!(get(&mut conn, project).unwrap().is_empty());
+    }
+
+    #[test]
+    fn monthly_usage() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let meeting = crate::tasks::create(&mut conn, project, "Meeting", None).unwrap();
+        let work = crate::tasks::create(&mut conn, project, "Work", None).unwrap();
+        crate::tasks::update_labels(&mut conn, meeting, &["meetings".to_string()], &[]).unwrap();
+        set(&mut conn, project, "meetings", Some(Duration::hours(10))).unwrap();
+        set(&mut conn, project, "reviews", Some(Duration::hours(5))).unwrap();
+        let limits = Limits::unlimited();
+        for (date, task, hours) in [
+            (day(Month::September, 30), meeting, 8),
+            (day(Month::October, 1), meeting, 6),
+            (day(Month::October, 2), meeting, 5),
+            (day(Month::October, 2), work, 3),
+        ] {
+            set_duration(
+                &mut conn,
+                project,
+                date,
+                task,
+                Duration::hours(hours),
+                &limits,
+            )
+            .unwrap();
+        }
+
+        let date = day(Month::October, 16);
+        let logged = usage(&mut conn, project, date, None)
+            .unwrap()
+            .into_iter()
+            .map(|usage| (usage.label, usage.logged.whole_hours()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            logged,
+            [("meetings".to_string(), 11), ("reviews".to_string(), 0)]
+        );
+        // Only caps of the task's labels apply to it
+        assert!(exceeded(&mut conn, project, date, work).unwrap().is_empty());
+        let over = exceeded(&mut conn, project, date, meeting).unwrap();
+        assert_eq!(over.len(), 1);
+        assert_eq!(over[0].label, "meetings");
+        assert!(
+            exceeded(&mut conn, project, day(Month::September, 1), meeting)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::schema::{log_entries, projects, tasks};
+use crate::utils::{fmt_duration, yn_prompt};
+use diesel::dsl::sum;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
+use eyre::Result;
+use owo_colors::OwoColorize;
+use std::collections::BTreeMap;
+use time::{Date, Duration};
+
+/// Rows of a table referencing missing rows of another table through the same foreign key
+#[derive(Debug, PartialEq)]
+struct Violation {
+    table: String,
+    parent: String,
+    /// Columns of the foreign key
+    columns: Vec<String>,
+    /// The key is cleared rather than the row deleted, following its `ON DELETE SET NULL` action
+    set_null: bool,
+    rowids: Vec<i64>,
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyCheck {
+    #[diesel(sql_type = Text)]
+    table: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    rowid: Option<i64>,
+    #[diesel(sql_type = Text)]
+    parent: String,
+    #[diesel(sql_type = Integer)]
+    fkid: i32,
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyColumn {
+    #[diesel(sql_type = Text)]
+    column: String,
+    #[diesel(sql_type = Text)]
+    on_delete: String,
+}
+
+/// Validate database consistency and offer to fix found problems. Schedules are stored once per
+/// project and month, so they can't overlap and aren't checked.
+pub fn run(conn: &mut SqliteConnection) -> Result<()> {
+    let mut problems = 0;
+
+    for violation in foreign_key_violations(conn)? {
+        problems += 1;
+        report(&format!(
+            "{} rows of {} reference a missing row of {}",
+            violation.rowids.len(),
+            violation.table,
+            violation.parent
+        ));
+        let prompt = if violation.set_null {
+            "Clear the references of these rows?"
+        } else {
+            "Delete these rows?"
+        };
+        if yn_prompt(prompt)? {
+            conn.transaction(|conn| fix(conn, &violation))?;
+        }
+    }
+
+    let negative: Vec<(Date, i32)> = log_entries::table
+        .filter(log_entries::duration_seconds.lt(0))
+        .select((log_entries::date, log_entries::task_id))
+        .load(conn)?;
+    if !negative.is_empty() {
+        problems += 1;
+        report(&format!(
+            "{} log entries have negative duration",
+            negative.len()
+        ));
+        if yn_prompt("Delete these log entries?")? {
+            conn.transaction(|conn| {
+                diesel::delete(log_entries::table.filter(log_entries::duration_seconds.lt(0)))
+                    .execute(conn)
+            })?;
+        }
+    }
+
+    let project_ids: Vec<i32> = projects::table.select(projects::id).load(conn)?;
+    for project_id in project_ids {
+        let overfull: Vec<(Date, Option<i64>)> = log_entries::table
+            .inner_join(tasks::table)
+            .filter(tasks::project_id.eq(project_id))
+            .group_by(log_entries::date)
+            .having(sum(log_entries::duration_seconds).gt(24 * 60 * 60))
+            .select((log_entries::date, sum(log_entries::duration_seconds)))
+            .load(conn)?;
+        for (date, seconds) in overfull {
+            problems += 1;
+            report(&format!(
+                "Project {project_id} has {} logged on {date}",
+                fmt_duration(Duration::seconds(seconds.unwrap_or(0)))
+            ));
+        }
+    }
+
+    if problems == 0 {
+        eprintln!("{} No problems found", "Success:".green().bold());
+    }
+    Ok(())
+}
+
+fn report(msg: &str) {
+    eprintln!("{} {msg}", "Warning:".yellow().bold());
+}
+
+/// Foreign key violations found by SQLite, grouped by foreign key. They remain from databases
+/// changed while foreign keys weren't enforced.
+fn foreign_key_violations(conn: &mut SqliteConnection) -> Result<Vec<Violation>> {
+    let mut violations = BTreeMap::<(String, i32), Violation>::new();
+    for check in diesel::sql_query("PRAGMA foreign_key_check").load::<ForeignKeyCheck>(conn)? {
+        // All tables have row IDs, rows without one couldn't be fixed anyway
+        let Some(rowid) = check.rowid else {
+            continue;
+        };
+        let key = (check.table.clone(), check.fkid);
+        if let Some(violation) = violations.get_mut(&key) {
+            violation.rowids.push(rowid);
+            continue;
+        }
+        let columns = diesel::sql_query(
+            "SELECT \"from\" AS \"column\", on_delete FROM pragma_foreign_key_list(?) \
+             WHERE id = ? ORDER BY seq",
+        )
+        .bind::<Text, _>(&check.table)
+        .bind::<Integer, _>(check.fkid)
+        .load::<ForeignKeyColumn>(conn)?;
+        violations.insert(
+            key,
+            Violation {
+                table: check.table,
+                parent: check.parent,
+                set_null: columns.iter().all(|column| column.on_delete == "SET NULL"),
+                columns: columns.into_iter().map(|column| column.column).collect(),
+                rowids: vec![rowid],
+            },
+        );
+    }
+    Ok(violations.into_values().collect())
+}
+
+/// Delete the violating rows, or clear their keys. Rows referencing deleted rows are removed along
+/// with them by their `ON DELETE` actions.
+fn fix(conn: &mut SqliteConnection, violation: &Violation) -> Result<()> {
+    let table = quote(&violation.table);
+    let statement = if violation.set_null {
+        let columns = violation
+            .columns
+            .iter()
+            .map(|column| format!("{} = NULL", quote(column)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("UPDATE {table} SET {columns} WHERE rowid = ?")
+    } else {
+        format!("DELETE FROM {table} WHERE rowid = ?")
+    };
+    for &rowid in &violation.rowids {
+        diesel::sql_query(&statement)
+            .bind::<BigInt, _>(rowid)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Identifier quoted for SQL
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data;
+
+    #[test]
+    fn foreign_keys() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Task", None).unwrap();
+        diesel::sql_query("PRAGMA foreign_keys = OFF")
+            .execute(&mut conn)
+            .unwrap();
+        diesel::sql_query("INSERT INTO clients (id, name) VALUES (1, 'Client')")
+            .execute(&mut conn)
+            .unwrap();
+        diesel::update(projects::table)
+            .set(projects::client_id.eq(1))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::sql_query("DELETE FROM clients")
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(tasks::table)
+            .values((
+                tasks::project_id.eq(project.0 + 1),
+                tasks::name.eq("Orphan"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::sql_query("PRAGMA foreign_keys = ON")
+            .execute(&mut conn)
+            .unwrap();
+
+        let violations = foreign_key_violations(&mut conn).unwrap();
+        assert_eq!(
+            violations
+                .iter()
+                .map(|v| (
+                    v.table.as_str(),
+                    v.parent.as_str(),
+                    v.set_null,
+                    v.rowids.len()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("projects", "clients", true, 1),
+                ("tasks", "projects", false, 1)
+            ]
+        );
+        for violation in &violations {
+            fix(&mut conn, violation).unwrap();
+        }
+        assert!(foreign_key_violations(&mut conn).unwrap().is_empty());
+        // The project stays, only its client is cleared
+        let tasks = tasks::table
+            .select(tasks::id)
+            .load::<i32>(&mut conn)
+            .unwrap();
+        assert_eq!(tasks, [task.0]);
+    }
+}
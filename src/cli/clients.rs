@@ -0,0 +1,78 @@
+use super::common::PeriodArgGroup;
+use crate::projects::ProjectId;
+use crate::{Config, clients, data};
+use clap::Subcommand;
+use eyre::Result;
+use owo_colors::OwoColorize;
+
+#[derive(Debug, Subcommand)]
+pub enum ClientCmd {
+    /// Create a new client
+    Create { name: String },
+    /// List clients with their projects
+    List,
+    /// Delete a client, its projects are kept
+    Delete { id: i32 },
+    /// Assign a project to a client
+    Assign {
+        /// Client ID
+        id: i32,
+        /// Project ID
+        #[arg(long)]
+        project: i32,
+    },
+    /// Remove a project from its client
+    Unassign {
+        /// Project ID
+        #[arg(long)]
+        project: i32,
+    },
+    /// Logged time per client and project
+    Totals {
+        /// Period
+        #[clap(flatten)]
+        period: PeriodArgGroup,
+    },
+}
+
+impl ClientCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        match self {
+            ClientCmd::Create { name } => {
+                let client = clients::create(&mut conn, &name)?;
+                eprintln!(
+                    "{} Client {} created with ID {}",
+                    "Success:".green().bold(),
+                    client.name,
+                    client.id
+                );
+            }
+            ClientCmd::List => clients::list(&mut conn, &config)?,
+            ClientCmd::Delete { id } => {
+                clients::delete(&mut conn, id)?;
+                eprintln!("{} Client {id} deleted", "Success:".green().bold());
+            }
+            ClientCmd::Assign { id, project } => {
+                clients::assign(&mut conn, ProjectId(project), Some(id))?;
+                eprintln!(
+                    "{} Project {project} assigned to client {id}",
+                    "Success:".green().bold()
+                );
+            }
+            ClientCmd::Unassign { project } => {
+                clients::assign(&mut conn, ProjectId(project), None)?;
+                eprintln!(
+                    "{} Project {project} removed from its client",
+                    "Success:".green().bold()
+                );
+            }
+            ClientCmd::Totals { period } => {
+                let period = period.to_period(&config, config.now()?)?;
+                clients::show_totals(&mut conn, &config, period.as_ref())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use super::common::{DateArgGroup, duration_value_parser};
 use crate::{Config, comments, data, projects};
 use clap::Args;
 use eyre::Result;
-use time::{Duration, OffsetDateTime};
+use time::Duration;
 
 #[derive(Debug, Args)]
 pub struct AddCommentCmd {
@@ -17,13 +17,12 @@ pub struct AddCommentCmd {
 }
 
 impl AddCommentCmd {
-    pub fn dispatch(self) -> Result<()> {
-        let config = Config::read()?.unwrap_or_default();
+    pub fn dispatch(self, config: Config) -> Result<()> {
         let mut conn = data::open(config.data_path.as_ref())?;
 
-        let now = OffsetDateTime::now_local()?;
+        let now = config.now()?;
         let date = self.date.to_date(&config, now)?;
-        let project = projects::get_default_or_create_interactive(&mut conn)?;
+        let project = projects::get_default_or_create_interactive(&mut conn, &config)?;
 
         let comment = comments::Comment {
             date,
@@ -1,5 +1,8 @@
 use crate::config::Config;
+use crate::error::WlogError;
+use crate::locale;
 use crate::log_entries::Period;
+use crate::parsers;
 use clap::Args;
 use eyre::{Result, anyhow, bail};
 use time::ext::NumericalDuration;
@@ -16,14 +19,22 @@ pub struct DateArgGroup {
     /// Log entry date, nearest past weekday
     #[arg(short, long, value_parser = weekday_value_parser, group = "date_group")]
     weekday: Option<Weekday>,
-    /// Log entry date, string in ISO8601 format
-    #[arg(long, value_parser = date_value_parser, group = "date_group")]
-    date: Option<Date>,
+    /// Log entry date, in ISO8601 format or a shorthand like `monday` or `-3d`
+    #[arg(
+        long,
+        value_parser = relaxed_date_value_parser,
+        allow_hyphen_values = true,
+        group = "date_group"
+    )]
+    date: Option<DateArg>,
+    /// Log entry date with optional start time, e.g. "2025-01-10 14:00"
+    #[arg(long, value_parser = date_time_value_parser, group = "date_group")]
+    at: Option<(Date, Option<Time>)>,
     /// Log entry day
     #[arg(short, long, group = "date_group")]
     day: Option<u8>,
     /// Log entry month
-    #[arg(short, long, requires = "day")]
+    #[arg(short, long, value_parser = month_value_parser, requires = "day")]
     month: Option<time::Month>,
     /// Log entry year
     #[arg(long, requires = "month")]
@@ -41,12 +52,21 @@ impl DateArgGroup {
         } else if let Some(weekday) = self.weekday {
             today.prev_occurrence(weekday)
         } else if let Some(date) = self.date {
+            date.first(today)?
+        } else if let Some((date, _)) = self.at {
             date
         } else if let Some(day) = self.day {
             match (self.month, self.year) {
+                // Days after today refer to the previous month, never further back
                 (None, None) if day > today.day() => {
-                    let last_month = today - (day as i64).days();
-                    last_month.replace_day(day)?
+                    let last_month = today.replace_day(1).unwrap() - 1.days();
+                    last_month.replace_day(day).map_err(|_| {
+                        anyhow!(
+                            "{} {} has no day {day}",
+                            last_month.month(),
+                            last_month.year()
+                        )
+                    })?
                 }
                 (None, None) => today.replace_day(day)?,
                 (None, Some(_)) => unreachable!("Invalid argument combination"),
@@ -66,58 +86,113 @@ impl DateArgGroup {
 
         Ok(date)
     }
+
+    /// Whether the date was inferred from `--day` without a year and landed in another month than
+    /// today, which is easy to get wrong around month boundaries
+    pub fn is_ambiguous(&self, date: Date, today: Date) -> bool {
+        self.day.is_some()
+            && self.year.is_none()
+            && (date.year(), date.month()) != (today.year(), today.month())
+    }
+
+    /// Start time given with `--at`
+    pub fn start_time(&self) -> Option<Time> {
+        self.at.and_then(|(_, time)| time)
+    }
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args)]
 pub struct PeriodArgGroup {
     /// List all logs
     #[arg(long)]
     all: bool,
-    /// Only show entries starting from this date, string in ISO8601 format
-    #[arg(long, value_parser = date_value_parser)]
-    from: Option<Date>,
-    /// Only show entries up to this date, string in ISO8601 format
-    #[arg(long, value_parser = date_value_parser)]
-    to: Option<Date>,
+    /// Only show entries starting from this date, in ISO8601 format or a shorthand like `monday`,
+    /// `-3d`, `2025-01` or `last-month`. Up to today without `--to`
+    #[arg(long, value_parser = relaxed_date_value_parser, allow_hyphen_values = true)]
+    from: Option<DateArg>,
+    /// Only show entries up to this date, same formats as `--from`. From the first entry on
+    /// without `--from`
+    #[arg(long, value_parser = relaxed_date_value_parser, allow_hyphen_values = true)]
+    to: Option<DateArg>,
     /// Only show entries from this day
     #[arg(long)]
     today: bool,
     /// Only show entries for the last 7 days
     #[arg(short, long)]
     week: bool,
+    /// Only show entries of this month, in YYYY-MM format
+    #[arg(long, value_parser = year_month_value_parser, conflicts_with_all = ["from", "to", "year"])]
+    month: Option<Date>,
+    /// Only show entries of this year
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    year: Option<i32>,
 }
 
 impl PeriodArgGroup {
-    pub fn to_period(&self, config: &Config, now: time::OffsetDateTime) -> Option<Period> {
+    /// Whether the period is the last week given with `--week`
+    pub fn is_week(&self) -> bool {
+        self.week
+    }
+
+    /// Whether no period is given and the previous month applies
+    pub fn is_default(&self) -> bool {
+        !self.all
+            && !self.today
+            && !self.week
+            && self.month.is_none()
+            && self.year.is_none()
+            && self.from.is_none()
+            && self.to.is_none()
+    }
+
+    pub fn to_period(&self, config: &Config, now: time::OffsetDateTime) -> Result<Option<Period>> {
         let today = if now.time() < config.day_change_threshold() {
             now.date().previous_day().unwrap()
         } else {
             now.date()
         };
+        self.resolve(today)
+    }
 
+    /// Period of the arguments, `None` for all entries. The first of these applies: `--all`,
+    /// `--today`, `--week`, `--month`, `--year`, `--from`/`--to`, and the previous month if none
+    /// is given.
+    fn resolve(&self, today: Date) -> Result<Option<Period>> {
         if self.all {
-            None
-        } else if self.today {
-            Some(Period {
+            return Ok(None);
+        }
+        let period = if self.today {
+            Period {
                 from: today,
                 to: today,
-            })
+            }
         } else if self.week {
-            Some(Period {
+            Period {
                 from: today - 7.days(),
                 to: today,
-            })
-        } else if self.from.is_none() && self.to.is_none() {
-            let from = (today - Duration::days(today.day() as i64))
-                .replace_day(1)
-                .unwrap();
-            let to = today - Duration::days(today.day() as i64);
-            Some(Period { from, to })
+            }
+        } else if let Some(month) = self.month {
+            Period::month(month)
+        } else if let Some(year) = self.year {
+            Period {
+                from: Date::from_calendar_date(year, time::Month::January, 1)?,
+                to: Date::from_calendar_date(year, time::Month::December, 31)?,
+            }
         } else {
-            let to = self.to.unwrap_or_else(|| now.date());
-            let from = self.from.unwrap_or_else(|| to - Duration::weeks(1));
-            Some(Period { from, to })
-        }
+            match (self.from, self.to) {
+                (Some(from), Some(to)) => Period {
+                    from: from.first(today)?,
+                    to: to.last(today)?,
+                },
+                (Some(from), None) => Period {
+                    from: from.first(today)?,
+                    to: today,
+                },
+                (None, Some(to)) => Period::until(to.last(today)?),
+                (None, None) => Period::month(today.replace_day(1).unwrap() - 1.days()),
+            }
+        };
+        Ok(Some(period))
     }
 }
 
@@ -125,61 +200,276 @@ pub fn time_value_parser(v: &str) -> Result<Time, time::error::Parse> {
     Time::parse(v, &time::format_description::well_known::Iso8601::TIME)
 }
 
+pub fn utc_offset_value_parser(v: &str) -> Result<time::UtcOffset> {
+    let (sign, offset) = match v.split_at_checked(1) {
+        Some(("+", offset)) => (1, offset),
+        Some(("-", offset)) => (-1, offset),
+        _ => bail!("UTC offset must start with '+' or '-'"),
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let hours = sign * hours.parse::<i8>()?;
+    let minutes = sign * minutes.parse::<i8>()?;
+    time::UtcOffset::from_hms(hours, minutes, 0).map_err(Into::into)
+}
+
 pub fn date_value_parser(v: &str) -> Result<Date, time::error::Parse> {
     Date::parse(v, &time::format_description::well_known::Iso8601::DATE)
 }
 
+/// ISO8601 date optionally followed by time of day separated by a space or `T`, e.g.
+/// `2025-01-10 14:00`
+pub fn date_time_value_parser(v: &str) -> Result<(Date, Option<Time>)> {
+    let (date, time) = match v.trim().split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (v.trim(), None),
+    };
+    let date = date_value_parser(date)?;
+    let Some(time) = time else {
+        return Ok((date, None));
+    };
+    let parts = time
+        .split(':')
+        .map(|part| part.parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow!("Invalid time: \"{time}\""))?;
+    let time = match parts[..] {
+        [hour, minute] => Time::from_hms(hour, minute, 0)?,
+        [hour, minute, second] => Time::from_hms(hour, minute, second)?,
+        _ => bail!("Expected time in HH:MM or HH:MM:SS format"),
+    };
+    Ok((date, Some(time)))
+}
+
+/// Date in ISO8601 format or a shorthand relative to today
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateArg {
+    Date(Date),
+    Today,
+    Yesterday,
+    /// Nearest past weekday
+    Weekday(Weekday),
+    /// Number of days before today
+    DaysAgo(i64),
+    /// Calendar month containing the date
+    Month(Date),
+    LastMonth,
+}
+
+impl DateArg {
+    /// First day the argument refers to, start of the month for months
+    pub fn first(self, today: Date) -> Result<Date> {
+        match self {
+            DateArg::Month(month) => Ok(month.replace_day(1).unwrap()),
+            DateArg::LastMonth => Ok(Period::month(today.replace_day(1).unwrap() - 1.days()).from),
+            _ => self.last(today),
+        }
+    }
+
+    /// Last day the argument refers to, end of the month for months
+    pub fn last(self, today: Date) -> Result<Date> {
+        Ok(match self {
+            DateArg::Date(date) => date,
+            DateArg::Today => today,
+            DateArg::Yesterday => today - 1.days(),
+            DateArg::Weekday(weekday) => today.prev_occurrence(weekday),
+            DateArg::DaysAgo(days) => days
+                .checked_mul(24 * 60 * 60)
+                .and_then(|seconds| today.checked_sub(Duration::seconds(seconds)))
+                .ok_or_else(|| {
+                    WlogError::Validation(format!("{days} days before {today} is out of range"))
+                })?,
+            DateArg::Month(month) => Period::month(month).to,
+            DateArg::LastMonth => today.replace_day(1).unwrap() - 1.days(),
+        })
+    }
+}
+
+/// Date in ISO8601 format, `today`, `yesterday`, a weekday, a number of days or weeks ago like
+/// `-3d` or `-2w`, a month in YYYY-MM format or `last-month`
+pub fn relaxed_date_value_parser(v: &str) -> Result<DateArg> {
+    let v = v.trim();
+    match v.to_lowercase().as_str() {
+        "today" => return Ok(DateArg::Today),
+        "yesterday" => return Ok(DateArg::Yesterday),
+        "last-month" => return Ok(DateArg::LastMonth),
+        _ => {}
+    }
+    if let Ok(date) = date_value_parser(v) {
+        return Ok(DateArg::Date(date));
+    }
+    if let Some(ago) = v.strip_prefix('-') {
+        let (number, days) = if let Some(number) = ago.strip_suffix('d') {
+            (number, 1)
+        } else if let Some(number) = ago.strip_suffix('w') {
+            (number, 7)
+        } else {
+            bail!("Expected number of days or weeks ago, e.g. -3d or -2w");
+        };
+        let number: i64 = number
+            .parse()
+            .map_err(|_| anyhow!("Invalid number of days or weeks: \"{number}\""))?;
+        let days = number
+            .checked_mul(days)
+            .ok_or_else(|| WlogError::Validation(format!("Too many days or weeks ago: \"{v}\"")))?;
+        return Ok(DateArg::DaysAgo(days));
+    }
+    if let Ok(month) = year_month_value_parser(v) {
+        return Ok(DateArg::Month(month));
+    }
+    if let Some(weekday) = locale::parse_weekday(v) {
+        return Ok(DateArg::Weekday(weekday));
+    }
+    bail!(
+        "Invalid date: \"{v}\", expected YYYY-MM-DD, YYYY-MM, a weekday, -3d, today, yesterday or last-month"
+    )
+}
+
+pub fn year_month_value_parser(v: &str) -> Result<Date> {
+    let (year, month) = v
+        .split_once('-')
+        .ok_or_else(|| anyhow!("Expected month in YYYY-MM format"))?;
+    let month = time::Month::try_from(month.parse::<u8>()?)?;
+    Date::from_calendar_date(year.parse()?, month, 1).map_err(Into::into)
+}
+
+/// Durations in hours, minutes and seconds, e.g. `1h30m`, or in days and weeks of five days with
+/// the configured length of a day, e.g. `1d4h`. Bare numbers are hours. Other grammars are
+/// accepted once enabled in the configuration, see [`crate::parsers`].
 pub fn duration_value_parser(v: &str) -> Result<Duration> {
-    let mut unit = 60;
-    let mut result = None;
-    let mut number = None;
-    for c in v.chars() {
-        match c {
-            '0'..='9' => number = Some(number.unwrap_or(0) * 10 + (c as u8 - b'0') as i64),
-            'h' => {
-                let res = result.unwrap_or(0);
-                let acc = number.ok_or_else(|| anyhow!("Number expected before unit"))?;
-                result = Some(res + acc * 60);
-                number = None;
-                unit = 1;
+    parsers::parse_duration(
+        v,
+        crate::config::duration_syntaxes,
+        crate::config::day_duration,
+    )
+}
+
+/// Parsed duration along with the input it was parsed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeArg {
+    pub input: String,
+    pub duration: Duration,
+}
+
+impl TimeArg {
+    /// Durations between `min` and `max` the input might have been meant as, e.g. 8h or 1h20m for
+    /// "80" parsed as 80h
+    pub fn alternatives(&self, min: Duration, max: Duration) -> Vec<Duration> {
+        let mut candidates = Vec::new();
+        if self.input.chars().all(|c| c.is_ascii_digit()) {
+            // Hours and minutes without a separator
+            for split in 1..self.input.len() {
+                let (hours, minutes) = self.input.split_at(split);
+                if let (Ok(hours), Ok(minutes)) = (hours.parse(), minutes.parse())
+                    && minutes < 60
+                {
+                    candidates.push(Duration::hours(hours) + Duration::minutes(minutes));
+                }
+            }
+        }
+        // Unit off by one step, minutes instead of hours or the other way around
+        candidates.push(self.duration / 60);
+        candidates.push(self.duration * 60);
+
+        let mut alternatives = Vec::new();
+        for candidate in candidates {
+            if candidate >= min
+                && candidate <= max
+                && candidate != self.duration
+                && !alternatives.contains(&candidate)
+            {
+                alternatives.push(candidate);
             }
-            'm' => {
-                let res = result.unwrap_or(0);
-                let acc = number.ok_or_else(|| anyhow!("Number expected before unit"))?;
-                result = Some(acc + res);
-                number = None;
-                unit = 0;
+        }
+        alternatives
+    }
+}
+
+pub fn time_arg_value_parser(v: &str) -> Result<TimeArg> {
+    Ok(TimeArg {
+        input: v.to_string(),
+        duration: duration_value_parser(v)?,
+    })
+}
+
+pub fn weekday_value_parser(v: &str) -> Result<Weekday> {
+    locale::parse_weekday(v).ok_or_else(|| anyhow!("Invalid weekday: \"{v}\""))
+}
+
+pub fn month_value_parser(v: &str) -> Result<time::Month> {
+    if let Ok(n) = v.parse::<u8>() {
+        return time::Month::try_from(n).map_err(Into::into);
+    }
+    locale::parse_month(v).ok_or_else(|| anyhow!("Invalid month: \"{v}\""))
+}
+
+/// Period compared with the shown one by `show --compare`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparedPeriod {
+    /// Calendar month before the start of the shown period
+    LastMonth,
+    /// Calendar month containing the date
+    Month(Date),
+}
+
+impl ComparedPeriod {
+    pub fn to_period(self, shown: &Period) -> Period {
+        match self {
+            ComparedPeriod::LastMonth => {
+                Period::month(shown.from.replace_day(1).unwrap() - 1.days())
             }
-            unexpected => bail!("Unexpected character in duration: '{unexpected}'"),
+            ComparedPeriod::Month(month) => Period::month(month),
         }
     }
-    if let Some(number) = number
-        && unit == 0
-    {
-        bail!("Unable to parse duration, unknown unit for value {number}",);
+}
+
+pub fn compared_period_value_parser(v: &str) -> Result<ComparedPeriod> {
+    if v == "last-month" {
+        return Ok(ComparedPeriod::LastMonth);
     }
-    let minutes = match (result, number) {
-        (Some(r), Some(n)) => r + n * unit,
-        (Some(r), None) => r,
-        (None, Some(n)) => n * unit,
-        (None, None) => bail!("Number expected"),
-    };
+    year_month_value_parser(v)
+        .map(ComparedPeriod::Month)
+        .map_err(|_| anyhow!("Expected \"last-month\" or a month in YYYY-MM format"))
+}
 
-    Ok(Duration::minutes(minutes))
+/// Index of a task in the list printed by `wlog recent`, prefixed with `@`, e.g. `@3`
+pub fn recent_task_value_parser(v: &str) -> Result<usize> {
+    let index = v
+        .strip_prefix('@')
+        .ok_or_else(|| anyhow!("Expected recent task index like @3"))?;
+    match index.parse() {
+        Ok(0) | Err(_) => bail!("Invalid recent task index: \"{index}\", counting starts at 1"),
+        Ok(index) => Ok(index),
+    }
 }
 
-pub fn weekday_value_parser(v: &str) -> Result<Weekday> {
-    let weekday = match v.to_lowercase().as_str() {
-        "mon" | "monday" => Weekday::Monday,
-        "tue" | "tuesday" => Weekday::Tuesday,
-        "wed" | "wednesday" => Weekday::Wednesday,
-        "thu" | "thursday" => Weekday::Thursday,
-        "fri" | "friday" => Weekday::Friday,
-        "sat" | "saturday" => Weekday::Saturday,
-        "sun" | "sunday" => Weekday::Sunday,
-        _ => bail!("Invalid weekday: \"{v}\""),
-    };
-    Ok(weekday)
+/// Piece of a split log entry, duration and target task separated by a colon, e.g. `2h:#123`
+pub fn split_piece_value_parser(v: &str) -> Result<(Duration, String)> {
+    let (duration, target) = v
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Expected duration:target, e.g. 2h:#123"))?;
+    if target.is_empty() {
+        bail!("Missing target task of piece \"{v}\"");
+    }
+    Ok((duration_value_parser(duration)?, target.to_string()))
+}
+
+/// Split an issue URL like `https://github.com/org/repo/issues/123` into the project URL and the
+/// issue number. Supports GitLab `/-/issues/` paths, as well as Jira `/browse/PROJ-123` and
+/// YouTrack `/issue/PROJ-123` paths with issue keys.
+pub fn parse_issue_url(v: &str) -> Option<(&str, i32)> {
+    if !v.starts_with("http://") && !v.starts_with("https://") {
+        return None;
+    }
+    let v = v.split(['?', '#']).next()?.trim_end_matches('/');
+    if let Some((project, number)) = v.rsplit_once("/issues/") {
+        let project = project.strip_suffix("/-").unwrap_or(project);
+        return Some((project, number.parse().ok()?));
+    }
+    let (project, key) = v
+        .rsplit_once("/browse/")
+        .or_else(|| v.rsplit_once("/issue/"))?;
+    let (_, number) = key.rsplit_once('-')?;
+    Some((project, number.parse().ok()?))
 }
 
 #[cfg(test)]
@@ -232,6 +522,140 @@ mod tests {
         );
     }
 
+    #[test]
+    fn date_arg_group_rollover() {
+        let config = Config::default();
+        let at = |year, month, day| {
+            OffsetDateTime::new_utc(
+                Date::from_calendar_date(year, month, day).unwrap(),
+                Time::from_hms(12, 0, 0).unwrap(),
+            )
+        };
+        let group = |day, month| DateArgGroup {
+            day: Some(day),
+            month,
+            ..Default::default()
+        };
+
+        // The previous month, not the one before it
+        let now = at(2025, Month::March, 1);
+        assert_eq!(
+            group(28, None).to_date(&config, now).unwrap(),
+            Date::from_calendar_date(2025, Month::February, 28).unwrap()
+        );
+        assert!(group(31, None).to_date(&config, now).is_err());
+        assert!(
+            group(28, None)
+                .is_ambiguous(group(28, None).to_date(&config, now).unwrap(), now.date())
+        );
+        assert!(!group(1, None).is_ambiguous(now.date(), now.date()));
+
+        // Across the year boundary
+        let now = at(2025, Month::January, 2);
+        assert_eq!(
+            group(31, None).to_date(&config, now).unwrap(),
+            Date::from_calendar_date(2024, Month::December, 31).unwrap()
+        );
+        assert_eq!(
+            group(5, Some(Month::December))
+                .to_date(&config, now)
+                .unwrap(),
+            Date::from_calendar_date(2024, Month::December, 5).unwrap()
+        );
+        assert_eq!(
+            group(2, Some(Month::January))
+                .to_date(&config, now)
+                .unwrap(),
+            now.date()
+        );
+    }
+
+    #[test]
+    fn period_arg_group() {
+        let today = Date::from_calendar_date(2025, Month::March, 12).unwrap();
+        let date = |month, day| Date::from_calendar_date(2025, month, day).unwrap();
+        let period = |from, to| Some(Period { from, to });
+
+        let group = PeriodArgGroup::default();
+        assert_eq!(
+            group.resolve(today).unwrap(),
+            period(date(Month::February, 1), date(Month::February, 28))
+        );
+
+        let group = PeriodArgGroup {
+            from: Some(DateArg::Date(date(Month::March, 3))),
+            ..Default::default()
+        };
+        assert_eq!(
+            group.resolve(today).unwrap(),
+            period(date(Month::March, 3), today)
+        );
+
+        let group = PeriodArgGroup {
+            to: Some(DateArg::Month(date(Month::January, 1))),
+            ..Default::default()
+        };
+        assert_eq!(
+            group.resolve(today).unwrap(),
+            Some(Period::until(date(Month::January, 31)))
+        );
+
+        let group = PeriodArgGroup {
+            year: Some(2024),
+            ..Default::default()
+        };
+        assert_eq!(
+            group.resolve(today).unwrap(),
+            period(
+                Date::from_calendar_date(2024, Month::January, 1).unwrap(),
+                Date::from_calendar_date(2024, Month::December, 31).unwrap()
+            )
+        );
+
+        // Shortcuts take precedence over each other in a fixed order
+        let group = PeriodArgGroup {
+            all: true,
+            today: true,
+            ..Default::default()
+        };
+        assert_eq!(group.resolve(today).unwrap(), None);
+        let group = PeriodArgGroup {
+            today: true,
+            week: true,
+            year: Some(2024),
+            ..Default::default()
+        };
+        assert_eq!(group.resolve(today).unwrap(), period(today, today));
+    }
+
+    #[test]
+    fn relaxed_date_parser() {
+        // Sunday
+        let today = Date::from_calendar_date(2025, Month::January, 26).unwrap();
+        let date =
+            |v| Date::parse(v, &time::format_description::well_known::Iso8601::DATE).unwrap();
+        let data = [
+            ("2025-01-10", "2025-01-10", "2025-01-10"),
+            ("today", "2025-01-26", "2025-01-26"),
+            ("monday", "2025-01-20", "2025-01-20"),
+            ("Sun", "2025-01-19", "2025-01-19"),
+            ("-3d", "2025-01-23", "2025-01-23"),
+            ("-2w", "2025-01-12", "2025-01-12"),
+            ("2024-02", "2024-02-01", "2024-02-29"),
+            ("last-month", "2024-12-01", "2024-12-31"),
+        ];
+        for (input, first, last) in data {
+            let arg = relaxed_date_value_parser(input).unwrap();
+            assert_eq!(arg.first(today).unwrap(), date(first), "{input}");
+            assert_eq!(arg.last(today).unwrap(), date(last), "{input}");
+        }
+        assert!(relaxed_date_value_parser("-3").is_err());
+        assert!(relaxed_date_value_parser("-9223372036854775807w").is_err());
+        let far = relaxed_date_value_parser("-999999999999d").unwrap();
+        assert!(far.last(today).is_err());
+        assert!(relaxed_date_value_parser("someday").is_err());
+    }
+
     #[test]
     fn duration_parser() {
         let data = [
@@ -252,6 +676,18 @@ mod tests {
             let parsed = duration_value_parser(input).ok();
             assert_eq!(parsed, minutes.map(Duration::minutes));
         }
+
+        let data = [
+            ("90s", Some(90)),
+            ("1m30s", Some(90)),
+            ("1h0m5s", Some(60 * 60 + 5)),
+            ("1m30", None),
+            ("s", None),
+        ];
+        for (input, seconds) in data {
+            let parsed = duration_value_parser(input).ok();
+            assert_eq!(parsed, seconds.map(Duration::seconds));
+        }
     }
 
     #[test]
@@ -273,10 +709,165 @@ mod tests {
             ("fri", Some(Weekday::Friday)),
             ("sat", Some(Weekday::Saturday)),
             ("sun", Some(Weekday::Sunday)),
+            ("Mo", Some(Weekday::Monday)),
+            ("di", Some(Weekday::Tuesday)),
+            ("Mittwoch", Some(Weekday::Wednesday)),
+            ("do", Some(Weekday::Thursday)),
+            ("so", Some(Weekday::Sunday)),
         ];
         for (input, output) in data {
             let parsed = weekday_value_parser(input).ok();
             assert_eq!(parsed, output);
         }
     }
+
+    #[test]
+    fn year_month_parser() {
+        let data = [
+            ("2025-01", Some((2025, Month::January))),
+            ("2024-12", Some((2024, Month::December))),
+            ("2024-13", None),
+            ("2024", None),
+            ("2024-", None),
+        ];
+        for (input, output) in data {
+            let parsed = year_month_value_parser(input).ok();
+            let expected =
+                output.map(|(year, month)| Date::from_calendar_date(year, month, 1).unwrap());
+            assert_eq!(parsed, expected);
+        }
+    }
+
+    #[test]
+    fn utc_offset_parser() {
+        let data = [
+            ("+02:00", Some((2, 0))),
+            ("-05:30", Some((-5, -30))),
+            ("+9", Some((9, 0))),
+            ("02:00", None),
+            ("+2:75", None),
+            ("", None),
+        ];
+        for (input, output) in data {
+            let parsed = utc_offset_value_parser(input).ok();
+            let expected = output.map(|(h, m)| time::UtcOffset::from_hms(h, m, 0).unwrap());
+            assert_eq!(parsed, expected);
+        }
+    }
+
+    #[test]
+    fn month_parser() {
+        let data = [
+            ("1", Some(Month::January)),
+            ("12", Some(Month::December)),
+            ("13", None),
+            ("march", Some(Month::March)),
+            ("März", Some(Month::March)),
+            ("oct", Some(Month::October)),
+            ("Oktober", Some(Month::October)),
+            ("Mai", Some(Month::May)),
+            ("ju", None),
+            ("", None),
+        ];
+        for (input, output) in data {
+            let parsed = month_value_parser(input).ok();
+            assert_eq!(parsed, output);
+        }
+    }
+
+    #[test]
+    fn issue_url() {
+        assert_eq!(
+            parse_issue_url("https://github.com/org/repo/issues/123"),
+            Some(("https://github.com/org/repo", 123))
+        );
+        assert_eq!(
+            parse_issue_url("https://gitlab.com/group/sub/repo/-/issues/7/#note_1"),
+            Some(("https://gitlab.com/group/sub/repo", 7))
+        );
+        assert_eq!(
+            parse_issue_url("https://example.atlassian.net/browse/PROJ-42"),
+            Some(("https://example.atlassian.net", 42))
+        );
+        assert_eq!(
+            parse_issue_url("https://example.youtrack.cloud/issue/WL-7/"),
+            Some(("https://example.youtrack.cloud", 7))
+        );
+        assert_eq!(parse_issue_url("123"), None);
+        assert_eq!(parse_issue_url("https://github.com/org/repo/pull/5"), None);
+        assert_eq!(
+            parse_issue_url("https://github.com/org/repo/issues/x"),
+            None
+        );
+    }
+
+    #[test]
+    fn compared_period() {
+        let shown = Period::month(Date::from_calendar_date(2025, Month::January, 1).unwrap());
+        let compared = compared_period_value_parser("last-month")
+            .unwrap()
+            .to_period(&shown);
+        assert_eq!(
+            (compared.from, compared.to),
+            (
+                Date::from_calendar_date(2024, Month::December, 1).unwrap(),
+                Date::from_calendar_date(2024, Month::December, 31).unwrap()
+            )
+        );
+        assert_eq!(
+            compared_period_value_parser("2024-02").unwrap(),
+            ComparedPeriod::Month(Date::from_calendar_date(2024, Month::February, 1).unwrap())
+        );
+        assert!(compared_period_value_parser("yesterday").is_err());
+    }
+
+    #[test]
+    fn time_arg_alternatives() {
+        let (min, max) = (Duration::minutes(5), Duration::hours(16));
+        let time = time_arg_value_parser("80").unwrap();
+        assert_eq!(
+            time.alternatives(min, max),
+            [Duration::hours(8), Duration::minutes(80)]
+        );
+        let time = time_arg_value_parser("830").unwrap();
+        assert_eq!(
+            time.alternatives(min, max),
+            [Duration::minutes(8 * 60 + 30), Duration::minutes(830)]
+        );
+        let time = time_arg_value_parser("3m").unwrap();
+        assert_eq!(time.alternatives(min, max), [Duration::hours(3)]);
+        let time = time_arg_value_parser("100h").unwrap();
+        assert_eq!(time.alternatives(min, max), [Duration::minutes(100)]);
+    }
+
+    #[test]
+    fn date_time() {
+        let date = Date::from_calendar_date(2025, Month::January, 10).unwrap();
+        assert_eq!(date_time_value_parser("2025-01-10").unwrap(), (date, None));
+        assert_eq!(
+            date_time_value_parser("2025-01-10 14:00").unwrap(),
+            (date, Some(Time::from_hms(14, 0, 0).unwrap()))
+        );
+        assert_eq!(
+            date_time_value_parser("2025-01-10T09:15:30").unwrap(),
+            (date, Some(Time::from_hms(9, 15, 30).unwrap()))
+        );
+        assert!(date_time_value_parser("2025-01-10 25:00").is_err());
+        assert!(date_time_value_parser("2025-01-10 14").is_err());
+    }
+
+    #[test]
+    fn split_piece() {
+        assert_eq!(
+            split_piece_value_parser("2h:#123").unwrap(),
+            (Duration::hours(2), "#123".to_string())
+        );
+        assert_eq!(
+            split_piece_value_parser("1h30m:Code review").unwrap(),
+            (Duration::minutes(90), "Code review".to_string())
+        );
+        assert!(split_piece_value_parser("2h").is_err());
+        assert!(split_piece_value_parser("2h:").is_err());
+        assert!(split_piece_value_parser("x:#1").is_err());
+    }
 }
@@ -1,12 +1,23 @@
-use super::common::time_value_parser;
+use super::common::{
+    duration_value_parser, time_value_parser, utc_offset_value_parser, weekday_value_parser,
+};
 use crate::Config;
+use crate::config::{DurationFormat, Precision, TableStyle, fmt_syntaxes};
+use crate::hooks::Event;
+use crate::locale::Locale;
+use crate::parsers::DurationSyntax;
+use crate::utils::fmt_duration;
 use clap::Subcommand;
 use eyre::Result;
 use std::path::PathBuf;
-use time::Time;
+use time::{Duration, Time, Weekday};
 
 #[derive(Debug, Subcommand)]
 pub enum ConfigCmd {
+    /// Show all effective settings
+    Show,
+    /// Open configuration file in $EDITOR
+    Edit,
     /// Get or set data path option
     DataPath { new_path: Option<PathBuf> },
     /// Get or set day change threshold option
@@ -14,13 +25,106 @@ pub enum ConfigCmd {
         #[arg(value_parser = time_value_parser)]
         new_threshold: Option<time::Time>,
     },
+    /// Get or set language of weekday and month names
+    Locale { new_locale: Option<Locale> },
+    /// Get or set time of day for the unlogged time reminder
+    ReminderTime {
+        #[arg(value_parser = time_value_parser)]
+        new_time: Option<time::Time>,
+    },
+    /// Get or set smallest unit of logged durations
+    Precision { new_precision: Option<Precision> },
+    /// Get or set fixed UTC offset used instead of the system time zone, e.g. +02:00
+    Timezone {
+        #[arg(value_parser = utc_offset_value_parser, allow_hyphen_values = true)]
+        new_offset: Option<time::UtcOffset>,
+        /// Use the system time zone
+        #[arg(long, conflicts_with = "new_offset")]
+        system: bool,
+    },
+    /// Get or set maximum total duration logged per day
+    MaxDailyMinutes {
+        #[arg(value_parser = duration_value_parser)]
+        new_max: Option<Duration>,
+    },
+    /// Get or set length of a day in durations given in days, e.g. `1d`
+    DayMinutes {
+        #[arg(value_parser = duration_value_parser)]
+        new_length: Option<Duration>,
+    },
+    /// Get or set duration above which logging asks to confirm the intended duration
+    SuspiciousMinutes {
+        #[arg(value_parser = duration_value_parser)]
+        new_threshold: Option<Duration>,
+    },
+    /// Get or set border style of printed tables
+    TableStyle { new_style: Option<TableStyle> },
+    /// Get or set display of durations in tables, totals and exports
+    DurationFormat { new_format: Option<DurationFormat> },
+    /// Get or set grammars of duration input accepted besides units like 1h30m, e.g.
+    /// "clock,minutes" for 1:30 and 90'
+    DurationSyntaxes {
+        #[arg(value_delimiter = ',')]
+        new_syntaxes: Vec<DurationSyntax>,
+        /// Only accept units
+        #[arg(long, conflicts_with = "new_syntaxes")]
+        unset: bool,
+    },
+    /// Get or set whether `show` prints the logging streak
+    StreakBadge { enabled: Option<bool> },
+    /// Get or set whether every command warns about an unlogged previous workday
+    Nag { enabled: Option<bool> },
+    /// Get or set URL notified with a JSON POST request of every added or updated log entry
+    OnLog {
+        new_url: Option<String>,
+        /// Stop sending log entries
+        #[arg(long, conflicts_with = "new_url")]
+        unset: bool,
+    },
+    /// Get or set weekday from which the first command of the week prints the weekly summary
+    AutoSummary {
+        #[arg(value_parser = weekday_value_parser)]
+        new_day: Option<Weekday>,
+        /// Also copy the summary to the clipboard
+        #[arg(long, requires = "new_day")]
+        copy: bool,
+        /// Stop printing the weekly summary
+        #[arg(long, conflicts_with = "new_day")]
+        unset: bool,
+    },
+    /// Get or set number of days after which log entries can only be modified with --force
+    EditGraceDays {
+        new_days: Option<u32>,
+        /// Allow modifying entries of any age
+        #[arg(long, conflicts_with = "new_days")]
+        unset: bool,
+    },
+    /// Get or set whether dates given with --day that fall into another month are confirmed
+    ConfirmDate { enabled: Option<bool> },
+    /// Get or set command run with a JSON payload on stdin when the event happens
+    Hook {
+        event: Event,
+        new_command: Option<String>,
+        /// Stop running a command on the event
+        #[arg(long, conflicts_with = "new_command")]
+        unset: bool,
+    },
+    /// Get or set file appended with a JSON line for every change a command makes to the database
+    LogFile {
+        new_path: Option<PathBuf>,
+        /// Stop logging database changes
+        #[arg(long, conflicts_with = "new_path")]
+        unset: bool,
+    },
     /// Reset to default configuration
     Reset,
 }
 
 impl ConfigCmd {
-    pub fn dispatch(self) -> Result<()> {
+    pub fn dispatch(self, config: Config) -> Result<()> {
         match self {
+            ConfigCmd::Show => config.print(),
+            ConfigCmd::Edit => Config::edit()?,
             ConfigCmd::DataPath { new_path } => match new_path {
                 None => {
                     let data_path = Config::read()?.unwrap_or_default().data_path;
@@ -42,6 +146,154 @@ impl ConfigCmd {
                     Config::update_day_change_threshold(new_threshold)?;
                 }
             },
+            ConfigCmd::Locale { new_locale } => match new_locale {
+                None => println!("{}", Config::read()?.unwrap_or_default().locale()),
+                Some(new_locale) => {
+                    Config::update_locale(new_locale)?;
+                }
+            },
+            ConfigCmd::ReminderTime { new_time } => match new_time {
+                None => println!("{}", Config::read()?.unwrap_or_default().reminder_time()),
+                Some(new_time) => {
+                    Config::update_reminder_time(new_time)?;
+                }
+            },
+            ConfigCmd::Precision { new_precision } => match new_precision {
+                None => println!("{}", Config::read()?.unwrap_or_default().precision()),
+                Some(new_precision) => {
+                    Config::update_precision(new_precision)?;
+                }
+            },
+            ConfigCmd::Timezone { new_offset, system } => match (new_offset, system) {
+                (None, false) => match Config::read()?.unwrap_or_default().timezone {
+                    Some(offset) => println!("UTC{offset}"),
+                    None => println!("system"),
+                },
+                (new_offset, _) => {
+                    Config::update_timezone(new_offset)?;
+                }
+            },
+            ConfigCmd::MaxDailyMinutes { new_max } => match new_max {
+                None => println!(
+                    "{}",
+                    fmt_duration(Config::read()?.unwrap_or_default().max_daily_duration())
+                ),
+                Some(new_max) => {
+                    Config::update_max_daily_duration(new_max)?;
+                }
+            },
+            ConfigCmd::DayMinutes { new_length } => match new_length {
+                None => println!(
+                    "{}",
+                    fmt_duration(Config::read()?.unwrap_or_default().day_duration())
+                ),
+                Some(new_length) => {
+                    Config::update_day_duration(new_length)?;
+                }
+            },
+            ConfigCmd::SuspiciousMinutes { new_threshold } => match new_threshold {
+                None => println!(
+                    "{}",
+                    fmt_duration(Config::read()?.unwrap_or_default().suspicious_duration())
+                ),
+                Some(new_threshold) => {
+                    Config::update_suspicious_duration(new_threshold)?;
+                }
+            },
+            ConfigCmd::DurationFormat { new_format } => match new_format {
+                None => println!("{}", Config::read()?.unwrap_or_default().duration_format()),
+                Some(new_format) => {
+                    Config::update_duration_format(new_format)?;
+                }
+            },
+            ConfigCmd::DurationSyntaxes {
+                new_syntaxes,
+                unset,
+            } => {
+                if new_syntaxes.is_empty() && !unset {
+                    let config = Config::read()?.unwrap_or_default();
+                    println!("{}", fmt_syntaxes(config.duration_syntaxes()));
+                } else {
+                    Config::update_duration_syntaxes(new_syntaxes)?;
+                }
+            }
+            ConfigCmd::TableStyle { new_style } => match new_style {
+                None => println!("{}", Config::read()?.unwrap_or_default().table_style()),
+                Some(new_style) => {
+                    Config::update_table_style(new_style)?;
+                }
+            },
+            ConfigCmd::StreakBadge { enabled } => match enabled {
+                None => println!("{}", Config::read()?.unwrap_or_default().streak_badge()),
+                Some(enabled) => {
+                    Config::update_streak_badge(enabled)?;
+                }
+            },
+            ConfigCmd::Nag { enabled } => match enabled {
+                None => println!("{}", Config::read()?.unwrap_or_default().nag()),
+                Some(enabled) => {
+                    Config::update_nag(enabled)?;
+                }
+            },
+            ConfigCmd::AutoSummary {
+                new_day,
+                copy,
+                unset,
+            } => match (new_day, unset) {
+                (None, false) => match Config::read()?.unwrap_or_default().auto_summary {
+                    Some(day) => println!("{}", day.to_string().to_lowercase()),
+                    None => println!("-"),
+                },
+                (new_day, _) => {
+                    Config::update_auto_summary(new_day, copy)?;
+                }
+            },
+            ConfigCmd::OnLog { new_url, unset } => match (new_url, unset) {
+                (None, false) => match Config::read()?.unwrap_or_default().on_log {
+                    Some(url) => println!("{url}"),
+                    None => println!("-"),
+                },
+                (new_url, _) => {
+                    Config::update_on_log(new_url)?;
+                }
+            },
+            ConfigCmd::EditGraceDays { new_days, unset } => match (new_days, unset) {
+                (None, false) => match Config::read()?.unwrap_or_default().edit_grace_days {
+                    Some(days) => println!("{days}"),
+                    None => println!("-"),
+                },
+                (new_days, _) => {
+                    Config::update_edit_grace_days(new_days)?;
+                }
+            },
+            ConfigCmd::ConfirmDate { enabled } => match enabled {
+                None => println!("{}", Config::read()?.unwrap_or_default().confirm_date()),
+                Some(enabled) => {
+                    Config::update_confirm_date(enabled)?;
+                }
+            },
+            ConfigCmd::Hook {
+                event,
+                new_command,
+                unset,
+            } => match (new_command, unset) {
+                (None, false) => {
+                    let config = Config::read()?.unwrap_or_default();
+                    println!("{}", config.hook(event).unwrap_or("-"));
+                }
+                (new_command, _) => {
+                    Config::update_hook(event, new_command)?;
+                }
+            },
+            ConfigCmd::LogFile { new_path, unset } => match (new_path, unset) {
+                (None, false) => match Config::read()?.unwrap_or_default().log_file {
+                    Some(path) => println!("{}", path.display()),
+                    None => println!("-"),
+                },
+                (new_path, _) => {
+                    Config::update_log_file(new_path)?;
+                }
+            },
             ConfigCmd::Reset => Config::reset()?,
         }
         Ok(())
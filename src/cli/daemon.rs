@@ -0,0 +1,21 @@
+use crate::{Config, daemon};
+use clap::Args;
+use eyre::Result;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct DaemonCmd {
+    /// Socket path, next to the database file by default. Methods: "log" with seconds, task,
+    /// issue, date and force params, and "today"
+    #[arg(long)]
+    socket: Option<PathBuf>,
+}
+
+impl DaemonCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let socket = self
+            .socket
+            .unwrap_or_else(|| config.data_path.with_extension("sock"));
+        daemon::serve(config, &socket)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::utils::OutputFormat;
+use crate::{Config, check, data};
+use clap::Subcommand;
+use eyre::Result;
+use owo_colors::OwoColorize;
+
+#[derive(Debug, Subcommand)]
+pub enum DataCmd {
+    /// Check database for inconsistencies and offer fixes
+    Check,
+    /// List applied and pending schema migrations without applying them
+    Version,
+    /// Apply pending schema migrations
+    Migrate,
+    /// Run a read-only SQL statement and print the result
+    Query {
+        sql: String,
+        /// Output format
+        #[arg(long, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+impl DataCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let path = config.data_path.as_ref();
+
+        match self {
+            DataCmd::Check => check::run(&mut data::open(path)?),
+            DataCmd::Version => data::print_version(&mut data::open_unmigrated(path)?, &config),
+            DataCmd::Migrate => {
+                match data::migrate(&mut data::open_unmigrated(path)?)? {
+                    0 => eprintln!("{} The database is up to date", "Info:".cyan()),
+                    n => eprintln!("{} Applied {n} migrations", "Success:".green().bold()),
+                }
+                Ok(())
+            }
+            DataCmd::Query { sql, format } => {
+                data::query(&mut data::open(path)?, &config, &sql, format)
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::common::{PeriodArgGroup, year_month_value_parser};
+use crate::log_entries::{EntryFilter, Period};
+use crate::{Config, data, export, projects};
+use clap::{Args, Subcommand};
+use eyre::Result;
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+use time::Date;
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct ExportCmd {
+    #[command(subcommand)]
+    command: Option<ExportSubcommand>,
+    /// Period
+    #[clap(flatten)]
+    period: PeriodArgGroup,
+    /// Replace task names and comments with hashes, salted differently for every export
+    #[arg(long)]
+    anonymize: bool,
+    /// Only export log entries added this way: cli, api, sync or import, optionally with the
+    /// tool like import:harvest
+    #[arg(long)]
+    source: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportSubcommand {
+    /// Write an Excel workbook with a sheet per week of the month and total formulas
+    Xlsx {
+        /// Month in YYYY-MM format, the current month by default
+        #[arg(long, value_parser = year_month_value_parser)]
+        month: Option<Date>,
+        /// Path of the workbook, wlog-YYYY-MM.xlsx in the current directory by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Only export log entries added this way, same as for CSV
+        #[arg(long)]
+        source: Option<String>,
+    },
+}
+
+impl ExportCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let now = config.now()?;
+        let project = projects::require_current(&mut conn, &config)?;
+
+        if let Some(ExportSubcommand::Xlsx {
+            month,
+            output,
+            source,
+        }) = self.command
+        {
+            let filter = source.map(|source| EntryFilter {
+                task: None,
+                source: Some(source),
+                changed_since: None,
+            });
+            let month = Period::month(month.unwrap_or(now.date()));
+            let output = output.unwrap_or_else(|| {
+                PathBuf::from(format!(
+                    "wlog-{}-{:02}.xlsx",
+                    month.from.year(),
+                    month.from.month() as u8
+                ))
+            });
+            export::export_xlsx(
+                &mut conn,
+                &config,
+                &project,
+                &month,
+                filter.as_ref(),
+                &output,
+            )?;
+            eprintln!(
+                "{} Exported {} to {}",
+                "Success:".green().bold(),
+                month.from.month(),
+                output.display()
+            );
+            return Ok(());
+        }
+
+        let period = self.period.to_period(&config, now)?;
+        let filter = self.source.map(|source| EntryFilter {
+            task: None,
+            source: Some(source),
+            changed_since: None,
+        });
+        export::export_csv(
+            &mut conn,
+            &config,
+            &project,
+            period.as_ref(),
+            filter.as_ref(),
+            self.anonymize,
+            &mut std::io::stdout().lock(),
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use crate::log_entries::Limits;
+use crate::{Config, data, import, webhook};
+use clap::Subcommand;
+use eyre::Result;
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum ImportCmd {
+    /// Import a Harvest time report export in CSV or JSON format
+    Harvest {
+        file: PathBuf,
+        /// Import even if a day total exceeds the configured daily maximum or a date is past the
+        /// edit grace period
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl ImportCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        match self {
+            ImportCmd::Harvest { file, force } => {
+                let limits = Limits::new(&config, force)?;
+                import::import_harvest(&mut conn, &config, &file, &limits)?
+            }
+        }
+        webhook::flush(&mut conn, &config)
+    }
+}
@@ -0,0 +1,100 @@
+use super::common::year_month_value_parser;
+use crate::log_entries::Period;
+use crate::utils::OutputFormat;
+use crate::{Config, data, invoices, projects};
+use clap::Subcommand;
+use diesel::Connection;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+use time::{Date, Duration};
+
+#[derive(Debug, Subcommand)]
+pub enum InvoiceCmd {
+    /// Invoice time logged up to the end of the month that isn't invoiced yet
+    Generate {
+        /// Month in YYYY-MM format
+        #[arg(long, value_parser = year_month_value_parser)]
+        month: Date,
+    },
+    /// List invoices of the default project
+    List {
+        /// Output format
+        #[arg(long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Show entries billed by an invoice
+    Show {
+        number: i32,
+        /// Output format
+        #[arg(long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Write entries billed by an invoice as CSV
+    Export {
+        number: i32,
+        /// Path of the CSV file, invoice-N.csv in the current directory by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl InvoiceCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let project = projects::require_current(&mut conn, &config)?;
+
+        match self {
+            InvoiceCmd::Generate { month } => {
+                let (invoice, items, overbilled) =
+                    conn.transaction(|conn| invoices::generate(conn, project.id, month))?;
+                let total: Duration = items.iter().map(|item| item.duration()).sum();
+                eprintln!(
+                    "{} Invoice {} created with {} entries, {} in total",
+                    "Success:".green().bold(),
+                    invoice.number,
+                    items.len(),
+                    config.fmt_duration(total)
+                );
+                let first = Period::month(month).from;
+                let late = items.iter().filter(|item| item.date < first).count();
+                if late > 0 {
+                    eprintln!(
+                        "{} {late} entries from before {} weren't invoiced yet and are included",
+                        "Note:".cyan(),
+                        invoice.month_str()
+                    );
+                }
+                for entry in overbilled {
+                    eprintln!(
+                        "{} {} on {} was invoiced for {} more than is logged now",
+                        "Warning:".yellow().bold(),
+                        entry.task_name,
+                        entry.date,
+                        config.fmt_duration(entry.duration)
+                    );
+                }
+                Ok(())
+            }
+            InvoiceCmd::List { format } => invoices::list(&mut conn, &config, project.id, format),
+            InvoiceCmd::Show { number, format } => {
+                invoices::show(&mut conn, &config, &project, number, format)
+            }
+            InvoiceCmd::Export { number, output } => {
+                let output =
+                    output.unwrap_or_else(|| PathBuf::from(format!("invoice-{number}.csv")));
+                // Loaded first, so that a missing invoice doesn't leave an empty file behind
+                let (_, items) = invoices::get(&mut conn, project.id, number)?;
+                let mut file = std::fs::File::create(&output)?;
+                invoices::export(&config, &items, &mut file)?;
+                eprintln!(
+                    "{} Invoice {number} exported to {}",
+                    "Success:".green().bold(),
+                    output.display()
+                );
+                Ok(())
+            }
+        }
+    }
+}
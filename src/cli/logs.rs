@@ -1,20 +1,137 @@
-use super::common::{DateArgGroup, PeriodArgGroup, duration_value_parser};
-use crate::{Config, data, log_entries, projects, tasks};
-use clap::{Args, ValueEnum};
-use eyre::Result;
-use time::{Duration, OffsetDateTime};
+use super::common::{
+    ComparedPeriod, DateArgGroup, PeriodArgGroup, TimeArg, compared_period_value_parser,
+    date_value_parser, duration_value_parser, parse_issue_url, recent_task_value_parser,
+    split_piece_value_parser, time_arg_value_parser,
+};
+use crate::error::WlogError;
+use crate::log_entries::{CsvTarget, EntryFilter, EntrySource, Limits, Period};
+use crate::projects::ProjectChoice;
+use crate::tasks::{TaskChoice, TaskFilter, TaskId};
+use crate::utils::{History, OutputFormat, fmt_duration, prompt_opt, yn_prompt};
+use crate::{
+    Config, balance, caps, data, hooks, log_entries, projects, schedule, streak, tasks, webhook,
+};
+use clap::{Args, Subcommand, ValueEnum};
+use diesel::{Connection, SqliteConnection};
+use eyre::{Result, anyhow, bail};
+use owo_colors::OwoColorize;
+use std::path::PathBuf;
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct LogCmd {
+    #[command(subcommand)]
+    command: Option<LogSubcommand>,
+    #[clap(flatten)]
+    add: AddLogCmd,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LogSubcommand {
+    /// Move log entries from one task to another
+    Move {
+        #[arg(long)]
+        from_task: i32,
+        #[arg(long)]
+        to_task: i32,
+        /// Only move entries starting from this date, string in ISO8601 format
+        #[arg(long, value_parser = date_value_parser)]
+        from_date: Option<Date>,
+        /// Only move entries up to this date, string in ISO8601 format
+        #[arg(long, value_parser = date_value_parser)]
+        to_date: Option<Date>,
+        /// Move entries past the edit grace period as well
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show log entries of the day with the values they replaced
+    History {
+        /// Date, string in ISO8601 format
+        #[arg(long, value_parser = date_value_parser)]
+        date: Date,
+    },
+    /// Replace a log entry with entries of other tasks
+    Split {
+        /// Date of the entry, string in ISO8601 format
+        #[arg(long, value_parser = date_value_parser)]
+        date: Date,
+        /// ID of the task the entry is logged for
+        #[arg(long)]
+        task: i32,
+        /// Comma-separated pieces as duration:target, where target is an issue like #123, a task
+        /// ID or a task name
+        #[arg(long, required = true, value_delimiter = ',', value_parser = split_piece_value_parser)]
+        into: Vec<(Duration, String)>,
+        /// Split even if the pieces don't add up to the original duration
+        #[arg(long)]
+        uneven: bool,
+        /// Split even if a day total exceeds the configured daily maximum or the entry is past the
+        /// edit grace period
+        #[arg(long)]
+        force: bool,
+    },
+}
 
 #[derive(Debug, Args)]
 pub struct AddLogCmd {
-    /// Duration in hours and minutes. Default unit is hours
-    #[arg(short, long, value_parser = duration_value_parser)]
-    time: Duration,
+    /// Duration in hours, minutes and seconds, or days and weeks, e.g. 1d. Default unit is hours.
+    /// Formats like 1:30 are accepted after enabling them with `wlog config duration-syntaxes`
+    // Optional only to allow `LogCmd` subcommands, required otherwise
+    #[arg(short, long, required = true, value_parser = time_arg_value_parser)]
+    time: Option<TimeArg>,
     /// Date
     #[clap(flatten)]
     date: DateArgGroup,
-    /// Link issue number
+    /// Link issue number or issue URL, which also selects the project by its URL
+    #[arg(short, long)]
+    issue: Option<String>,
+    /// Task name
+    #[arg(long)]
+    name: Option<String>,
+    /// Task from the list printed by `wlog recent`, e.g. @3 for the third one
+    #[arg(value_name = "@N", value_parser = recent_task_value_parser, conflicts_with_all = ["issue", "name"])]
+    recent: Option<usize>,
+    /// Log even if the day total exceeds the configured daily maximum, the date isn't a workday
+    /// of a flexible schedule or is past the edit grace period
+    #[arg(long)]
+    force: bool,
+    /// Don't ask to confirm unusually long or short durations or dates that aren't workdays, or
+    /// offer the task of the previous day
+    #[arg(short, long)]
+    yes: bool,
+    /// Replace the duration already logged for the task on the date
+    #[arg(long)]
+    set: bool,
+    /// Add to the duration already logged for the task on the date, the default
+    #[arg(long, conflicts_with = "set")]
+    add: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DistributeCmd {
+    /// Any date within the target week, string in ISO8601 format. Current week by default
+    #[arg(long, value_parser = date_value_parser)]
+    week: Option<Date>,
+    /// Total weekly duration in hours, minutes and seconds. Default unit is hours
+    #[arg(short, long, value_parser = duration_value_parser)]
+    time: Duration,
+    /// Link issue number or issue URL, which also selects the project by its URL
+    #[arg(short, long)]
+    issue: Option<String>,
+    /// Task name
+    #[arg(long)]
+    name: Option<String>,
+    /// Distribute even if the week is past the edit grace period
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct FillCmd {
+    /// Link issue number or issue URL, which also selects the project by its URL
     #[arg(short, long)]
-    issue: Option<i32>,
+    issue: Option<String>,
     /// Task name
     #[arg(long)]
     name: Option<String>,
@@ -31,6 +148,60 @@ pub struct ShowCmd {
     /// Include comments in the output
     #[arg(short, long)]
     comments: bool,
+    /// Add an empty row for every day of the period without entries
+    #[arg(long)]
+    fill: bool,
+    /// Only show entries of tasks with this issue number
+    #[arg(short, long, conflicts_with = "task")]
+    issue: Option<String>,
+    /// Only show entries of the task with this ID, or of tasks with names containing the text
+    #[arg(long)]
+    task: Option<String>,
+    /// Only show entries added this way: cli, api, sync or import, optionally with the tool like
+    /// import:harvest
+    #[arg(long)]
+    source: Option<String>,
+    /// Only show entries added or changed since `show` last listed all of the project's entries,
+    /// with `--all` or `--since-last` and no filters. Of every date unless a period is given
+    #[arg(long)]
+    since_last: bool,
+    /// Output format
+    #[arg(long, default_value = "table")]
+    format: OutputFormat,
+    /// Show a calendar shaded by time logged relative to the schedule instead of entries
+    #[arg(long, conflicts_with_all = ["comments", "fill", "issue", "task", "source", "since_last"])]
+    heatmap: bool,
+    /// Compare time per task with another period: "last-month" or a month in YYYY-MM format
+    #[arg(long, value_parser = compared_period_value_parser, conflicts_with_all = ["comments", "fill", "heatmap"])]
+    compare: Option<ComparedPeriod>,
+    /// Repeat the date in every row instead of grouping entries into per-day sections
+    #[arg(long, conflicts_with_all = ["heatmap", "compare", "top"])]
+    flat: bool,
+    /// Rank the N tasks with the most time, with shares of the total time
+    #[arg(long, value_name = "N", conflicts_with_all = ["comments", "fill", "heatmap", "compare"])]
+    top: Option<usize>,
+    /// Write the task summary CSV of `--by task` to this file instead of the clipboard
+    #[arg(long, value_name = "FILE")]
+    copy_to: Option<PathBuf>,
+    /// Print directly even if the output doesn't fit the terminal
+    #[arg(long)]
+    no_pager: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct LeftCmd {}
+
+#[derive(Debug, Args)]
+pub struct StreakCmd {}
+
+#[derive(Debug, Args)]
+pub struct ForecastCmd {}
+
+#[derive(Debug, Args)]
+pub struct RecentCmd {
+    /// Output format
+    #[arg(long, default_value = "table")]
+    format: OutputFormat,
 }
 
 #[derive(Debug, Default, Clone, Copy, ValueEnum)]
@@ -40,6 +211,7 @@ pub enum LogFormat {
     Day,
     #[clap(alias("issue"))]
     Task,
+    Label,
 }
 
 impl std::str::FromStr for LogFormat {
@@ -49,57 +221,750 @@ impl std::str::FromStr for LogFormat {
         match s.to_lowercase().as_str() {
             "issue" => Ok(LogFormat::Task),
             "day" => Ok(LogFormat::Day),
+            "label" => Ok(LogFormat::Label),
             _ => Err("Unknown log format"),
         }
     }
 }
 
+impl LogCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        match self.command {
+            None => self.add.dispatch(config),
+            Some(command) => command.dispatch(config),
+        }
+    }
+}
+
+impl LogSubcommand {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        match self {
+            LogSubcommand::Move {
+                from_task,
+                to_task,
+                from_date,
+                to_date,
+                force,
+            } => {
+                let period = Period {
+                    from: from_date.unwrap_or(Date::MIN),
+                    to: to_date.unwrap_or(Date::MAX),
+                };
+                let limits = Limits::new(&config, force)?;
+                let moved = conn.transaction(|conn| {
+                    let (from, to) = (TaskId(from_task), TaskId(to_task));
+                    let dates = log_entries::move_entries(conn, from, to, &period, &limits)?;
+                    for &date in &dates {
+                        webhook::enqueue(conn, &config, date, from)?;
+                        webhook::enqueue(conn, &config, date, to)?;
+                    }
+                    eyre::Ok(dates.len())
+                })?;
+                eprintln!(
+                    "{} Moved {moved} log entries from task {from_task} to task {to_task}",
+                    "Success:".green().bold()
+                );
+                webhook::flush(&mut conn, &config)
+            }
+            LogSubcommand::History { date } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                log_entries::history(&mut conn, &config, &project, date)
+            }
+            LogSubcommand::Split {
+                date,
+                task,
+                into,
+                uneven,
+                force,
+            } => {
+                let limits = Limits::new(&config, force)?;
+                let project = tasks::get(&mut conn, TaskId(task))?.project_id;
+                let project = projects::get(&mut conn, project)?;
+                let mut choices = Vec::new();
+                for (duration, target) in &into {
+                    let choice = piece_task(&mut conn, &config, &project, target)?;
+                    choices.push((config.precision().round(*duration), choice));
+                }
+                conn.transaction(|conn| {
+                    let mut pieces = Vec::new();
+                    for (duration, choice) in choices {
+                        pieces.push((duration, choice.get_or_create(conn, project.id)?));
+                    }
+                    log_entries::split_entry(
+                        conn,
+                        project.id,
+                        date,
+                        TaskId(task),
+                        &pieces,
+                        uneven,
+                        &limits,
+                    )?;
+                    webhook::enqueue(conn, &config, date, TaskId(task))?;
+                    for (_, piece) in &pieces {
+                        webhook::enqueue(conn, &config, date, *piece)?;
+                    }
+                    eprintln!(
+                        "{} Split entry of task {task} on {date} into {} entries",
+                        "Success:".green().bold(),
+                        pieces.len()
+                    );
+                    eyre::Ok(())
+                })?;
+                webhook::flush(&mut conn, &config)
+            }
+        }
+    }
+}
+
 impl AddLogCmd {
-    pub fn dispatch(self) -> Result<()> {
-        let config = Config::read()?.unwrap_or_default();
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let time = self.time.ok_or_else(|| anyhow!("Duration is required"))?;
+        let time = if self.yes {
+            time.duration
+        } else {
+            confirm_duration(&config, &time)?
+        };
 
         let mut conn = data::open(config.data_path.as_ref())?;
 
-        let now = OffsetDateTime::now_local()?;
+        let now = config.now()?;
         let date = self.date.to_date(&config, now)?;
-        let project = projects::get_default_or_create_interactive(&mut conn)?;
-
-        let issue = tasks::get_or_create_interactive(
-            &mut conn,
-            project.id,
-            self.issue,
-            self.name.as_deref(),
-        )?;
-
-        let entry = log_entries::LogEntry {
-            date,
-            duration: self.time,
-            task: issue,
+        if config.confirm_date()
+            && !self.yes
+            && self.date.is_ambiguous(date, now.date())
+            && !yn_prompt(&format!(
+                "Log to {} {date}?",
+                config.locale().weekday(date.weekday())
+            ))?
+        {
+            bail!(WlogError::Canceled("Logging aborted".into()));
+        }
+        // Checked before any prompts, entries are checked again when written
+        let limits = Limits::new(&config, self.force)?;
+        limits.check_date(date)?;
+        let (project, issue) = project_and_issue(&mut conn, &config, self.issue.as_deref())?;
+        let duration = config.precision().round(time);
+
+        // All questions are asked before writing, so the database isn't locked while waiting
+        let task = match project.existing() {
+            Some(existing) => {
+                // Catches off-by-one dates, e.g. from `--weekday`
+                let scheduled = schedule::scheduled_duration(&mut conn, existing.id, date)?;
+                if !self.force
+                    && !self.yes
+                    && scheduled.is_some_and(|scheduled| scheduled.is_zero())
+                    && !yn_prompt(&format!(
+                        "{} {} ({date}) is not a scheduled workday, log anyway?",
+                        "Warning:".yellow().bold(),
+                        config.locale().weekday(date.weekday())
+                    ))?
+                {
+                    bail!(WlogError::Canceled("Logging aborted".into()));
+                }
+
+                // Most days continue the work of the previous one
+                let previous = match (issue, &self.name, self.recent) {
+                    (None, None, None) if !self.yes => {
+                        log_entries::get_previous_task(&mut conn, existing.id, date)?
+                    }
+                    _ => None,
+                };
+                match previous {
+                    _ if let Some(n) = self.recent => {
+                        TaskChoice::Existing(recent_task(&mut conn, existing.id, n)?)
+                    }
+                    Some(task)
+                        if yn_prompt(&format!(
+                            "Log to the same task as the previous day ({})?",
+                            match task.issue {
+                                Some(n) => format!("{} {}", existing.issue_key(n), task.name),
+                                None => task.name.clone(),
+                            }
+                        ))? =>
+                    {
+                        TaskChoice::Existing(task.id)
+                    }
+                    _ => tasks::choose_interactive(
+                        &mut conn,
+                        Some(existing.id),
+                        issue,
+                        self.name.as_deref(),
+                    )?,
+                }
+            }
+            None if let Some(n) = self.recent => {
+                bail!(WlogError::NotFound(format!(
+                    "No recent task @{n}, see `wlog recent`"
+                )));
+            }
+            None => tasks::choose_interactive(&mut conn, None, issue, self.name.as_deref())?,
         };
 
-        log_entries::add_log(&mut conn, project.id, entry)?;
+        // Don't leave a new project or task behind if logging fails halfway
+        let hooks = conn.transaction(|conn| {
+            let project = project.get_or_create(conn)?;
+            let issue = task.get_or_create(conn, project.id)?;
 
-        Ok(())
+            let replaced = if self.set {
+                log_entries::get_duration(conn, date, issue)?
+            } else {
+                Duration::ZERO
+            };
+            let entry = log_entries::LogEntry {
+                date,
+                duration,
+                task: issue,
+                utc_offset: Some(now.offset()),
+                start: self.date.start_time(),
+                source: EntrySource::Cli,
+            };
+
+            if self.set {
+                log_entries::replace_log(conn, project.id, entry, &limits)?;
+            } else {
+                log_entries::add_log(conn, project.id, entry, &limits)?;
+            }
+            caps::warn_exceeded(conn, &config, project.id, date, issue)?;
+            webhook::enqueue(conn, &config, date, issue)?;
+            hooks::log_added(conn, &config, date, issue, duration - replaced)
+        })?;
+        hooks::run(hooks);
+        webhook::flush(&mut conn, &config)
+    }
+}
+
+/// Ask which duration was meant if the logged one is unusually long or short
+fn confirm_duration(config: &Config, time: &TimeArg) -> Result<Duration> {
+    let (min, max) = (Duration::minutes(5), config.suspicious_duration());
+    if time.duration >= min && time.duration <= max {
+        return Ok(time.duration);
+    }
+    let alternatives = time.alternatives(min, max);
+    eprintln!(
+        "{} \"{}\" is parsed as {}, which is unusually {}",
+        "Warning:".yellow().bold(),
+        time.input,
+        fmt_duration(time.duration),
+        if time.duration < min { "short" } else { "long" }
+    );
+    if alternatives.is_empty() {
+        if !yn_prompt("Log it anyway?")? {
+            bail!(WlogError::Canceled("Logging aborted".into()));
+        }
+        return Ok(time.duration);
+    }
+    eprintln!("Did you mean:");
+    for (i, alternative) in alternatives.iter().enumerate() {
+        eprintln!("  {}) {}", i + 1, fmt_duration(*alternative));
+    }
+    let choice = prompt_opt::<usize>("Number of the intended duration", History::Choice)?;
+    match choice {
+        None => Ok(time.duration),
+        Some(n) => alternatives
+            .get(n.wrapping_sub(1))
+            .copied()
+            .ok_or_else(|| WlogError::Validation(format!("No option {n}")).into()),
+    }
+}
+
+impl DistributeCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let now = config.now()?;
+        let week = self.week.unwrap_or_else(|| now.date());
+        let (project, issue) = project_and_issue(&mut conn, &config, self.issue.as_deref())?;
+        // A project that is yet to be created has no schedule either
+        let Some(project) = project.existing() else {
+            bail!(WlogError::NotFound(
+                "No schedule is set for the current project".into()
+            ));
+        };
+
+        let Some(schedule) = schedule::get(&mut conn, project.id)? else {
+            bail!(WlogError::NotFound(
+                "No schedule is set for the current project".into()
+            ));
+        };
+        if !schedule.is_flexible() {
+            bail!(WlogError::Validation(
+                "Weekly distribution is only available for flexible schedules".into()
+            ));
+        }
+        let total = config.precision().round(self.time);
+        let days = match schedule::get_rotation(&mut conn, project.id)? {
+            Some(rotation) => rotation.distribute(week, total),
+            None => schedule.distribute(week, total),
+        };
+        if days.is_empty() {
+            bail!(WlogError::Validation(
+                "The current schedule has no workdays".into()
+            ));
+        }
+        let limits = Limits::new(&config, self.force)?;
+        limits.check_date(days[0].0)?;
+
+        let task =
+            tasks::choose_interactive(&mut conn, Some(project.id), issue, self.name.as_deref())?;
+
+        let hooks = conn.transaction(|conn| {
+            let task = task.get_or_create(conn, project.id)?;
+            let mut hooks = Vec::new();
+            for &(date, duration) in &days {
+                let entry = log_entries::LogEntry {
+                    date,
+                    duration,
+                    task,
+                    utc_offset: Some(now.offset()),
+                    start: None,
+                    source: EntrySource::Cli,
+                };
+                log_entries::add_log(conn, project.id, entry, &limits)?;
+                webhook::enqueue(conn, &config, date, task)?;
+                hooks.extend(hooks::log_added(conn, &config, date, task, duration)?);
+                eprintln!(
+                    "{} Logged {} on {date}, {}",
+                    "Success:".green().bold(),
+                    config.fmt_duration(duration),
+                    config.locale().weekday(date.weekday())
+                );
+            }
+            // Weeks may cross into the next month
+            let (first, last) = (days[0].0, days[days.len() - 1].0);
+            caps::warn_exceeded(conn, &config, project.id, first, task)?;
+            if last.month() != first.month() {
+                caps::warn_exceeded(conn, &config, project.id, last, task)?;
+            }
+            eyre::Ok(hooks)
+        })?;
+        hooks::run(hooks);
+        webhook::flush(&mut conn, &config)
+    }
+}
+
+impl FillCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let now = config.now()?;
+        let date = if now.time() < config.day_change_threshold() {
+            now.date().previous_day().unwrap()
+        } else {
+            now.date()
+        };
+        let (project, issue) = project_and_issue(&mut conn, &config, self.issue.as_deref())?;
+        // A project that is yet to be created has no schedule either
+        let Some(project) = project.existing() else {
+            bail!(WlogError::NotFound(
+                "No schedule is set for the current project".into()
+            ));
+        };
+
+        let Some(scheduled) = schedule::scheduled_duration(&mut conn, project.id, date)? else {
+            bail!(WlogError::NotFound(
+                "No schedule is set for the current project".into()
+            ));
+        };
+        if scheduled.is_zero() {
+            bail!(WlogError::Validation(format!(
+                "{} ({date}) is not a scheduled workday",
+                config.locale().weekday(date.weekday())
+            )));
+        }
+        let logged = log_entries::get_day_total(&mut conn, project.id, date)?;
+        let left = scheduled - logged;
+        if !left.is_positive() {
+            bail!(WlogError::Validation(format!(
+                "{date} is already complete with {} of {} logged",
+                config.fmt_duration(logged),
+                config.fmt_duration(scheduled)
+            )));
+        }
+
+        let task =
+            tasks::choose_interactive(&mut conn, Some(project.id), issue, self.name.as_deref())?;
+
+        let hooks = conn.transaction(|conn| {
+            let task = task.get_or_create(conn, project.id)?;
+            let entry = log_entries::LogEntry {
+                date,
+                duration: left,
+                task,
+                utc_offset: Some(now.offset()),
+                start: None,
+                source: EntrySource::Cli,
+            };
+            log_entries::add_log(conn, project.id, entry, &Limits::new(&config, false)?)?;
+            eprintln!(
+                "{} Logged {} on {date}, completing its {} scheduled",
+                "Success:".green().bold(),
+                config.fmt_duration(left),
+                config.fmt_duration(scheduled)
+            );
+            caps::warn_exceeded(conn, &config, project.id, date, task)?;
+            webhook::enqueue(conn, &config, date, task)?;
+            hooks::log_added(conn, &config, date, task, left)
+        })?;
+        hooks::run(hooks);
+        webhook::flush(&mut conn, &config)
     }
 }
 
 impl ShowCmd {
-    pub fn dispatch(self) -> Result<()> {
-        let config = Config::read()?.unwrap_or_default();
+    pub fn dispatch(self, config: Config) -> Result<()> {
         let mut conn = data::open(config.data_path.as_ref())?;
 
-        let now = OffsetDateTime::now_local()?;
-        let period = self.period.to_period(&config, now);
+        let now = config.now()?;
+        let period = self.listed_period(&config, now)?;
 
-        let project = projects::get_default_or_create_interactive(&mut conn)?;
+        let project = projects::require_current(&mut conn, &config)?;
+        let last_shown = log_entries::get_last_shown(&mut conn, project.id)?;
+        if self.since_last && last_shown.is_none() {
+            eprintln!(
+                "{} Entries weren't shown before, showing all of them",
+                "Note:".cyan()
+            );
+        }
+        let changed_since = last_shown.filter(|_| self.since_last);
+
+        #[cfg(unix)]
+        let pager = if self.no_pager {
+            None
+        } else {
+            crate::pager::start()?
+        };
+
+        let lists_all = self.lists_all(period.as_ref());
+        let task = match (self.issue, self.task) {
+            (Some(issue), _) => Some(TaskFilter::Issue(project.parse_issue(&config, &issue)?)),
+            (None, Some(task)) => Some(match task.parse() {
+                Ok(id) => TaskFilter::Id(TaskId(id)),
+                Err(_) => TaskFilter::Name(task),
+            }),
+            (None, None) => None,
+        };
+        let filter = (task.is_some() || self.source.is_some() || changed_since.is_some())
+            .then_some(EntryFilter {
+                task,
+                source: self.source,
+                changed_since,
+            });
 
-        match self.by {
-            LogFormat::Day => {
-                log_entries::show_by_day(&mut conn, &project, period.as_ref(), self.comments)
+        if self.heatmap {
+            log_entries::show_heatmap(&mut conn, &config, &project, period.as_ref())?;
+        } else if let Some(compare) = self.compare {
+            let Some(period) = period.filter(|period| !period.is_open_start()) else {
+                bail!(WlogError::Validation(
+                    "Comparison requires a period with a start, not --all or only --to".into()
+                ));
+            };
+            log_entries::show_comparison(
+                &mut conn,
+                &config,
+                &project,
+                &period,
+                &compare.to_period(&period),
+                filter.as_ref(),
+                self.format,
+            )?;
+        } else if let Some(count) = self.top {
+            log_entries::show_top(
+                &mut conn,
+                &config,
+                &project,
+                period.as_ref(),
+                filter.as_ref(),
+                count,
+                self.format,
+            )?;
+        } else {
+            match self.by {
+                LogFormat::Day => log_entries::show_by_day(
+                    &mut conn,
+                    &config,
+                    &project,
+                    period.as_ref(),
+                    filter.as_ref(),
+                    self.comments,
+                    self.fill,
+                    self.flat,
+                    self.format,
+                ),
+                LogFormat::Label => log_entries::show_by_label(
+                    &mut conn,
+                    &config,
+                    project.id,
+                    period.as_ref(),
+                    filter.as_ref(),
+                    self.format,
+                ),
+                LogFormat::Task => log_entries::show_by_task(
+                    &mut conn,
+                    &config,
+                    &project,
+                    period.as_ref(),
+                    filter.as_ref(),
+                    Some(&match self.copy_to {
+                        Some(path) => CsvTarget::File(path),
+                        None => CsvTarget::Clipboard,
+                    }),
+                    self.format,
+                ),
+            }?;
+
+            if self.period.is_week()
+                && matches!(self.format, OutputFormat::Table)
+                && let Some(period) = &period
+            {
+                log_entries::show_target_progress(&mut conn, &config, &project, period)?;
             }
-            LogFormat::Task => {
-                log_entries::show_by_task(&mut conn, &project, period.as_ref(), true)
+        }
+        #[cfg(unix)]
+        drop(pager);
+
+        // Recorded once the entries were actually shown
+        if lists_all {
+            let utc = now.to_offset(UtcOffset::UTC);
+            let recorded = log_entries::mark_shown(
+                &mut conn,
+                project.id,
+                PrimitiveDateTime::new(utc.date(), utc.time()),
+            )?;
+            if !recorded && self.since_last {
+                eprintln!(
+                    "{} The database is read-only, the next --since-last shows these entries \
+                     again",
+                    "Warning:".yellow().bold()
+                );
+            }
+        }
+
+        if config.streak_badge() {
+            let today = now.date();
+            if let Some(streak) = streak::get(&mut conn, project.id, today)? {
+                eprintln!("{}", streak.badge());
             }
         }
+        Ok(())
+    }
+
+    /// Period of the listing, every date with `--since-last` unless a period is given
+    fn listed_period(&self, config: &Config, now: OffsetDateTime) -> Result<Option<Period>> {
+        if self.since_last && self.period.is_default() {
+            Ok(None)
+        } else {
+            self.period.to_period(config, now)
+        }
+    }
+
+    /// Whether every entry of the project is listed, which is recorded for `--since-last`. Other
+    /// views, periods and filtered listings leave out new entries, which then have to be shown by
+    /// the next `--since-last`.
+    fn lists_all(&self, period: Option<&Period>) -> bool {
+        period.is_none()
+            && self.issue.is_none()
+            && self.task.is_none()
+            && self.source.is_none()
+            && !self.heatmap
+            && self.compare.is_none()
+            && self.top.is_none()
+    }
+}
+
+impl LeftCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let Some(project) = projects::get_current(&mut conn, &config)? else {
+            bail!(WlogError::NotFound("No default project".into()));
+        };
+        let (today, week) = balance::left(&mut conn, project.id, config.now()?.date())?;
+        println!(
+            "{} today, {} this week",
+            config.fmt_duration(today),
+            config.fmt_duration(week)
+        );
+        Ok(())
+    }
+}
+
+/// Print the current project, its latest log entry and today's total, shown by `wlog` without a
+/// command
+pub fn print_status(config: Config) -> Result<()> {
+    let mut conn = data::open(config.data_path.as_ref())?;
+
+    let Some(project) = projects::get_current(&mut conn, &config)? else {
+        println!("No project configured, run `wlog project create`");
+        return Ok(());
+    };
+    println!(
+        "Project: {}",
+        project.name.as_deref().unwrap_or(&project.url)
+    );
+    match log_entries::get_last_entry(&mut conn, project.id)? {
+        Some((date, task, duration)) => println!(
+            "Last entry: {} {date}, {}{}, {}",
+            config.locale().weekday(date.weekday()),
+            task.issue
+                .map(|n| format!("{} ", OutputFormat::Table.issue(n, &project)))
+                .unwrap_or_default(),
+            task.name,
+            config.fmt_duration(duration)
+        ),
+        None => println!("Last entry: -"),
+    }
+    let today = config.now()?.date();
+    let logged = log_entries::get_day_total(&mut conn, project.id, today)?;
+    match schedule::scheduled_duration(&mut conn, project.id, today)? {
+        Some(scheduled) => println!(
+            "Today: {} of {}",
+            config.fmt_duration(logged),
+            config.fmt_duration(scheduled)
+        ),
+        None => println!("Today: {}", config.fmt_duration(logged)),
+    }
+    Ok(())
+}
+
+impl StreakCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let Some(project) = projects::get_current(&mut conn, &config)? else {
+            bail!(WlogError::NotFound("No default project".into()));
+        };
+        let Some(streak) = streak::get(&mut conn, project.id, config.now()?.date())? else {
+            bail!(WlogError::NotFound(
+                "No schedule is set for the current project".into()
+            ));
+        };
+        println!("Current streak: {} workdays", streak.current);
+        println!("Longest streak: {} workdays", streak.longest);
+        Ok(())
+    }
+}
+
+impl ForecastCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let project = projects::require_current(&mut conn, &config)?;
+        balance::show_forecast(&mut conn, &config, &project, config.now()?.date())
+    }
+}
+
+impl RecentCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let project = projects::require_current(&mut conn, &config)?;
+        let recent = log_entries::get_recent_tasks(&mut conn, project.id, RECENT_TASKS)?;
+        let rows = recent.into_iter().enumerate().map(|(i, (task, date))| {
+            vec![
+                format!("@{}", i + 1),
+                task.issue
+                    .map(|n| self.format.issue(n, &project))
+                    .unwrap_or_else(|| "-".into()),
+                self.format.task_name(&task.name),
+                date.to_string(),
+            ]
+        });
+        self.format.print_table(
+            config.table_style(),
+            &["#", "Issue", "Task", "Last logged"],
+            rows,
+        )
+    }
+}
+
+/// Number of tasks listed by `wlog recent` and available to `wlog log @N`
+const RECENT_TASKS: i64 = 10;
+
+/// Task at the 1-based index of the `wlog recent` list
+fn recent_task(
+    conn: &mut SqliteConnection,
+    project: projects::ProjectId,
+    n: usize,
+) -> Result<TaskId> {
+    let recent = log_entries::get_recent_tasks(conn, project, RECENT_TASKS)?;
+    recent
+        .into_iter()
+        .nth(n - 1)
+        .map(|(task, _)| task.id)
+        .ok_or_else(|| {
+            WlogError::NotFound(format!("No recent task @{n}, see `wlog recent`")).into()
+        })
+}
+
+/// Task of a split piece target: an issue number with `#` or an issue prefix, a task ID or a task
+/// name
+fn piece_task(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &projects::Project,
+    target: &str,
+) -> Result<TaskChoice> {
+    if project.is_issue_ref(config, target) {
+        let issue = project.parse_issue(config, target)?;
+        return tasks::choose_interactive(conn, Some(project.id), Some(issue), None);
+    }
+    if let Ok(id) = target.parse() {
+        let task = tasks::get(conn, TaskId(id))?;
+        if task.project_id != project.id {
+            bail!(WlogError::Validation(format!(
+                "Task {id} belongs to another project"
+            )));
+        }
+        return Ok(TaskChoice::Existing(task.id));
+    }
+    tasks::choose_interactive(conn, Some(project.id), None, Some(target))
+}
+
+/// Project of the issue URL, or the current project with the issue number as is
+fn project_and_issue(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    issue: Option<&str>,
+) -> Result<(ProjectChoice, Option<i32>)> {
+    if let Some((url, number)) = issue.and_then(parse_issue_url) {
+        return Ok((projects::choose_by_url(conn, url)?, Some(number)));
+    }
+    let project = projects::choose_default_interactive(conn, config)?;
+    let issue = issue.map(|v| project.parse_issue(config, v)).transpose()?;
+    Ok((project, issue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use time::{Month, Time};
+
+    #[derive(Parser)]
+    struct Show {
+        #[command(flatten)]
+        show: ShowCmd,
+    }
+
+    fn lists_all(args: &[&str]) -> bool {
+        let show = Show::parse_from(std::iter::once("show").chain(args.iter().copied())).show;
+        let now = OffsetDateTime::new_utc(
+            Date::from_calendar_date(2025, Month::March, 12).unwrap(),
+            Time::from_hms(12, 0, 0).unwrap(),
+        );
+        let period = show.listed_period(&Config::default(), now).unwrap();
+        show.lists_all(period.as_ref())
+    }
+
+    #[test]
+    fn since_last_after_default_show() {
+        // The default listing only covers the previous month, so later entries stay new
+        assert!(!lists_all(&[]));
+        assert!(lists_all(&["--since-last"]));
+        assert!(lists_all(&["--all"]));
+        assert!(!lists_all(&["--since-last", "--week"]));
+        assert!(!lists_all(&["--all", "--task", "Task"]));
     }
 }
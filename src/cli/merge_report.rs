@@ -0,0 +1,44 @@
+use super::common::PeriodArgGroup;
+use crate::Config;
+use crate::merge_report::{self, Source};
+use crate::utils::OutputFormat;
+use clap::Args;
+use eyre::Result;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct MergeReportCmd {
+    /// Database files, optionally as NAME=PATH. People are named after file names otherwise.
+    #[arg(required = true, value_parser = source_value_parser)]
+    databases: Vec<Source>,
+    /// Period
+    #[clap(flatten)]
+    period: PeriodArgGroup,
+    /// Output format
+    #[arg(long, default_value = "table")]
+    format: OutputFormat,
+}
+
+impl MergeReportCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let period = self.period.to_period(&config, config.now()?)?;
+        merge_report::show(&config, &self.databases, period.as_ref(), self.format)
+    }
+}
+
+fn source_value_parser(v: &str) -> Result<Source> {
+    if let Some((person, path)) = v.split_once('=')
+        && !person.is_empty()
+    {
+        return Ok(Source {
+            person: person.to_string(),
+            path: PathBuf::from(path),
+        });
+    }
+    let path = PathBuf::from(v);
+    let person = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| v.to_string());
+    Ok(Source { person, path })
+}
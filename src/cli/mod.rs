@@ -1,21 +1,61 @@
-use clap::{Parser, Subcommand};
+use crate::Config;
+use clap::{CommandFactory, Parser, Subcommand};
 use eyre::Result;
+use owo_colors::OwoColorize;
+use std::ffi::OsString;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
+mod clients;
 mod comments;
 mod common;
 mod config;
+#[cfg(unix)]
+mod daemon;
+mod data;
+mod export;
+mod import;
+mod invoice;
 mod logs;
+mod merge_report;
+mod month;
 mod projects;
+mod push;
+mod reminder;
+mod report;
+mod sprint;
+mod sync;
 mod tasks;
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// Add a new log entry
     #[clap(visible_alias("new"), alias("n"), alias("l"))]
-    Log(logs::AddLogCmd),
+    Log(logs::LogCmd),
+    /// Spread a weekly duration across scheduled workdays
+    Distribute(logs::DistributeCmd),
+    /// Log the scheduled time left today to a task
+    Fill(logs::FillCmd),
     /// Display logged work information
     #[clap(alias("s"))]
     Show(logs::ShowCmd),
+    /// Print scheduled time left to log today and this week
+    Left(logs::LeftCmd),
+    /// Print current and longest streaks of fully logged workdays
+    Streak(logs::StreakCmd),
+    /// Project whether the monthly requirement will be reached and the time needed per workday
+    Forecast(logs::ForecastCmd),
+    /// List the most recently logged tasks, which `wlog log @N` logs to
+    Recent(logs::RecentCmd),
+    /// Export log entries and comments as CSV, or log entries as an Excel workbook
+    Export(export::ExportCmd),
+    /// Import time entries from other tools
+    #[command(subcommand)]
+    Import(import::ImportCmd),
+    /// Render a custom report from a template
+    Report(report::ReportCmd),
+    /// Combine time per person and issue from the databases of several people
+    MergeReport(merge_report::MergeReportCmd),
     /// Manage tasks
     #[command(subcommand)]
     #[clap(alias("issue"), alias("t"))]
@@ -24,33 +64,187 @@ pub enum Command {
     #[command(subcommand)]
     #[clap(alias("p"))]
     Project(projects::ProjectCmd),
+    /// Manage clients grouping projects
+    #[command(subcommand)]
+    Client(clients::ClientCmd),
     /// Manage project schedule
     #[command(subcommand)]
     Schedule(projects::ScheduleCmd),
+    /// Monthly hour balance
+    #[command(subcommand)]
+    Month(month::MonthCmd),
+    /// Sprint estimates and logged time
+    #[command(subcommand)]
+    Sprint(sprint::SprintCmd),
+    /// Invoices of logged time, each entry is billed once
+    #[command(subcommand)]
+    Invoice(invoice::InvoiceCmd),
     /// Add a comment
     #[clap(visible_alias("c"))]
     Comment(comments::AddCommentCmd),
+    /// Remind about unlogged work time
+    #[command(subcommand)]
+    Reminder(reminder::ReminderCmd),
+    /// Synchronize log entries between machines
+    #[command(subcommand)]
+    Sync(sync::SyncCmd),
+    /// Publish logged time to issue trackers
+    #[command(subcommand)]
+    Push(push::PushCmd),
+    /// Serve a JSON-RPC interface for editor plugins on a Unix socket
+    #[cfg(unix)]
+    Daemon(daemon::DaemonCmd),
+    /// Database maintenance
+    #[command(subcommand)]
+    Data(data::DataCmd),
     /// Update configuration
     #[command(subcommand)]
     Config(config::ConfigCmd),
 }
 
+/// Value of `--config` in the arguments. Values are parsed as plain strings, so that durations
+/// aren't parsed before the configuration is loaded, and errors are left to the actual parsing.
+fn config_arg() -> Option<PathBuf> {
+    let command = raw_values(Cli::command()).ignore_errors(true);
+    let matches = command.try_get_matches().ok()?;
+    // Global arguments are only stored with the subcommand they're given after
+    let mut matches = &matches;
+    let mut path = matches.get_one::<OsString>("config").cloned();
+    while let Some((_, sub)) = matches.subcommand() {
+        matches = sub;
+        path = matches.get_one::<OsString>("config").cloned().or(path);
+    }
+    path.map(PathBuf::from)
+}
+
+fn raw_values(command: clap::Command) -> clap::Command {
+    command
+        .mut_args(|arg| {
+            if arg.get_action().takes_values() {
+                arg.value_parser(clap::builder::ValueParser::os_string())
+            } else {
+                arg
+            }
+        })
+        .mut_subcommands(raw_values)
+}
+
+/// Terminal width in columns below which the output is plain
+const NARROW_WIDTH: u16 = 100;
+
 #[derive(Debug, Parser)]
 pub struct Cli {
+    /// Configuration file used instead of the one in the standard config directory, also read
+    /// from WLOG_CONFIG
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// ASCII-only output without hyperlinks and with long task names truncated, the default in
+    /// terminals narrower than 100 columns
+    #[arg(long, global = true)]
+    plain: bool,
+    /// Fail instead of upgrading the database schema, see `wlog data version`
+    #[arg(long, global = true)]
+    no_migrate: bool,
+    /// Append a JSON line for every change made to the database to the file, see also
+    /// `wlog config log-file`
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+    /// Print every change made to the database to stderr, in the format of `--log-file`
+    #[arg(long, short, global = true)]
+    verbose: bool,
+    /// Without a command, the current project, its latest log entry and today's total are printed
     #[command(subcommand)]
-    command: Command,
+    command: Option<Command>,
 }
 
 impl Cli {
-    pub fn dispatch(self) -> Result<()> {
-        match self.command {
-            Command::Log(cmd) => cmd.dispatch(),
-            Command::Show(cmd) => cmd.dispatch(),
-            Command::Task(cmd) => cmd.dispatch(),
-            Command::Project(cmd) => cmd.dispatch(),
-            Command::Schedule(cmd) => cmd.dispatch(),
-            Command::Comment(cmd) => cmd.dispatch(),
-            Command::Config(cmd) => cmd.dispatch(),
+    /// Load the configuration given in the arguments or the environment before the arguments are
+    /// parsed, as parsing durations depends on the configuration. Durations are parsed with the
+    /// default syntaxes if the configuration fails to load, the error is left to [`Cli::dispatch`].
+    pub fn load_config() -> Result<Config> {
+        if let Some(path) =
+            config_arg().or_else(|| std::env::var_os("WLOG_CONFIG").map(PathBuf::from))
+        {
+            crate::config::set_config_path(path);
+        }
+        let config = Config::load()?;
+        crate::config::set_duration_input(&config);
+        Ok(config)
+    }
+
+    pub fn dispatch(self, config: Result<Config>) -> Result<()> {
+        let config = match config {
+            Ok(config) => config,
+            // Config commands stay usable so that a broken configuration can be fixed
+            Err(e) if matches!(self.command, Some(Command::Config(_))) => {
+                eprintln!("{} {e}", "Warning:".yellow().bold());
+                Config::default()
+            }
+            Err(e) => return Err(e),
+        };
+        // Checked before the pager takes over stdout
+        let narrow = console::Term::stdout()
+            .size_checked()
+            .is_some_and(|(_, width)| width < NARROW_WIDTH);
+        crate::config::set_plain_output(self.plain || narrow);
+        crate::config::set_hyperlinks(std::io::stdout().is_terminal());
+        crate::data::set_no_migrate(self.no_migrate);
+        let log_file = match self.log_file.or_else(|| config.log_file.clone()) {
+            Some(path) => match crate::event_log::check(&path) {
+                Ok(()) => Some(path),
+                // Config commands stay usable so that a broken path can be fixed
+                Err(e) if matches!(self.command, Some(Command::Config(_))) => {
+                    eprintln!("{} {e}", "Warning:".yellow().bold());
+                    None
+                }
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        crate::event_log::enable(log_file, self.verbose)?;
+
+        // Config commands stay quiet so that the warning can always be turned off, data commands
+        // inspect and migrate the database before anything else reads it
+        if !matches!(self.command, Some(Command::Config(_) | Command::Data(_))) {
+            if config.nag() {
+                crate::reminder::nag(&config);
+            }
+            if config.auto_summary().is_some() {
+                crate::reminder::auto_summary(&config);
+            }
+        }
+
+        let Some(command) = self.command else {
+            return logs::print_status(config);
+        };
+        match command {
+            Command::Log(cmd) => cmd.dispatch(config),
+            Command::Distribute(cmd) => cmd.dispatch(config),
+            Command::Fill(cmd) => cmd.dispatch(config),
+            Command::Show(cmd) => cmd.dispatch(config),
+            Command::Left(cmd) => cmd.dispatch(config),
+            Command::Streak(cmd) => cmd.dispatch(config),
+            Command::Forecast(cmd) => cmd.dispatch(config),
+            Command::Recent(cmd) => cmd.dispatch(config),
+            Command::Export(cmd) => cmd.dispatch(config),
+            Command::Import(cmd) => cmd.dispatch(config),
+            Command::Report(cmd) => cmd.dispatch(config),
+            Command::MergeReport(cmd) => cmd.dispatch(config),
+            Command::Task(cmd) => cmd.dispatch(config),
+            Command::Project(cmd) => cmd.dispatch(config),
+            Command::Client(cmd) => cmd.dispatch(config),
+            Command::Schedule(cmd) => cmd.dispatch(config),
+            Command::Month(cmd) => cmd.dispatch(config),
+            Command::Sprint(cmd) => cmd.dispatch(config),
+            Command::Invoice(cmd) => cmd.dispatch(config),
+            Command::Comment(cmd) => cmd.dispatch(config),
+            Command::Reminder(cmd) => cmd.dispatch(config),
+            Command::Sync(cmd) => cmd.dispatch(config),
+            Command::Push(cmd) => cmd.dispatch(config),
+            #[cfg(unix)]
+            Command::Daemon(cmd) => cmd.dispatch(config),
+            Command::Data(cmd) => cmd.dispatch(config),
+            Command::Config(cmd) => cmd.dispatch(config),
         }
     }
 }
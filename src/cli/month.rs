@@ -0,0 +1,46 @@
+use super::common::year_month_value_parser;
+use crate::{Config, balance, data, projects};
+use clap::Subcommand;
+use eyre::Result;
+use time::Date;
+
+#[derive(Debug, Subcommand)]
+pub enum MonthCmd {
+    /// Verify that all scheduled days are logged and store the hour balance as carryover
+    Close {
+        /// Month in YYYY-MM format
+        #[arg(value_parser = year_month_value_parser)]
+        month: Date,
+        /// Close the month even if some scheduled days have no log entries
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show hour balance of a month including carryover from the previous one, along with time
+    /// logged to labels with monthly caps
+    Balance {
+        /// Month in YYYY-MM format, current month by default
+        #[arg(value_parser = year_month_value_parser)]
+        month: Option<Date>,
+    },
+}
+
+impl MonthCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let project = projects::require_current(&mut conn, &config)?;
+
+        match self {
+            MonthCmd::Close { month, force } => {
+                balance::close(&mut conn, &config, &project, month, force)
+            }
+            MonthCmd::Balance { month } => {
+                let month = match month {
+                    Some(month) => month,
+                    None => config.now()?.date(),
+                };
+                balance::show(&mut conn, &config, &project, month)
+            }
+        }
+    }
+}
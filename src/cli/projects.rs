@@ -1,19 +1,85 @@
-use super::common::{date_value_parser, weekday_value_parser};
-use crate::schedule::{ScheduleLog, WeekBasedSchedule};
-use crate::{Config, data, projects, schedule};
+use super::common::{
+    date_value_parser, duration_value_parser, weekday_value_parser, year_month_value_parser,
+};
+use crate::error::WlogError;
+use crate::locale::Locale;
+use crate::schedule::{Rotation, ScheduleLog, WeekBasedSchedule};
+use crate::utils::{OutputFormat, fmt_duration};
+use crate::{Config, caps, credentials, data, projects, remote_issues, schedule};
 use clap::Subcommand;
 use eyre::{Result, bail};
 use owo_colors::OwoColorize;
-use time::{Date, Weekday};
+use time::{Date, Duration, Weekday};
 
 #[derive(Debug, Subcommand)]
 pub enum ProjectCmd {
     /// Create a new project
     Create,
     /// List all existing projects
-    List,
+    List {
+        /// Include archived projects, marked with "A"
+        #[arg(long)]
+        include_archived: bool,
+        /// Output format
+        #[arg(long, default_value = "table")]
+        format: OutputFormat,
+    },
     /// Pick a default project
     Default,
+    /// Hide a finished project from listings and task selection
+    Archive { id: i32 },
+    /// Restore an archived project
+    Unarchive { id: i32 },
+    /// Get or set prefix of issue keys of the default project, e.g. "PROJ-" for Jira
+    IssuePrefix {
+        new_prefix: Option<String>,
+        /// Use plain issue numbers
+        #[arg(long, conflicts_with = "new_prefix")]
+        unset: bool,
+    },
+    /// Get or set browse URL of issues of the default project, with `{issue}` in place of the
+    /// issue number and optionally `{project_url}`, e.g.
+    /// "https://jira.example.com/browse/PROJ-{issue}"
+    IssueUrlTemplate {
+        new_template: Option<String>,
+        /// Derive issue URLs from the project URL
+        #[arg(long, conflicts_with = "new_template")]
+        unset: bool,
+    },
+    /// Get or set whether `wlog push github-comments` posts on issues of the default project
+    GithubComments {
+        #[arg(long)]
+        enable: bool,
+        #[arg(long, conflicts_with = "enable")]
+        disable: bool,
+    },
+    /// Get or set the monthly cap of time logged to tasks with the label in the default project.
+    /// `wlog log`, `wlog distribute`, `wlog fill` and the daemon warn about exceeded caps, usage
+    /// of all caps is listed by `wlog month balance`.
+    LabelCap {
+        label: String,
+        #[arg(value_parser = duration_value_parser)]
+        cap: Option<Duration>,
+        /// Remove the cap
+        #[arg(long, conflicts_with = "cap")]
+        unset: bool,
+    },
+    /// Manage API credentials of the default project
+    #[command(subcommand)]
+    Auth(AuthCmd),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCmd {
+    /// Store an API token in the system keyring
+    Set {
+        #[arg(long)]
+        token: String,
+    },
+    /// Check whether an API token is stored
+    Status,
+    /// Remove the stored API token
+    Remove,
 }
 
 #[derive(Debug, Subcommand)]
@@ -28,15 +94,34 @@ pub enum ScheduleCmd {
         /// Set weekly schedule
         #[clap(long, value_parser = weekday_value_parser, value_delimiter = ',', num_args=1..=7)]
         weekdays: Vec<Weekday>,
-        /// Time log entries must be added for exact dates
+        /// Repeating runs of workdays and days off instead of weekdays, e.g. "4on,4off", or
+        /// "5on,2off,4on,3off" for every other Friday off
+        #[clap(long, conflicts_with = "weekdays")]
+        rotation: Option<String>,
+        /// First day of the rotation, today by default
+        #[clap(long, value_parser = date_value_parser, requires = "rotation")]
+        anchor: Option<Date>,
+        /// Time can only be logged on scheduled workdays, and weekly distribution and weekly
+        /// targets are unavailable
         #[clap(long)]
         rigid: bool,
+        /// Scheduled time of every workday
+        #[clap(long, default_value = "8h", value_parser = duration_value_parser)]
+        hours: Duration,
+        /// Hours required per ISO week regardless of which days are worked
+        #[clap(long, value_parser = duration_value_parser, conflicts_with = "rigid")]
+        weekly_hours: Option<Duration>,
+    },
+    /// Record the current schedule for a month, so that later schedule changes don't affect it
+    Log {
+        /// Month in YYYY-MM format, current month by default
+        #[arg(value_parser = year_month_value_parser)]
+        month: Option<Date>,
     },
 }
 
 impl ProjectCmd {
-    pub fn dispatch(self) -> Result<()> {
-        let config = Config::read()?.unwrap_or_default();
+    pub fn dispatch(self, config: Config) -> Result<()> {
         let mut conn = data::open(config.data_path.as_ref())?;
 
         match self {
@@ -44,57 +129,301 @@ impl ProjectCmd {
                 projects::create_interactive(&mut conn)?;
                 Ok(())
             }
-            ProjectCmd::List => projects::list_all(&mut conn),
-            ProjectCmd::Default => projects::set_default_interactive(&mut conn),
+            ProjectCmd::List {
+                include_archived,
+                format,
+            } => projects::list_all(&mut conn, &config, include_archived, format),
+            ProjectCmd::Default => projects::set_default_interactive(&mut conn, &config),
+            ProjectCmd::Archive { id } => {
+                projects::set_archived(&mut conn, projects::ProjectId(id), true)?;
+                eprintln!("{} Project {id} archived", "Success:".green().bold());
+                Ok(())
+            }
+            ProjectCmd::Unarchive { id } => {
+                projects::set_archived(&mut conn, projects::ProjectId(id), false)?;
+                eprintln!("{} Project {id} restored", "Success:".green().bold());
+                Ok(())
+            }
+            ProjectCmd::IssuePrefix { new_prefix, unset } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                match (new_prefix, unset) {
+                    (None, false) => {
+                        println!("{}", project.issue_prefix.as_deref().unwrap_or("-"))
+                    }
+                    (new_prefix, _) => {
+                        projects::set_issue_prefix(&mut conn, project.id, new_prefix.as_deref())?;
+                        match new_prefix {
+                            Some(prefix) => eprintln!(
+                                "{} Issue prefix set to {prefix}",
+                                "Success:".green().bold()
+                            ),
+                            None => {
+                                eprintln!("{} Issue prefix removed", "Success:".green().bold())
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ProjectCmd::IssueUrlTemplate {
+                new_template,
+                unset,
+            } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                match (new_template, unset) {
+                    (None, false) => {
+                        println!("{}", project.issue_url_template.as_deref().unwrap_or("-"))
+                    }
+                    (new_template, _) => {
+                        projects::set_issue_url_template(
+                            &mut conn,
+                            &project,
+                            new_template.as_deref(),
+                        )?;
+                        match new_template {
+                            Some(_) => eprintln!(
+                                "{} Issue URL template set, issue 123 links to {}",
+                                "Success:".green().bold(),
+                                projects::get(&mut conn, project.id)?.issue_url(123)
+                            ),
+                            None => eprintln!(
+                                "{} Issue URL template removed",
+                                "Success:".green().bold()
+                            ),
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ProjectCmd::GithubComments { enable, disable } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                if !enable && !disable {
+                    let state = if project.github_comments {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    };
+                    println!("{state}");
+                    return Ok(());
+                }
+                if enable && remote_issues::split_repo_url(&project.url)?.0 != "github.com" {
+                    bail!(WlogError::Validation(format!(
+                        "Project URL {} isn't a GitHub repository",
+                        project.url
+                    )));
+                }
+                projects::set_github_comments(&mut conn, project.id, enable)?;
+                let state = if enable { "enabled" } else { "disabled" };
+                eprintln!("{} GitHub comments {state}", "Success:".green().bold());
+                Ok(())
+            }
+            ProjectCmd::LabelCap { label, cap, unset } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                if cap.is_none() && !unset {
+                    let label = label.trim().to_lowercase();
+                    match caps::get(&mut conn, project.id)?
+                        .into_iter()
+                        .find(|(capped, _)| *capped == label)
+                    {
+                        Some((_, cap)) => println!("{}", fmt_duration(cap)),
+                        None => println!("-"),
+                    }
+                    return Ok(());
+                }
+                caps::set(&mut conn, project.id, &label, cap)?;
+                match cap {
+                    Some(cap) => eprintln!(
+                        "{} Monthly cap of \"{label}\" set to {}",
+                        "Success:".green().bold(),
+                        fmt_duration(cap)
+                    ),
+                    None => eprintln!(
+                        "{} Monthly cap of \"{label}\" removed",
+                        "Success:".green().bold()
+                    ),
+                }
+                Ok(())
+            }
+            ProjectCmd::Auth(cmd) => {
+                let project = projects::require_current(&mut conn, &config)?;
+                cmd.dispatch(&project)
+            }
         }
     }
 }
 
+impl AuthCmd {
+    pub fn dispatch(self, project: &projects::Project) -> Result<()> {
+        match self {
+            AuthCmd::Set { token } => {
+                credentials::set_token(project.id, &token)?;
+                eprintln!("{} API token stored", "Success:".green().bold());
+            }
+            AuthCmd::Status => {
+                if credentials::get_token(project.id)?.is_some() {
+                    println!("API token is set");
+                } else {
+                    println!("API token is not set");
+                }
+            }
+            AuthCmd::Remove => {
+                credentials::remove_token(project.id)?;
+                eprintln!("{} API token removed", "Success:".green().bold());
+            }
+        }
+        Ok(())
+    }
+}
+
 impl ScheduleCmd {
-    pub fn dispatch(self) -> Result<()> {
-        let config = Config::read()?.unwrap_or_default();
+    pub fn dispatch(self, config: Config) -> Result<()> {
         let mut conn = data::open(config.data_path.as_ref())?;
-        let project = projects::get_default_or_create_interactive(&mut conn)?;
+        let project = projects::require_current(&mut conn, &config)?;
 
         match self {
             ScheduleCmd::Show { for_date } => {
                 if let Some(date) = for_date {
                     if let Some(bitmap) = schedule::get_log(&mut conn, project.id, date)? {
-                        print_calendar(date, bitmap);
+                        print_calendar(date, bitmap, config.locale());
                         Ok(())
                     } else {
-                        bail!("No results")
+                        bail!(WlogError::NotFound("No results".into()))
                     }
                 } else if let Some(result) = schedule::get(&mut conn, project.id)? {
+                    let workday =
+                        schedule::get_workday(&mut conn, project.id)?.unwrap_or(Duration::hours(8));
+                    let today = config.now()?.date();
+                    let monday =
+                        today - Duration::days(today.weekday().number_days_from_monday() as i64);
                     println!("Active schedule:");
-                    println!(
-                        "{}",
-                        result
-                            .to_weekdays()
-                            .into_iter()
-                            .map(|weekday| weekday.to_string())
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    );
+                    if let Some(rotation) = schedule::get_rotation(&mut conn, project.id)? {
+                        println!("Rotation {rotation} starting on {}", rotation.anchor);
+                        let mondays = (0..5)
+                            .map(|week| monday + Duration::weeks(week))
+                            .collect::<Vec<_>>();
+                        print_weeks(
+                            &mondays,
+                            true,
+                            |date| rotation.is_workday(date),
+                            workday,
+                            config.locale(),
+                        );
+                    } else {
+                        let weekdays = result.to_weekdays();
+                        print_weeks(
+                            &[monday],
+                            false,
+                            |date| weekdays.contains(&date.weekday()),
+                            workday,
+                            config.locale(),
+                        );
+                    }
                     println!("Flexible: {}", result.is_flexible());
+                    if let Some(workday) = schedule::get_workday(&mut conn, project.id)? {
+                        println!("Workday: {}", fmt_duration(workday));
+                    }
+                    if let Some(target) = schedule::get_weekly_target(&mut conn, project.id)? {
+                        println!("Weekly target: {}", fmt_duration(target));
+                    }
                     Ok(())
                 } else {
-                    bail!("No results")
+                    bail!(WlogError::NotFound("No results".into()))
                 }
             }
-            ScheduleCmd::Set { weekdays, rigid } => schedule::set(
-                &mut conn,
-                project.id,
-                WeekBasedSchedule::new(&weekdays, !rigid),
-            ),
+            ScheduleCmd::Set {
+                weekdays,
+                rotation,
+                anchor,
+                rigid,
+                hours,
+                weekly_hours,
+            } => {
+                let rotation = match rotation {
+                    Some(pattern) => {
+                        let anchor = match anchor {
+                            Some(anchor) => anchor,
+                            None => config.now()?.date(),
+                        };
+                        Some(Rotation::parse(&pattern, anchor)?)
+                    }
+                    None => None,
+                };
+                schedule::set(
+                    &mut conn,
+                    project.id,
+                    WeekBasedSchedule::new(&weekdays, !rigid),
+                    rotation.as_ref(),
+                    hours,
+                    weekly_hours,
+                )
+            }
+            ScheduleCmd::Log { month } => {
+                if schedule::get(&mut conn, project.id)?.is_none() {
+                    bail!(WlogError::NotFound(
+                        "No schedule is set for the current project".into()
+                    ));
+                }
+                let month = match month {
+                    Some(month) => month,
+                    None => config.now()?.date(),
+                };
+                schedule::log(&mut conn, project.id, month)?;
+                if let Some(log) = schedule::get_log(&mut conn, project.id, month)? {
+                    print_calendar(month, log, config.locale());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Print scheduled time of every day of the weeks starting on the Mondays under weekday names,
+/// workdays highlighted. Labeled rows start with the date of the Monday.
+fn print_weeks(
+    mondays: &[Date],
+    labeled: bool,
+    is_workday: impl Fn(Date) -> bool,
+    workday: Duration,
+    locale: Locale,
+) {
+    const WIDTH: usize = 5;
+    if labeled {
+        print!("{:10} ", "");
+    }
+    let mut weekday = Weekday::Monday;
+    for _ in 0..7 {
+        print!(" {:>WIDTH$}", locale.weekday_short(weekday));
+        weekday = weekday.next();
+    }
+    println!();
+
+    for monday in mondays {
+        if labeled {
+            print!("{monday} ");
+        }
+        for day in 0..7 {
+            let date = *monday + Duration::days(day);
+            if is_workday(date) {
+                let hours = format!("{:>WIDTH$}", fmt_duration(workday));
+                print!(" {}", hours.green().bold());
+            } else {
+                print!(" {}", format!("{:>WIDTH$}", "-").dimmed());
+            }
         }
+        println!();
     }
 }
 
-pub fn print_calendar(date: time::Date, schedule: ScheduleLog) {
+pub fn print_calendar(date: time::Date, schedule: ScheduleLog, locale: Locale) {
     let date = date.replace_day(1).unwrap();
     let weekday_ord = date.weekday().number_days_from_monday();
-    println!(" Mo Tu We Th Fr Sa Su");
+    println!(" {} {}", locale.month(date.month()), date.year());
+    let mut weekday = Weekday::Monday;
+    for _ in 0..7 {
+        print!(" {}", locale.weekday_short(weekday));
+        weekday = weekday.next();
+    }
+    println!();
     print!("{: <1$}", "", weekday_ord as usize * 3);
     for i in 1..=time::util::days_in_month(date.month(), date.year()) {
         if (weekday_ord + i) % 7 == 1 && i != 0 {
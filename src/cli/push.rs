@@ -0,0 +1,62 @@
+use super::common::date_value_parser;
+use crate::error::WlogError;
+use crate::log_entries::Period;
+use crate::{Config, data, github_comments, projects};
+use clap::Subcommand;
+use eyre::{Result, bail};
+use owo_colors::OwoColorize;
+use time::{Date, Duration};
+
+#[derive(Debug, Subcommand)]
+pub enum PushCmd {
+    /// Comment a summary of the week's logged time on every touched GitHub issue of the default
+    /// project. Comments are queued and posted once the network is available, each issue is
+    /// commented once per week
+    GithubComments {
+        /// Any date of the week, string in ISO8601 format. Previous week by default
+        #[arg(long, value_parser = date_value_parser)]
+        week_of: Option<Date>,
+    },
+}
+
+impl PushCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        match self {
+            PushCmd::GithubComments { week_of } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                if !project.github_comments {
+                    bail!(WlogError::Validation(format!(
+                        "GitHub comments are disabled for project {}, enable them with `wlog project github-comments --enable`",
+                        project.id.0
+                    )));
+                }
+                let date = match week_of {
+                    Some(date) => date,
+                    None => config.now()?.date() - Duration::weeks(1),
+                };
+                let from = date - Duration::days(date.weekday().number_days_from_monday() as i64);
+                let week = Period {
+                    from,
+                    to: from + Duration::days(6),
+                };
+                let queued = github_comments::enqueue_week(&mut conn, &config, project.id, &week)?;
+                let (posted, pending) = github_comments::flush(&mut conn)?;
+                eprintln!(
+                    "{} {queued} comments queued for the week {} - {}, {posted} posted",
+                    "Success:".green().bold(),
+                    week.from,
+                    week.to
+                );
+                if pending > 0 {
+                    eprintln!(
+                        "{} {pending} comments will be posted by the next push",
+                        "Note:".cyan()
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
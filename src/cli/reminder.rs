@@ -0,0 +1,18 @@
+use crate::{Config, reminder};
+use clap::Subcommand;
+use eyre::Result;
+
+#[derive(Debug, Subcommand)]
+pub enum ReminderCmd {
+    /// Run in foreground and send a desktop notification if scheduled time isn't logged by the
+    /// configured reminder time
+    Daemon,
+}
+
+impl ReminderCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        match self {
+            ReminderCmd::Daemon => reminder::run_daemon(&config),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::common::PeriodArgGroup;
+use crate::{Config, data, projects, report};
+use clap::Args;
+use eyre::Result;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct ReportCmd {
+    /// Tera template file. Variables: project, period, entries, tasks, schedule, totals
+    #[arg(long)]
+    template: PathBuf,
+    /// Period
+    #[clap(flatten)]
+    period: PeriodArgGroup,
+}
+
+impl ReportCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let now = config.now()?;
+        let period = self.period.to_period(&config, now)?;
+        let template = std::fs::read_to_string(&self.template)?;
+
+        let project = projects::require_current(&mut conn, &config)?;
+
+        let report = report::render(&mut conn, &config, &project, period.as_ref(), &template)?;
+        print!("{report}");
+        Ok(())
+    }
+}
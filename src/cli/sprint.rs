@@ -0,0 +1,41 @@
+use super::common::{DateArg, relaxed_date_value_parser};
+use crate::log_entries::Period;
+use crate::utils::OutputFormat;
+use crate::{Config, data, projects, sprint};
+use clap::Subcommand;
+use eyre::Result;
+
+#[derive(Debug, Subcommand)]
+pub enum SprintCmd {
+    /// Compare task estimates with time logged within the sprint
+    Report {
+        /// First day of the sprint, in ISO8601 format or a shorthand like `monday` or `-2w`
+        #[arg(long, value_parser = relaxed_date_value_parser, allow_hyphen_values = true)]
+        from: DateArg,
+        /// Last day of the sprint, same formats as `--from`. Today by default
+        #[arg(long, value_parser = relaxed_date_value_parser, allow_hyphen_values = true)]
+        to: Option<DateArg>,
+        /// Output format
+        #[arg(long, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+impl SprintCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        let project = projects::require_current(&mut conn, &config)?;
+
+        match self {
+            SprintCmd::Report { from, to, format } => {
+                let today = config.now()?.date();
+                let period = Period {
+                    from: from.first(today)?,
+                    to: to.map_or(Ok(today), |to| to.last(today))?,
+                };
+                sprint::report(&mut conn, &config, &project, &period, format)
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::log_entries::Limits;
+use crate::{Config, data, sync, webhook};
+use clap::Subcommand;
+use eyre::Result;
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum SyncCmd {
+    /// Exchange changes with other machines through journals in a shared directory
+    File { dir: PathBuf },
+}
+
+impl SyncCmd {
+    pub fn dispatch(self, config: Config) -> Result<()> {
+        let mut conn = data::open(config.data_path.as_ref())?;
+
+        match self {
+            SyncCmd::File { dir } => {
+                sync::sync_file(&mut conn, &config, &dir, &Limits::new(&config, false)?)?
+            }
+        }
+        webhook::flush(&mut conn, &config)
+    }
+}
@@ -1,9 +1,27 @@
-use crate::{Config, data, projects, tasks};
+use super::common::duration_value_parser;
+use crate::error::WlogError;
+use crate::utils::{
+    History, OutputFormat, prompt_opt, prompt_opt_with_initial, prompt_with_initial, yn_prompt,
+};
+use crate::{Config, data, log_entries, projects, remote_issues, tasks, webhook};
 use clap::Subcommand;
+use diesel::Connection;
 use eyre::Result;
+use owo_colors::OwoColorize;
+use time::Duration;
 
 #[derive(Debug, Subcommand)]
 pub enum TaskCmd {
+    /// Create a new task without prompts
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        issue: Option<i32>,
+        /// ID of the project, the default project if omitted
+        #[arg(long)]
+        project: Option<i32>,
+    },
     /// Update an existing task
     Update {
         #[arg(long)]
@@ -12,41 +30,416 @@ pub enum TaskCmd {
         name: Option<String>,
         #[arg(long = "set-issue", group = "issue_value")]
         issue: Option<i32>,
-        #[arg(long = "remove-issue", group = "issue_value")]
-        no_issue: bool,
+        /// Remove the linked issue, or only the given one if a number is provided
+        #[arg(long = "remove-issue", num_args = 0..=1, group = "issue_value")]
+        remove_issue: Option<Option<i32>>,
+        /// Link an additional issue to the task
+        #[arg(long = "add-issue")]
+        add_issue: Option<i32>,
+        /// Set task notes, an empty string removes them
+        #[arg(long = "set-description")]
+        description: Option<String>,
+        /// Comma-separated labels to add, e.g. "bug" or "support"
+        #[arg(long, value_delimiter = ',')]
+        add_label: Vec<String>,
+        /// Comma-separated labels to remove
+        #[arg(long, value_delimiter = ',')]
+        remove_label: Vec<String>,
+    },
+    /// Show task details and its latest log entries
+    Show {
+        id: i32,
+        /// Number of latest log entries to show
+        #[arg(long, default_value_t = 10)]
+        recent: i64,
+    },
+    /// Get or set the weekly time target of the task, progress is shown by `wlog show --week`
+    Target {
+        id: i32,
+        #[arg(value_parser = duration_value_parser)]
+        weekly: Option<Duration>,
+        /// Remove the target
+        #[arg(long, conflicts_with = "weekly")]
+        unset: bool,
+    },
+    /// Get or set the estimated total time of the task, compared by `wlog sprint report`
+    Estimate {
+        id: i32,
+        #[arg(value_parser = duration_value_parser)]
+        estimate: Option<Duration>,
+        /// Remove the estimate
+        #[arg(long, conflicts_with = "estimate")]
+        unset: bool,
     },
+    /// Show renames and issue changes of the task
+    History { id: i32 },
     /// List all existing tasks
-    List,
+    List {
+        /// Order of the listed tasks
+        #[arg(long, default_value = "created")]
+        sort: tasks::TaskSort,
+        /// Maximum number of tasks to show
+        #[arg(long)]
+        limit: Option<i64>,
+        /// Number of tasks to skip
+        #[arg(long)]
+        offset: Option<i64>,
+        /// List tasks of every project
+        #[arg(long)]
+        all_projects: bool,
+        /// Include tasks of archived projects
+        #[arg(long, requires = "all_projects")]
+        include_archived: bool,
+        /// Output format
+        #[arg(long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Cache open issues of the project tracker to suggest task names
+    Fetch,
+    /// Create tasks for open issues of the project tracker in one go
+    Import {
+        /// Import issues of the GitHub repository of the project
+        #[arg(long, required = true)]
+        from_github: bool,
+        /// Only import issues with this label, e.g. "sprint-12"
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Move a task with all its log entries to another project
+    Move {
+        id: i32,
+        /// ID of the target project
+        #[arg(long)]
+        to_project: i32,
+        /// Issue number of the task in the target project, replacing the current and linked ones
+        #[arg(long, conflicts_with = "clear_issue")]
+        issue: Option<i32>,
+        /// Remove the issue and linked issues, they don't exist in the target project
+        #[arg(long)]
+        clear_issue: bool,
+        /// Move the task even if it has entries past the edit grace period
+        #[arg(long)]
+        force: bool,
+    },
+    /// Find tasks of the default project with near-duplicate names and merge or rename them
+    Tidy {
+        /// Merge tasks even if it changes entries past the edit grace period
+        #[arg(long)]
+        force: bool,
+    },
     /// Search for a task that contains the provided substring
-    Search { query: Option<String> },
+    Search {
+        query: Option<String>,
+        /// Search tasks of every project
+        #[arg(long, requires = "query")]
+        all_projects: bool,
+        /// Include tasks of archived projects
+        #[arg(long, requires = "all_projects")]
+        include_archived: bool,
+    },
 }
 
 impl TaskCmd {
-    pub fn dispatch(self) -> Result<()> {
-        let config = Config::read()?.unwrap_or_default();
+    pub fn dispatch(self, config: Config) -> Result<()> {
         let mut conn = data::open(config.data_path.as_ref())?;
 
-        let project = projects::get_default_or_create_interactive(&mut conn)?;
+        let project = match &self {
+            TaskCmd::Create {
+                project: Some(id), ..
+            } => projects::get(&mut conn, projects::ProjectId(*id))?,
+            _ => projects::require_current(&mut conn, &config)?,
+        };
 
         match self {
+            TaskCmd::Create { name, issue, .. } => {
+                let task = tasks::create(&mut conn, project.id, &name, issue)?;
+                eprintln!(
+                    "{} Task {} created with ID {}",
+                    "Success:".green().bold(),
+                    name,
+                    task.0
+                );
+                Ok(())
+            }
             TaskCmd::Update {
                 id,
                 issue,
-                no_issue,
+                remove_issue,
+                add_issue,
                 name,
+                description,
+                add_label,
+                remove_label,
             } => {
-                let issue = issue.map(Some).or_else(|| no_issue.then_some(None));
-                tasks::update(
+                let (issue, remove_issue) = match remove_issue {
+                    Some(None) => (Some(None), None),
+                    Some(Some(removed)) => (issue.map(Some), Some(removed)),
+                    None => (issue.map(Some), None),
+                };
+                let task = conn.transaction(|conn| {
+                    let task = tasks::update(
+                        conn,
+                        tasks::TaskId(id),
+                        name.as_deref(),
+                        issue,
+                        add_issue,
+                        remove_issue,
+                        description
+                            .as_deref()
+                            .map(|d| Some(d).filter(|d| !d.is_empty())),
+                    )?;
+                    tasks::update_labels(conn, task.id, &add_label, &remove_label)?;
+                    eyre::Ok(task)
+                })?;
+                eprintln!("{} Task has been updated", "Success:".green().bold());
+                tasks::print_task_list(
                     &mut conn,
-                    &project,
-                    tasks::TaskId(id),
-                    name.as_deref(),
-                    issue,
+                    &config,
+                    Some(&project),
+                    &[task],
+                    OutputFormat::Table,
                 )
             }
-            TaskCmd::List => tasks::list(&mut conn, &project),
-            TaskCmd::Search { query: Some(query) } => tasks::search(&mut conn, &project, query),
-            TaskCmd::Search { query: None } => tasks::search_interactive(&mut conn, &project),
+            TaskCmd::Show { id, recent } => {
+                tasks::show(&mut conn, &config, tasks::TaskId(id), recent)
+            }
+            TaskCmd::Target { id, weekly, unset } => {
+                let id = tasks::TaskId(id);
+                if weekly.is_none() && !unset {
+                    match tasks::get_target(&mut conn, id)? {
+                        Some(target) => println!("{}", config.fmt_duration(target)),
+                        None => println!("-"),
+                    }
+                    return Ok(());
+                }
+                tasks::set_target(&mut conn, id, weekly)?;
+                match weekly {
+                    Some(target) => eprintln!(
+                        "{} Weekly target of task {} set to {}",
+                        "Success:".green().bold(),
+                        id.0,
+                        config.fmt_duration(target)
+                    ),
+                    None => eprintln!(
+                        "{} Weekly target of task {} removed",
+                        "Success:".green().bold(),
+                        id.0
+                    ),
+                }
+                Ok(())
+            }
+            TaskCmd::Move {
+                id,
+                to_project,
+                issue,
+                clear_issue,
+                force,
+            } => {
+                let issue = match (issue, clear_issue) {
+                    (Some(issue), _) => Some(Some(issue)),
+                    (None, true) => Some(None),
+                    (None, false) => None,
+                };
+                let to = projects::ProjectId(to_project);
+                let limits = log_entries::Limits::new(&config, force)?;
+                let (moved, kept_issues) = conn.transaction(|conn| {
+                    tasks::move_to_project(conn, tasks::TaskId(id), to, issue, &limits)
+                })?;
+                eprintln!(
+                    "{} Task {id} moved to project {to_project} with {moved} log entries",
+                    "Success:".green().bold()
+                );
+                if kept_issues {
+                    eprintln!(
+                        "{} Issue numbers are kept, use --issue or --clear-issue if they differ \
+                         in the target project",
+                        "Note:".cyan()
+                    );
+                }
+                Ok(())
+            }
+            TaskCmd::Estimate {
+                id,
+                estimate,
+                unset,
+            } => {
+                let id = tasks::TaskId(id);
+                if estimate.is_none() && !unset {
+                    match tasks::get_estimate(&mut conn, id)? {
+                        Some(estimate) => println!("{}", config.fmt_duration(estimate)),
+                        None => println!("-"),
+                    }
+                    return Ok(());
+                }
+                tasks::set_estimate(&mut conn, id, estimate)?;
+                match estimate {
+                    Some(estimate) => eprintln!(
+                        "{} Estimate of task {} set to {}",
+                        "Success:".green().bold(),
+                        id.0,
+                        config.fmt_duration(estimate)
+                    ),
+                    None => eprintln!(
+                        "{} Estimate of task {} removed",
+                        "Success:".green().bold(),
+                        id.0
+                    ),
+                }
+                Ok(())
+            }
+            TaskCmd::Tidy { force } => {
+                let project = projects::require_current(&mut conn, &config)?;
+                let groups = tasks::find_similar(&mut conn, project.id)?;
+                if groups.is_empty() {
+                    eprintln!("{} No tasks with similar names", "Info:".cyan());
+                    return Ok(());
+                }
+                let totals = log_entries::get_task_totals(&mut conn, project.id, None, None)?;
+                for group in groups {
+                    eprintln!("Tasks with similar names:");
+                    for (i, task) in group.iter().enumerate() {
+                        let logged = totals
+                            .iter()
+                            .find(|total| total.task_id == task.id)
+                            .map(|total| total.duration)
+                            .unwrap_or_default();
+                        eprintln!(
+                            "  {}) Task {}: {}{} ({})",
+                            i + 1,
+                            task.id.0,
+                            task.issue
+                                .map(|n| format!("{} ", project.issue_key(n)))
+                                .unwrap_or_default(),
+                            task.name,
+                            config.fmt_duration(logged)
+                        );
+                    }
+                    let choice = prompt_opt::<usize>(
+                        "Number of the task to merge the others into",
+                        History::Choice,
+                    )?;
+                    if let Some(n) = choice {
+                        let option = |n: usize| {
+                            group
+                                .get(n.wrapping_sub(1))
+                                .ok_or_else(|| WlogError::Validation(format!("No option {n}")))
+                        };
+                        let into = option(n)?;
+                        let others = (1..=group.len())
+                            .filter(|&i| i != n)
+                            .map(|i| i.to_string())
+                            .collect::<Vec<_>>()
+                            .join(",");
+                        let picked = prompt_with_initial::<String>(
+                            "Numbers of the tasks to merge into it, separated by commas",
+                            &others,
+                            History::Choice,
+                        )?;
+                        let mut merged = Vec::new();
+                        for number in picked.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                            let number = number.parse::<usize>().map_err(|_| {
+                                WlogError::Validation(format!("Invalid option \"{number}\""))
+                            })?;
+                            let task = option(number)?;
+                            if task.id != into.id
+                                && !merged.iter().any(|m: &&tasks::Task| m.id == task.id)
+                            {
+                                merged.push(task);
+                            }
+                        }
+                        let ids = merged
+                            .iter()
+                            .map(|task| task.id.0.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        if merged.is_empty()
+                            || !yn_prompt(&format!(
+                                "Merge tasks {ids} into task {} and delete them?",
+                                into.id.0
+                            ))?
+                        {
+                            eprintln!("{} No tasks were merged", "Note:".cyan());
+                            continue;
+                        }
+                        let limits = log_entries::Limits::new(&config, force)?;
+                        let moved = conn.transaction(|conn| {
+                            let mut moved = 0;
+                            for task in &merged {
+                                moved += tasks::merge(conn, &config, task.id, into.id, &limits)?;
+                            }
+                            eyre::Ok(moved)
+                        })?;
+                        eprintln!(
+                            "{} {} tasks merged into task {} with {moved} log entries",
+                            "Success:".green().bold(),
+                            merged.len(),
+                            into.id.0
+                        );
+                        webhook::flush(&mut conn, &config)?;
+                        continue;
+                    }
+                    for task in &group {
+                        let name = prompt_opt_with_initial::<String>(
+                            &format!("New name of task {}", task.id.0),
+                            &task.name,
+                            History::TaskName,
+                        )?;
+                        if let Some(name) = name.filter(|name| *name != task.name) {
+                            tasks::update(&mut conn, task.id, Some(&name), None, None, None, None)?;
+                            eprintln!(
+                                "{} Task {} renamed to {name}",
+                                "Success:".green().bold(),
+                                task.id.0
+                            );
+                        }
+                    }
+                }
+                Ok(())
+            }
+            TaskCmd::History { id } => tasks::history(&mut conn, &config, tasks::TaskId(id)),
+            TaskCmd::List {
+                sort,
+                limit,
+                offset,
+                all_projects,
+                include_archived,
+                format,
+            } => {
+                let project = (!all_projects).then_some(&project);
+                let tasks = tasks::list(&mut conn, project, include_archived, sort, limit, offset)?;
+                tasks::print_task_list(&mut conn, &config, project, &tasks, format)
+            }
+            TaskCmd::Search {
+                query: Some(query),
+                all_projects,
+                include_archived,
+            } => {
+                let project = (!all_projects).then_some(&project);
+                let tasks = tasks::search(&mut conn, project, include_archived, query)?;
+                tasks::print_task_list(&mut conn, &config, project, &tasks, OutputFormat::Table)
+            }
+            TaskCmd::Search { query: None, .. } => tasks::search_interactive(&mut conn, &project),
+            TaskCmd::Fetch => {
+                let count = remote_issues::fetch(&mut conn, &project)?;
+                eprintln!("{} {count} open issues cached", "Success:".green().bold());
+                Ok(())
+            }
+            TaskCmd::Import { label, .. } => {
+                let issues = remote_issues::fetch_github(&project, label.as_deref())?;
+                let (created, skipped) = tasks::import_issues(&mut conn, project.id, &issues)?;
+                eprintln!(
+                    "{} {created} tasks created from {} open issues",
+                    "Success:".green().bold(),
+                    issues.len()
+                );
+                if skipped > 0 {
+                    eprintln!(
+                        "{} {skipped} issues skipped, their titles are already names of other tasks",
+                        "Note:".cyan()
+                    );
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,146 @@
+use crate::Config;
+use crate::error::WlogError;
+use crate::log_entries::Period;
+use crate::projects::{self, ProjectId};
+use crate::schema::{clients, log_entries, projects as projects_table, tasks};
+use crate::utils::OutputFormat;
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use eyre::{Result, bail};
+use time::{Date, Duration};
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::clients)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Client {
+    pub id: i32,
+    pub name: String,
+}
+
+pub fn create(conn: &mut SqliteConnection, name: &str) -> Result<Client> {
+    diesel::insert_into(clients::table)
+        .values(clients::name.eq(name))
+        .returning(Client::as_select())
+        .get_result(conn)
+        .map_err(|e| match e {
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                WlogError::Validation(format!("Client \"{name}\" already exists")).into()
+            }
+            e => e.into(),
+        })
+}
+
+/// Delete the client, its projects are kept without a client
+pub fn delete(conn: &mut SqliteConnection, id: i32) -> Result<()> {
+    let deleted = diesel::delete(clients::table.find(id)).execute(conn)?;
+    if deleted == 0 {
+        bail!(WlogError::NotFound(format!("Client {id} doesn't exist")));
+    }
+    Ok(())
+}
+
+/// Assign the project to the client, or remove it from its client if `None`
+pub fn assign(conn: &mut SqliteConnection, project: ProjectId, client: Option<i32>) -> Result<()> {
+    if let Some(client) = client {
+        let exists = clients::table
+            .find(client)
+            .select(clients::id)
+            .first::<i32>(conn)
+            .optional()?
+            .is_some();
+        if !exists {
+            bail!(WlogError::NotFound(format!(
+                "Client {client} doesn't exist"
+            )));
+        }
+    }
+    let updated = diesel::update(projects_table::table.find(project.0))
+        .set(projects_table::client_id.eq(client))
+        .execute(conn)?;
+    if updated == 0 {
+        bail!(WlogError::NotFound(format!(
+            "Project {} doesn't exist",
+            project.0
+        )));
+    }
+    Ok(())
+}
+
+pub fn list(conn: &mut SqliteConnection, config: &Config) -> Result<()> {
+    let clients = clients::table
+        .select(Client::as_select())
+        .order_by(clients::name)
+        .load(conn)?;
+    let projects = projects::get_all(conn, true)?;
+    OutputFormat::Table.print_table(
+        config.table_style(),
+        &["ID", "Name", "Projects"],
+        clients.iter().map(|client| {
+            let names = projects
+                .iter()
+                .filter(|p| p.client_id == Some(client.id))
+                .map(|p| p.name.clone().unwrap_or_else(|| p.url.clone()))
+                .collect::<Vec<_>>();
+            [client.id.to_string(), client.name.clone(), names.join(", ")]
+        }),
+    )?;
+    Ok(())
+}
+
+/// Logged time of every project in the period grouped by client, with a total row per client
+pub fn show_totals(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    period: Option<&Period>,
+) -> Result<()> {
+    let (from, to) = period.map_or((Date::MIN, Date::MAX), |p| (p.from, p.to));
+    let totals = log_entries::table
+        .inner_join(tasks::table.inner_join(projects_table::table))
+        .filter(log_entries::date.ge(from))
+        .filter(log_entries::date.le(to))
+        .group_by(projects_table::id)
+        .select((
+            projects_table::id,
+            diesel::dsl::sum(log_entries::duration_seconds),
+        ))
+        .load::<(i32, Option<i64>)>(conn)?;
+    let clients = clients::table
+        .select(Client::as_select())
+        .order_by(clients::name)
+        .load(conn)?;
+    let projects = projects::get_all(conn, true)?;
+
+    let client_names = clients
+        .iter()
+        .map(|c| (Some(c.id), c.name.as_str()))
+        .chain([(None, "No client")]);
+    let mut rows = Vec::new();
+    for (client, client_name) in client_names {
+        let mut client_total = Duration::ZERO;
+        for project in projects.iter().filter(|p| p.client_id == client) {
+            let Some((_, seconds)) = totals.iter().find(|(id, _)| *id == project.id.0) else {
+                continue;
+            };
+            let logged = Duration::seconds(seconds.unwrap_or(0));
+            client_total += logged;
+            rows.push([
+                client_name.to_string(),
+                project.name.clone().unwrap_or_else(|| project.url.clone()),
+                config.fmt_duration(logged),
+            ]);
+        }
+        if client_total.is_positive() {
+            rows.push([
+                client_name.to_string(),
+                "Total".to_string(),
+                config.fmt_duration(client_total),
+            ]);
+        }
+    }
+    OutputFormat::Table.print_table(
+        config.table_style(),
+        &["Client", "Project", "Logged"],
+        rows,
+    )?;
+    Ok(())
+}
@@ -1,54 +1,299 @@
-use crate::utils::yn_prompt;
+use crate::error::WlogError;
+use crate::hooks::Event;
+use crate::locale::Locale;
+use crate::parsers::DurationSyntax;
+use crate::utils::{fmt_duration, yn_prompt};
 use directories::ProjectDirs;
 use eyre::{Result, anyhow, bail};
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 use std::{io::Write, path::PathBuf};
-use time::Time;
+use time::{Duration, OffsetDateTime, Time, UtcOffset, Weekday};
+
+const LOCAL_CONFIG_NAME: &str = ".wlog.toml";
+
+/// Configuration file given with `--config` or `WLOG_CONFIG`
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+static PLAIN_OUTPUT: OnceLock<bool> = OnceLock::new();
+static HYPERLINKS: OnceLock<bool> = OnceLock::new();
+static TERMINAL_WIDTH: OnceLock<u16> = OnceLock::new();
+static DURATION_INPUT: OnceLock<(Vec<DurationSyntax>, Duration)> = OnceLock::new();
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub data_path: PathBuf,
     pub day_change_threshold: Option<Time>,
+    pub locale: Option<Locale>,
+    pub reminder_time: Option<Time>,
+    pub precision: Option<Precision>,
+    /// Fixed UTC offset used instead of the system time zone
+    pub timezone: Option<UtcOffset>,
+    /// Upper limit of the total duration logged per day
+    pub max_daily_minutes: Option<u32>,
+    /// Length of a day in durations given in days, a week being five days
+    pub day_minutes: Option<u32>,
+    /// Logged durations above this or below 5 minutes ask to confirm the intended duration
+    pub suspicious_minutes: Option<u32>,
+    pub table_style: Option<TableStyle>,
+    pub duration_format: Option<DurationFormat>,
+    /// Grammars of duration input accepted besides units like 1h30m
+    pub duration_syntaxes: Option<Vec<DurationSyntax>>,
+    /// Show the logging streak below `show` output
+    pub streak_badge: Option<bool>,
+    /// Warn before every command if the previous workday has no log entries
+    pub nag: Option<bool>,
+    /// URL receiving a JSON POST request for every added or updated log entry
+    pub on_log: Option<String>,
+    /// Weekday from which the first command of the week prints the weekly summary, e.g. "friday"
+    #[serde(default, with = "weekday_name")]
+    pub auto_summary: Option<Weekday>,
+    /// Copy the automatic weekly summary to the clipboard
+    pub auto_summary_copy: Option<bool>,
+    /// Log entries older than this many days can only be modified with `--force`
+    pub edit_grace_days: Option<u32>,
+    /// Ask to confirm dates given with `--day` that fall into another month
+    pub confirm_date: Option<bool>,
+    /// Command run with a JSON payload on stdin for every log entry added
+    pub on_log_added: Option<String>,
+    /// Command run with a JSON payload on stdin when the logged time of a day reaches the schedule
+    pub on_day_complete: Option<String>,
+    /// Command run with a JSON payload on stdin when a month is closed
+    pub on_month_close: Option<String>,
+    /// File appended with a JSON line for every change a command makes to the database
+    pub log_file: Option<PathBuf>,
+    /// Settings of the current directory, see [`Config::load`]
+    #[serde(skip)]
+    pub local: LocalConfig,
+    /// Read from the configuration file rather than defaulted
+    #[serde(skip)]
+    pub from_file: bool,
+}
+
+/// Per-repository settings read from `.wlog.toml` in the current directory or the closest parent
+/// directory containing one
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LocalConfig {
+    /// Project ID used instead of the default project
+    pub project: Option<i32>,
+    /// Prefix of issue references stripped from `--issue`, e.g. "PROJ-"
+    pub issue_prefix: Option<String>,
+    pub precision: Option<Precision>,
+    /// File the settings were read from
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl LocalConfig {
+    pub fn discover() -> Result<Self> {
+        let mut dir = std::env::current_dir()?;
+        loop {
+            let path = dir.join(LOCAL_CONFIG_NAME);
+            match std::fs::read_to_string(&path) {
+                Ok(str) => {
+                    let local: Self = toml::from_str(&str).map_err(|e| {
+                        WlogError::Config(format!("Invalid {}: {e}", path.display()))
+                    })?;
+                    return Ok(Self {
+                        path: Some(path),
+                        ..local
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+            if !dir.pop() {
+                return Ok(Self::default());
+            }
+        }
+    }
+
+    /// Parse issue number, optionally prefixed with `#` or the configured issue prefix
+    pub fn parse_issue(&self, v: &str) -> Result<i32> {
+        let v = self
+            .issue_prefix
+            .as_deref()
+            .and_then(|prefix| v.strip_prefix(prefix))
+            .or_else(|| v.strip_prefix('#'))
+            .unwrap_or(v);
+        v.parse()
+            .map_err(|_| WlogError::Validation(format!("Invalid issue number: \"{v}\"")).into())
+    }
+}
+
+/// Smallest unit of time log durations
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    #[default]
+    Minutes,
+    Seconds,
+}
+
+impl Precision {
+    /// Round the duration to the nearest whole unit
+    pub fn round(self, duration: Duration) -> Duration {
+        match self {
+            Precision::Minutes => {
+                Duration::minutes((duration + Duration::seconds(30)).whole_minutes())
+            }
+            Precision::Seconds => Duration::seconds(duration.whole_seconds()),
+        }
+    }
+}
+
+impl std::fmt::Display for Precision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Precision::Minutes => f.write_str("minutes"),
+            Precision::Seconds => f.write_str("seconds"),
+        }
+    }
+}
+
+/// Display of durations in tables, totals and exports
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationFormat {
+    /// Hours, minutes and seconds, e.g. 7h30m
+    #[default]
+    Standard,
+    /// Decimal hours, e.g. 7.50
+    Industrial,
+}
+
+impl DurationFormat {
+    pub fn format(self, duration: Duration) -> String {
+        match self {
+            DurationFormat::Standard => fmt_duration(duration),
+            DurationFormat::Industrial => format!("{:.2}", duration.as_seconds_f64() / 3600.0),
+        }
+    }
+}
+
+impl std::fmt::Display for DurationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationFormat::Standard => f.write_str("standard"),
+            DurationFormat::Industrial => f.write_str("industrial"),
+        }
+    }
+}
+
+/// Border style of printed tables
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    Ascii,
+    Rounded,
+    #[default]
+    Heavy,
+    Markdown,
+    /// ASCII borders without row separators, wrapped to the terminal width
+    Compact,
+    None,
+}
+
+impl TableStyle {
+    pub fn table(self) -> comfy_table::Table {
+        let mut table = comfy_table::Table::new();
+        match self {
+            TableStyle::Ascii => table.load_preset(comfy_table::presets::ASCII_FULL),
+            TableStyle::Compact => {
+                table
+                    .load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED)
+                    .set_content_arrangement(comfy_table::ContentArrangement::Dynamic);
+                // Output collected for the pager isn't a terminal with a width of its own
+                if let Some(width) = terminal_width() {
+                    table.set_width(width);
+                }
+                &mut table
+            }
+            TableStyle::Rounded => table
+                .load_preset(comfy_table::presets::UTF8_FULL)
+                .apply_modifier(comfy_table::modifiers::UTF8_ROUND_CORNERS),
+            TableStyle::Heavy => table.load_preset(crate::utils::TABLE_STYLE),
+            TableStyle::Markdown => table.load_preset(comfy_table::presets::ASCII_MARKDOWN),
+            TableStyle::None => table.load_preset(comfy_table::presets::NOTHING),
+        };
+        table
+    }
+}
+
+impl std::fmt::Display for TableStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableStyle::Ascii => f.write_str("ascii"),
+            TableStyle::Rounded => f.write_str("rounded"),
+            TableStyle::Heavy => f.write_str("heavy"),
+            TableStyle::Markdown => f.write_str("markdown"),
+            TableStyle::Compact => f.write_str("compact"),
+            TableStyle::None => f.write_str("none"),
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let data_path = directories().unwrap().data_dir().join("wlog.db");
+        let data_path = default_data_path();
         Self {
             data_path,
             day_change_threshold: None,
+            locale: None,
+            reminder_time: None,
+            precision: None,
+            timezone: None,
+            max_daily_minutes: None,
+            day_minutes: None,
+            suspicious_minutes: None,
+            table_style: None,
+            duration_format: None,
+            duration_syntaxes: None,
+            streak_badge: None,
+            nag: None,
+            on_log: None,
+            auto_summary: None,
+            auto_summary_copy: None,
+            edit_grace_days: None,
+            confirm_date: None,
+            on_log_added: None,
+            on_day_complete: None,
+            on_month_close: None,
+            log_file: None,
+            local: LocalConfig::default(),
+            from_file: false,
         }
     }
 }
 
 impl Config {
     pub fn read() -> Result<Option<Self>> {
-        let config_path = directories()?.config_dir().join("config.toml");
+        let config_path = config_path()?;
         let config_str = match std::fs::read_to_string(config_path) {
             Ok(str) => str,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
             Err(e) => return Err(e.into()),
         };
-        toml::from_str(&config_str).map(Some).map_err(Into::into)
+        toml::from_str(&config_str)
+            .map(|config| {
+                Some(Self {
+                    from_file: true,
+                    ..config
+                })
+            })
+            .map_err(|e| WlogError::Config(format!("Invalid configuration: {e}")).into())
     }
 
-    pub fn update_data_path(data_path: PathBuf) -> Result<Self> {
-        let dirs = directories()?;
-        let config_folder = dirs.config_dir();
-        std::fs::create_dir_all(config_folder)?;
-        let config_path = config_folder.join("config.toml");
-
-        let mut config = match std::fs::read_to_string(&config_path) {
-            Ok(str) => toml::from_str(&str)?,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
-            Err(e) => return Err(e.into()),
-        };
-        config.data_path = data_path;
+    /// Global configuration with settings of the current directory applied
+    pub fn load() -> Result<Self> {
+        let mut config = Self::read()?.unwrap_or_default();
+        config.local = LocalConfig::discover()?;
+        Ok(config)
+    }
 
-        let mut f = std::fs::File::create(&config_path)?;
-        let config_str = toml::to_string_pretty(&config)?;
-        f.write_all(config_str.as_bytes())?;
+    pub fn update_data_path(data_path: PathBuf) -> Result<Self> {
+        let config = Self::update(|config| config.data_path = data_path)?;
 
         eprintln!(
             "{} Data path updated to {}",
@@ -60,21 +305,7 @@ impl Config {
     }
 
     pub fn update_day_change_threshold(threshold: Time) -> Result<Self> {
-        let dirs = directories()?;
-        let config_folder = dirs.config_dir();
-        std::fs::create_dir_all(config_folder)?;
-        let config_path = config_folder.join("config.toml");
-
-        let mut config = match std::fs::read_to_string(&config_path) {
-            Ok(str) => toml::from_str(&str)?,
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
-            Err(e) => return Err(e.into()),
-        };
-        config.day_change_threshold = Some(threshold);
-
-        let mut f = std::fs::File::create(&config_path)?;
-        let config_str = toml::to_string_pretty(&config)?;
-        f.write_all(config_str.as_bytes())?;
+        let config = Self::update(|config| config.day_change_threshold = Some(threshold))?;
 
         eprintln!(
             "{} Day change threshold updated to {threshold}",
@@ -84,20 +315,252 @@ impl Config {
         Ok(config)
     }
 
-    pub fn reset() -> Result<()> {
-        if !yn_prompt("Do you want to reset to default configuration?")? {
-            bail!("Config reset aborted");
+    pub fn update_locale(locale: Locale) -> Result<Self> {
+        let config = Self::update(|config| config.locale = Some(locale))?;
+
+        eprintln!("{} Locale updated to {locale}", "Success:".green().bold());
+
+        Ok(config)
+    }
+
+    pub fn update_reminder_time(time: Time) -> Result<Self> {
+        let config = Self::update(|config| config.reminder_time = Some(time))?;
+
+        eprintln!(
+            "{} Reminder time updated to {time}",
+            "Success:".green().bold()
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_precision(precision: Precision) -> Result<Self> {
+        let config = Self::update(|config| config.precision = Some(precision))?;
+
+        eprintln!(
+            "{} Duration precision updated to {precision}",
+            "Success:".green().bold()
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_timezone(timezone: Option<UtcOffset>) -> Result<Self> {
+        let config = Self::update(|config| config.timezone = timezone)?;
+
+        match timezone {
+            Some(offset) => eprintln!(
+                "{} Time zone updated to UTC{offset}",
+                "Success:".green().bold()
+            ),
+            None => eprintln!(
+                "{} Time zone reset to system default",
+                "Success:".green().bold()
+            ),
         }
-        let dirs = directories()?;
-        let config_folder = dirs.config_dir();
-        std::fs::create_dir_all(config_folder)?;
-        let config_path = config_folder.join("config.toml");
 
-        let config = Config::default();
+        Ok(config)
+    }
 
-        let mut f = std::fs::File::create(&config_path)?;
-        let config_str = toml::to_string_pretty(&config)?;
-        f.write_all(config_str.as_bytes())?;
+    pub fn update_day_duration(day: Duration) -> Result<Self> {
+        let minutes = positive_minutes(day, "Day length")?;
+        let config = Self::update(|config| config.day_minutes = Some(minutes))?;
+
+        eprintln!(
+            "{} Day length updated to {}",
+            "Success:".green().bold(),
+            fmt_duration(config.day_duration())
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_max_daily_duration(max: Duration) -> Result<Self> {
+        let minutes = positive_minutes(max, "Daily maximum")?;
+        let config = Self::update(|config| config.max_daily_minutes = Some(minutes))?;
+
+        eprintln!(
+            "{} Daily maximum updated to {}",
+            "Success:".green().bold(),
+            fmt_duration(config.max_daily_duration())
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_suspicious_duration(threshold: Duration) -> Result<Self> {
+        let minutes = positive_minutes(threshold, "Suspicious duration threshold")?;
+        let config = Self::update(|config| config.suspicious_minutes = Some(minutes))?;
+
+        eprintln!(
+            "{} Suspicious duration threshold updated to {}",
+            "Success:".green().bold(),
+            fmt_duration(config.suspicious_duration())
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_duration_format(format: DurationFormat) -> Result<Self> {
+        let config = Self::update(|config| config.duration_format = Some(format))?;
+
+        eprintln!(
+            "{} Duration format updated to {format}",
+            "Success:".green().bold()
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_duration_syntaxes(syntaxes: Vec<DurationSyntax>) -> Result<Self> {
+        let config = Self::update(|config| {
+            config.duration_syntaxes = (!syntaxes.is_empty()).then(|| syntaxes.clone())
+        })?;
+
+        match config.duration_syntaxes.as_deref() {
+            Some(syntaxes) => eprintln!(
+                "{} Durations are accepted in units and as {}",
+                "Success:".green().bold(),
+                fmt_syntaxes(syntaxes)
+            ),
+            None => eprintln!(
+                "{} Durations are only accepted in units",
+                "Success:".green().bold()
+            ),
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_table_style(style: TableStyle) -> Result<Self> {
+        let config = Self::update(|config| config.table_style = Some(style))?;
+
+        eprintln!(
+            "{} Table style updated to {style}",
+            "Success:".green().bold()
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_streak_badge(enabled: bool) -> Result<Self> {
+        let config = Self::update(|config| config.streak_badge = Some(enabled))?;
+
+        let state = if enabled { "enabled" } else { "disabled" };
+        eprintln!("{} Streak badge {state}", "Success:".green().bold());
+
+        Ok(config)
+    }
+
+    pub fn update_nag(enabled: bool) -> Result<Self> {
+        let config = Self::update(|config| config.nag = Some(enabled))?;
+
+        let state = if enabled { "enabled" } else { "disabled" };
+        eprintln!(
+            "{} Unlogged workday warning {state}",
+            "Success:".green().bold()
+        );
+
+        Ok(config)
+    }
+
+    pub fn update_on_log(url: Option<String>) -> Result<Self> {
+        let config = Self::update(|config| config.on_log = url)?;
+
+        match &config.on_log {
+            Some(url) => eprintln!("{} Log webhook set to {url}", "Success:".green().bold()),
+            None => eprintln!("{} Log webhook removed", "Success:".green().bold()),
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_auto_summary(day: Option<Weekday>, copy: bool) -> Result<Self> {
+        let config = Self::update(|config| {
+            config.auto_summary = day;
+            config.auto_summary_copy = day.map(|_| copy);
+        })?;
+
+        match config.auto_summary() {
+            Some(day) => eprintln!(
+                "{} Weekly summary is printed from {day} on",
+                "Success:".green().bold()
+            ),
+            None => eprintln!("{} Weekly summary disabled", "Success:".green().bold()),
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_edit_grace_days(days: Option<u32>) -> Result<Self> {
+        let config = Self::update(|config| config.edit_grace_days = days)?;
+
+        match days {
+            Some(days) => eprintln!(
+                "{} Entries older than {days} days now require --force to modify",
+                "Success:".green().bold()
+            ),
+            None => eprintln!("{} Edit grace period removed", "Success:".green().bold()),
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_log_file(path: Option<PathBuf>) -> Result<Self> {
+        // Relative paths would point elsewhere depending on the working directory of a command
+        let path = path.map(std::path::absolute).transpose()?;
+        if let Some(path) = &path {
+            crate::event_log::check(path)?;
+        }
+        let config = Self::update(|config| config.log_file = path)?;
+
+        match &config.log_file {
+            Some(path) => eprintln!(
+                "{} Database changes are logged to {}",
+                "Success:".green().bold(),
+                path.display()
+            ),
+            None => eprintln!("{} Event log disabled", "Success:".green().bold()),
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_hook(event: Event, command: Option<String>) -> Result<Self> {
+        let config = Self::update(|config| match event {
+            Event::LogAdded => config.on_log_added = command,
+            Event::DayComplete => config.on_day_complete = command,
+            Event::MonthClose => config.on_month_close = command,
+        })?;
+
+        match config.hook(event) {
+            Some(command) => eprintln!(
+                "{} Hook on {event} set to `{command}`",
+                "Success:".green().bold()
+            ),
+            None => eprintln!("{} Hook on {event} removed", "Success:".green().bold()),
+        }
+
+        Ok(config)
+    }
+
+    pub fn update_confirm_date(enabled: bool) -> Result<Self> {
+        let config = Self::update(|config| config.confirm_date = Some(enabled))?;
+
+        let state = if enabled { "enabled" } else { "disabled" };
+        eprintln!(
+            "{} Confirmation of dates in another month {state}",
+            "Success:".green().bold()
+        );
+
+        Ok(config)
+    }
+
+    pub fn reset() -> Result<()> {
+        if !yn_prompt("Do you want to reset to default configuration?")? {
+            bail!(WlogError::Canceled("Config reset aborted".into()));
+        }
+        Config::default().write()?;
 
         eprintln!(
             "{} Default configuration restored",
@@ -111,6 +574,441 @@ impl Config {
         self.day_change_threshold
             .unwrap_or_else(|| Time::from_hms(12, 0, 0).unwrap())
     }
+
+    pub fn edit() -> Result<()> {
+        let config_path = config_path()?;
+        let original = match std::fs::read_to_string(&config_path) {
+            Ok(str) => Some(str),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+
+        // The configuration is only replaced once the edit parses, so an aborted editor or a
+        // crash never leaves a broken or empty file behind
+        let edit_path = config_path.with_extension("edit.toml");
+        if let Some(config_folder) = edit_path.parent() {
+            std::fs::create_dir_all(config_folder)?;
+        }
+        let initial = match &original {
+            Some(original) => original.clone(),
+            None => toml::to_string_pretty(&Config::default())?,
+        };
+        std::fs::write(&edit_path, &initial)?;
+        let edited = Self::edit_file(&editor, &edit_path);
+        std::fs::remove_file(&edit_path)?;
+        let edited = edited?;
+
+        if original.is_some() && edited == initial {
+            eprintln!("{} Configuration unchanged", "Note:".cyan());
+            return Ok(());
+        }
+        std::fs::write(&config_path, edited)?;
+        eprintln!("{} Configuration updated", "Success:".green().bold());
+        Ok(())
+    }
+
+    /// Run the editor on the file until its content parses, returns the content
+    fn edit_file(editor: &str, path: &std::path::Path) -> Result<String> {
+        loop {
+            // Editors are often given with arguments, e.g. `code --wait`
+            let mut args = editor.split_whitespace();
+            let program = args.next().unwrap_or("vi");
+            let status = std::process::Command::new(program)
+                .args(args)
+                .arg(path)
+                .status()?;
+            if !status.success() {
+                bail!(WlogError::Canceled(format!(
+                    "Editor exited with {status}, configuration left unchanged"
+                )));
+            }
+
+            let edited = std::fs::read_to_string(path)?;
+            match toml::from_str::<Config>(&edited) {
+                Ok(_) => return Ok(edited),
+                Err(e) => {
+                    eprintln!("{} Invalid configuration: {e}", "Error:".red().bold());
+                    if !yn_prompt("Edit again?")? {
+                        bail!(WlogError::Canceled(
+                            "Config edit aborted, configuration left unchanged".into()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn print(&self) {
+        let mut table = self.table_style().table();
+        table.set_header(["Setting", "Value", "Source"]);
+        let source = |is_set: bool| if is_set { "config" } else { "default" };
+        let local_source = self.local.path.as_ref().map_or_else(
+            || LOCAL_CONFIG_NAME.to_string(),
+            |path| path.display().to_string(),
+        );
+        table.add_row([
+            "data_path",
+            &self.data_path.to_string_lossy(),
+            source(self.from_file),
+        ]);
+        table.add_row([
+            "day_change_threshold",
+            &self.day_change_threshold().to_string(),
+            source(self.day_change_threshold.is_some()),
+        ]);
+        table.add_row([
+            "locale",
+            &self.locale().to_string(),
+            source(self.locale.is_some()),
+        ]);
+        table.add_row([
+            "reminder_time",
+            &self.reminder_time().to_string(),
+            source(self.reminder_time.is_some()),
+        ]);
+        table.add_row([
+            "timezone",
+            &self
+                .timezone
+                .map(|offset| format!("UTC{offset}"))
+                .unwrap_or_else(|| "system".to_string()),
+            source(self.timezone.is_some()),
+        ]);
+        table.add_row([
+            "precision",
+            &self.precision().to_string(),
+            if self.local.precision.is_some() {
+                &local_source
+            } else {
+                source(self.precision.is_some())
+            },
+        ]);
+        table.add_row([
+            "max_daily_minutes",
+            &self.max_daily_duration().whole_minutes().to_string(),
+            source(self.max_daily_minutes.is_some()),
+        ]);
+        table.add_row([
+            "day_minutes",
+            &self.day_duration().whole_minutes().to_string(),
+            source(self.day_minutes.is_some()),
+        ]);
+        table.add_row([
+            "suspicious_minutes",
+            &self.suspicious_duration().whole_minutes().to_string(),
+            source(self.suspicious_minutes.is_some()),
+        ]);
+        table.add_row([
+            "table_style",
+            &self.table_style().to_string(),
+            source(self.table_style.is_some()),
+        ]);
+        table.add_row([
+            "duration_format",
+            &self.duration_format().to_string(),
+            source(self.duration_format.is_some()),
+        ]);
+        table.add_row([
+            "duration_syntaxes",
+            &fmt_syntaxes(self.duration_syntaxes()),
+            source(self.duration_syntaxes.is_some()),
+        ]);
+        table.add_row([
+            "streak_badge",
+            &self.streak_badge().to_string(),
+            source(self.streak_badge.is_some()),
+        ]);
+        table.add_row(["nag", &self.nag().to_string(), source(self.nag.is_some())]);
+        table.add_row([
+            "on_log",
+            self.on_log.as_deref().unwrap_or("-"),
+            source(self.on_log.is_some()),
+        ]);
+        table.add_row([
+            "auto_summary",
+            &self
+                .auto_summary
+                .map_or_else(|| "-".to_string(), |day| day.to_string().to_lowercase()),
+            source(self.auto_summary.is_some()),
+        ]);
+        table.add_row([
+            "auto_summary_copy",
+            &self.auto_summary_copy().to_string(),
+            source(self.auto_summary_copy.is_some()),
+        ]);
+        table.add_row([
+            "edit_grace_days",
+            &self
+                .edit_grace_days
+                .map(|days| days.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            source(self.edit_grace_days.is_some()),
+        ]);
+        table.add_row([
+            "confirm_date",
+            &self.confirm_date().to_string(),
+            source(self.confirm_date.is_some()),
+        ]);
+        for event in [Event::LogAdded, Event::DayComplete, Event::MonthClose] {
+            table.add_row([
+                &format!("on_{event}"),
+                self.hook(event).unwrap_or("-"),
+                source(self.hook(event).is_some()),
+            ]);
+        }
+        table.add_row([
+            "log_file",
+            &self
+                .log_file
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            source(self.log_file.is_some()),
+        ]);
+        // Only set by the local configuration, otherwise taken from the database
+        if let Some(project) = self.local.project {
+            table.add_row(["project", &project.to_string(), &local_source]);
+        }
+        if let Some(prefix) = &self.local.issue_prefix {
+            table.add_row(["issue_prefix", prefix, &local_source]);
+        }
+        println!("{table}");
+    }
+
+    pub fn reminder_time(&self) -> Time {
+        self.reminder_time
+            .unwrap_or_else(|| Time::from_hms(17, 0, 0).unwrap())
+    }
+
+    /// Current time in the configured time zone
+    pub fn now(&self) -> Result<OffsetDateTime> {
+        match self.timezone {
+            Some(offset) => Ok(OffsetDateTime::now_utc().to_offset(offset)),
+            None => OffsetDateTime::now_local().map_err(Into::into),
+        }
+    }
+
+    pub fn precision(&self) -> Precision {
+        self.local.precision.or(self.precision).unwrap_or_default()
+    }
+
+    pub fn max_daily_duration(&self) -> Duration {
+        Duration::minutes(self.max_daily_minutes.unwrap_or(24 * 60) as i64)
+    }
+
+    pub fn day_duration(&self) -> Duration {
+        Duration::minutes(self.day_minutes.unwrap_or(8 * 60) as i64)
+    }
+
+    pub fn suspicious_duration(&self) -> Duration {
+        Duration::minutes(self.suspicious_minutes.unwrap_or(16 * 60) as i64)
+    }
+
+    pub fn duration_syntaxes(&self) -> &[DurationSyntax] {
+        self.duration_syntaxes.as_deref().unwrap_or_default()
+    }
+
+    pub fn table_style(&self) -> TableStyle {
+        match self.table_style.unwrap_or_default() {
+            TableStyle::Ascii | TableStyle::Rounded | TableStyle::Heavy if plain_output() => {
+                TableStyle::Compact
+            }
+            style => style,
+        }
+    }
+
+    pub fn duration_format(&self) -> DurationFormat {
+        self.duration_format.unwrap_or_default()
+    }
+
+    /// Duration in the configured format
+    pub fn fmt_duration(&self, duration: Duration) -> String {
+        self.duration_format().format(duration)
+    }
+
+    pub fn streak_badge(&self) -> bool {
+        self.streak_badge.unwrap_or(false)
+    }
+
+    pub fn nag(&self) -> bool {
+        self.nag.unwrap_or(false)
+    }
+
+    pub fn confirm_date(&self) -> bool {
+        self.confirm_date.unwrap_or(true)
+    }
+
+    /// Command run on the event, if configured
+    pub fn hook(&self, event: Event) -> Option<&str> {
+        match event {
+            Event::LogAdded => self.on_log_added.as_deref(),
+            Event::DayComplete => self.on_day_complete.as_deref(),
+            Event::MonthClose => self.on_month_close.as_deref(),
+        }
+    }
+
+    pub fn auto_summary(&self) -> Option<Weekday> {
+        self.auto_summary
+    }
+
+    pub fn auto_summary_copy(&self) -> bool {
+        self.auto_summary_copy.unwrap_or(false)
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_default()
+    }
+
+    fn update(f: impl FnOnce(&mut Self)) -> Result<Self> {
+        let mut config = Self::read()?.unwrap_or_default();
+        f(&mut config);
+        config.write()?;
+        Ok(config)
+    }
+
+    fn write(&self) -> Result<()> {
+        let config_path = config_path()?;
+        if let Some(config_folder) = config_path.parent() {
+            std::fs::create_dir_all(config_folder)?;
+        }
+
+        let mut f = std::fs::File::create(&config_path)?;
+        let config_str = toml::to_string_pretty(self)?;
+        f.write_all(config_str.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Whole minutes of a duration setting, fails if it's shorter than a minute or doesn't fit
+fn positive_minutes(duration: Duration, setting: &str) -> Result<u32> {
+    if duration < Duration::MINUTE {
+        bail!(WlogError::Validation(format!(
+            "{setting} must be at least a minute, got {}",
+            fmt_duration(duration)
+        )));
+    }
+    u32::try_from(duration.whole_minutes()).map_err(|_| {
+        WlogError::Validation(format!(
+            "{setting} of {} is too long",
+            fmt_duration(duration)
+        ))
+        .into()
+    })
+}
+
+/// Use the file instead of the configuration in the standard config directory. The database
+/// defaults to the same directory as well.
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+/// Print compact ASCII tables, truncate long task names and leave out hyperlinks
+pub fn set_plain_output(plain: bool) {
+    let _ = PLAIN_OUTPUT.set(plain);
+}
+
+pub fn plain_output() -> bool {
+    PLAIN_OUTPUT.get().copied().unwrap_or(false)
+}
+
+/// Link issues in terminal tables, set when stdout is a terminal rendering escape sequences
+pub fn set_hyperlinks(enabled: bool) {
+    let _ = HYPERLINKS.set(enabled);
+}
+
+pub fn hyperlinks() -> bool {
+    HYPERLINKS.get().copied().unwrap_or(false) && !plain_output()
+}
+
+/// Width of the terminal tables are wrapped to, set when stdout is redirected to the pager
+pub fn set_terminal_width(width: u16) {
+    let _ = TERMINAL_WIDTH.set(width);
+}
+
+pub fn terminal_width() -> Option<u16> {
+    TERMINAL_WIDTH.get().copied()
+}
+
+/// Parse durations in arguments with the grammars and the length of a day of the configuration
+pub fn set_duration_input(config: &Config) {
+    let _ = DURATION_INPUT.set((config.duration_syntaxes().to_vec(), config.day_duration()));
+}
+
+/// Length of a day in durations given in days, see [`set_duration_input`]
+pub fn day_duration() -> Duration {
+    DURATION_INPUT
+        .get()
+        .map(|(_, day)| *day)
+        .unwrap_or_else(|| Config::default().day_duration())
+}
+
+/// Enabled grammars of duration input, see [`set_duration_input`]
+pub fn duration_syntaxes() -> Vec<DurationSyntax> {
+    DURATION_INPUT
+        .get()
+        .map(|(syntaxes, _)| syntaxes.clone())
+        .unwrap_or_default()
+}
+
+/// Comma-separated grammar names, "-" for none
+pub fn fmt_syntaxes(syntaxes: &[DurationSyntax]) -> String {
+    if syntaxes.is_empty() {
+        return "-".to_string();
+    }
+    syntaxes
+        .iter()
+        .map(|syntax| syntax.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Weekdays stored by lowercase English name, names of every locale are accepted
+mod weekday_name {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use time::Weekday;
+
+    pub fn serialize<S: Serializer>(day: &Option<Weekday>, s: S) -> Result<S::Ok, S::Error> {
+        match day {
+            Some(day) => s.serialize_some(&day.to_string().to_lowercase()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Weekday>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|name| {
+                crate::locale::parse_weekday(&name)
+                    .ok_or_else(|| serde::de::Error::custom(format!("invalid weekday \"{name}\"")))
+            })
+            .transpose()
+    }
+}
+
+fn config_path() -> Result<PathBuf> {
+    if let Some(path) = CONFIG_PATH.get() {
+        return Ok(path.clone());
+    }
+    Ok(directories()?.config_dir().join("config.toml"))
+}
+
+fn default_data_path() -> PathBuf {
+    match CONFIG_PATH.get() {
+        Some(path) => path.with_file_name("wlog.db"),
+        None => directories().unwrap().data_dir().join("wlog.db"),
+    }
+}
+
+/// Directory with input histories of interactive prompts, next to the database by default
+pub fn history_dir() -> Option<PathBuf> {
+    match CONFIG_PATH.get() {
+        Some(path) => Some(path.with_file_name("history")),
+        None => directories()
+            .ok()
+            .map(|dirs| dirs.data_dir().join("history")),
+    }
 }
 
 fn directories() -> Result<ProjectDirs> {
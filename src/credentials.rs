@@ -0,0 +1,28 @@
+use crate::projects::ProjectId;
+use eyre::Result;
+
+const SERVICE: &str = "wlog";
+
+pub fn set_token(project: ProjectId, token: &str) -> Result<()> {
+    entry(project)?.set_password(token)?;
+    Ok(())
+}
+
+pub fn get_token(project: ProjectId) -> Result<Option<String>> {
+    match entry(project)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn remove_token(project: ProjectId) -> Result<()> {
+    match entry(project)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn entry(project: ProjectId) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, &format!("project-{}", project.0)).map_err(Into::into)
+}
@@ -0,0 +1,403 @@
+use crate::Config;
+use crate::caps;
+use crate::error::WlogError;
+use crate::hooks;
+use crate::log_entries::{self, EntrySource, Limits, LogEntry, Period};
+use crate::projects;
+use crate::tasks;
+use crate::webhook;
+use diesel::{Connection, SqliteConnection};
+use eyre::{Result, bail};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use time::{Date, Duration};
+
+const PARSE_ERROR: i32 = -32700;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+/// Request was valid but wlog refused or failed to execute it
+const SERVER_ERROR: i32 = -32000;
+
+/// JSON-RPC 2.0 request, one per line. Requests without an ID are notifications and get no
+/// response.
+#[derive(Debug, Deserialize)]
+struct Request {
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+/// Parameters of the `log` method. The entry is added to the default project, for a task found by
+/// issue number or by name and created if only the name matches nothing.
+#[derive(Debug, Deserialize)]
+struct LogParams {
+    seconds: i64,
+    task: Option<String>,
+    issue: Option<i32>,
+    /// ISO8601 date, the current day by default
+    date: Option<String>,
+    /// Log even if the daily maximum is exceeded or the date is past the edit grace period
+    #[serde(default)]
+    force: bool,
+}
+
+/// Monthly label cap of the logged task exceeded in the month of the entry
+#[derive(Debug, Serialize)]
+struct ExceededCap {
+    label: String,
+    cap_seconds: i64,
+    logged_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct TodayEntry {
+    task_id: i32,
+    task: String,
+    issue: Option<i32>,
+    seconds: i64,
+}
+
+/// Serve JSON-RPC requests on a Unix socket until the process is stopped. The database stays open
+/// between requests, so that editor plugins don't pay for process startup on every call.
+pub fn serve(config: Config, socket: &Path) -> Result<()> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!(WlogError::Validation(format!(
+                "Another daemon is listening on {}",
+                socket.display()
+            )));
+        }
+        // Left behind by a daemon that was killed
+        std::fs::remove_file(socket)?;
+    }
+    // The socket is created accessible to the user only, setting permissions after binding would
+    // let other users connect in between
+    // SAFETY: umask only changes the process file mode mask, no other threads are running yet
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(socket);
+    // SAFETY: as above
+    unsafe { libc::umask(umask) };
+    let listener = listener?;
+    let conn = Arc::new(Mutex::new(crate::data::open(config.data_path.as_ref())?));
+    let config = Arc::new(config);
+    // Webhook delivery can take seconds, it has its own connection so that requests aren't blocked
+    let (flush, pending) = mpsc::channel();
+    let flush_conn = crate::data::open(config.data_path.as_ref())?;
+    let flush_config = config.clone();
+    std::thread::spawn(move || flusher(flush_conn, &flush_config, pending));
+    eprintln!("{} Listening on {}", "Info:".cyan(), socket.display());
+
+    for stream in listener.incoming() {
+        // A failed connection, e.g. when running out of file descriptors, doesn't stop the daemon
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{} Connection failed: {e}", "Warning:".yellow().bold());
+                continue;
+            }
+        };
+        let (conn, config, flush) = (conn.clone(), config.clone(), flush.clone());
+        std::thread::spawn(move || {
+            if let Err(e) = handle_client(stream, &conn, &config, &flush) {
+                eprintln!("{} Client disconnected: {e}", "Warning:".yellow().bold());
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Flush the webhook queue whenever a log entry was added, until all clients are gone
+fn flusher(mut conn: SqliteConnection, config: &Config, pending: Receiver<()>) {
+    while pending.recv().is_ok() {
+        // Entries added in the meantime are delivered by the same flush
+        while pending.try_recv().is_ok() {}
+        if let Err(e) = webhook::flush(&mut conn, config) {
+            eprintln!(
+                "{} Webhook delivery failed: {e}",
+                "Warning:".yellow().bold()
+            );
+        }
+    }
+}
+
+fn handle_client(
+    stream: UnixStream,
+    conn: &Mutex<SqliteConnection>,
+    config: &Config,
+    flush: &mpsc::Sender<()>,
+) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let result = {
+                    let mut conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+                    call(&mut conn, config, &request.method, request.params)
+                };
+                if request.method == "log" && result.is_ok() {
+                    // The flusher only stops with the daemon
+                    let _ = flush.send(());
+                }
+                let Some(id) = request.id else {
+                    continue;
+                };
+                response(id, result)
+            }
+            Err(e) => response(
+                Value::Null,
+                Err(RpcError {
+                    code: PARSE_ERROR,
+                    message: e.to_string(),
+                }),
+            ),
+        };
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+    }
+    Ok(())
+}
+
+fn response(id: Value, result: std::result::Result<Value, RpcError>) -> Response {
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err(error) => (None, Some(error)),
+    };
+    Response {
+        jsonrpc: "2.0",
+        id,
+        result,
+        error,
+    }
+}
+
+fn call(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    method: &str,
+    params: Value,
+) -> std::result::Result<Value, RpcError> {
+    let server_error = |e: eyre::Report| RpcError {
+        code: SERVER_ERROR,
+        message: e.to_string(),
+    };
+    match method {
+        "log" => {
+            let params = serde_json::from_value(params).map_err(|e| RpcError {
+                code: INVALID_PARAMS,
+                message: e.to_string(),
+            })?;
+            log(conn, config, params).map_err(server_error)
+        }
+        "today" => today(conn, config).map_err(server_error),
+        _ => Err(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("Unknown method \"{method}\""),
+        }),
+    }
+}
+
+fn log(conn: &mut SqliteConnection, config: &Config, params: LogParams) -> Result<Value> {
+    let Some(project) = projects::get_current(conn, config)? else {
+        bail!(WlogError::NotFound("No default project".into()));
+    };
+    let now = config.now()?;
+    let date = match &params.date {
+        Some(date) => parse_date(date)?,
+        None => current_day(config)?,
+    };
+    let duration = config.precision().round(Duration::seconds(params.seconds));
+    let limits = Limits::new(config, params.force)?;
+
+    let (task, hooks) = conn.transaction(|conn| {
+        let task = match (params.issue, params.task.as_deref()) {
+            (None, None) => bail!(WlogError::Validation("Task or issue is required".into())),
+            (Some(issue), None) => {
+                tasks::get_by_issue(conn, project.id, issue)?.ok_or_else(|| {
+                    WlogError::NotFound(format!("No task with issue {}", project.issue_key(issue)))
+                })?
+            }
+            (issue, name) => tasks::get_or_create_interactive(conn, project.id, issue, name)?,
+        };
+        log_entries::add_log(
+            conn,
+            project.id,
+            LogEntry {
+                date,
+                task,
+                duration,
+                utc_offset: Some(now.offset()),
+                start: None,
+                source: EntrySource::Api,
+            },
+            &limits,
+        )?;
+        webhook::enqueue(conn, config, date, task)?;
+        let hooks = hooks::log_added(conn, config, date, task, duration)?;
+        eyre::Ok((task, hooks))
+    })?;
+    hooks::run(hooks);
+    caps::warn_exceeded(conn, config, project.id, date, task)?;
+    let exceeded = caps::exceeded(conn, project.id, date, task)?
+        .into_iter()
+        .map(|usage| ExceededCap {
+            label: usage.label,
+            cap_seconds: usage.cap.whole_seconds(),
+            logged_seconds: usage.logged.whole_seconds(),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(serde_json::json!({
+        "date": date.to_string(),
+        "task_id": task.0,
+        "seconds": duration.whole_seconds(),
+        "exceeded_caps": exceeded,
+    }))
+}
+
+/// Entries of the default project on the current day
+fn today(conn: &mut SqliteConnection, config: &Config) -> Result<Value> {
+    let Some(project) = projects::get_current(conn, config)? else {
+        bail!(WlogError::NotFound("No default project".into()));
+    };
+    let date = current_day(config)?;
+    let period = Period {
+        from: date,
+        to: date,
+    };
+    let entries = log_entries::get_by_day_expanded(conn, project.id, Some(&period), None)?
+        .into_iter()
+        .map(|entry| TodayEntry {
+            task_id: entry.task_id.0,
+            task: entry.task_name,
+            issue: entry.issue_number,
+            seconds: entry.duration.whole_seconds(),
+        })
+        .collect::<Vec<_>>();
+    let total: i64 = entries.iter().map(|entry| entry.seconds).sum();
+    Ok(serde_json::json!({
+        "date": date.to_string(),
+        "total_seconds": total,
+        "entries": entries,
+    }))
+}
+
+/// Current date, before the day change threshold it's still the previous day
+fn current_day(config: &Config) -> Result<Date> {
+    let now = config.now()?;
+    Ok(if now.time() < config.day_change_threshold() {
+        now.date().previous_day().unwrap()
+    } else {
+        now.date()
+    })
+}
+
+fn parse_date(v: &str) -> Result<Date> {
+    Date::parse(v, &time::format_description::well_known::Iso8601::DATE)
+        .map_err(|e| WlogError::Validation(format!("Invalid date \"{v}\": {e}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data;
+    use std::io::Read;
+
+    fn config(project: projects::ProjectId) -> Config {
+        Config {
+            timezone: Some(time::UtcOffset::UTC),
+            local: crate::config::LocalConfig {
+                project: Some(project.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Send the lines to a client connection and read all responses
+    fn exchange(lines: &[&str]) -> Vec<Value> {
+        let (conn, project) = data::open_memory_with_project();
+        let config = config(project);
+        let conn = Mutex::new(conn);
+        let (flush, _pending) = mpsc::channel();
+        let (mut client, server) = UnixStream::pair().unwrap();
+        let output = std::thread::scope(|s| {
+            let handler = s.spawn(|| handle_client(server, &conn, &config, &flush));
+            for line in lines {
+                writeln!(client, "{line}").unwrap();
+            }
+            client.shutdown(std::net::Shutdown::Write).unwrap();
+            let mut output = String::new();
+            client.read_to_string(&mut output).unwrap();
+            handler.join().unwrap().unwrap();
+            output
+        });
+        output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn protocol_errors() {
+        let responses = exchange(&[
+            "not json",
+            r#"{"jsonrpc":"2.0","id":1,"method":"unknown"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"log","params":{"task":"Task"}}"#,
+            r#"{"jsonrpc":"2.0","method":"unknown"}"#,
+            r#"{"jsonrpc":"2.0","id":3,"method":"log","params":{"seconds":60}}"#,
+        ]);
+        let codes = responses
+            .iter()
+            .map(|response| (response["id"].clone(), response["error"]["code"].as_i64()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            codes,
+            [
+                (Value::Null, Some(PARSE_ERROR as i64)),
+                (Value::from(1), Some(METHOD_NOT_FOUND as i64)),
+                (Value::from(2), Some(INVALID_PARAMS as i64)),
+                (Value::from(3), Some(SERVER_ERROR as i64)),
+            ]
+        );
+    }
+
+    #[test]
+    fn log_and_today() {
+        let responses = exchange(&[
+            r#"{"jsonrpc":"2.0","id":1,"method":"log","params":{"task":"Task","seconds":3600}}"#,
+            r#"{"jsonrpc":"2.0","method":"log","params":{"task":"Task","seconds":1800}}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"today"}"#,
+        ]);
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"]["seconds"], 3600);
+        let today = &responses[1]["result"];
+        assert_eq!(today["total_seconds"], 5400);
+        assert_eq!(today["entries"][0]["task"], "Task");
+    }
+}
@@ -1,17 +1,237 @@
+use crate::Config;
+use crate::error::WlogError;
+use crate::utils::OutputFormat;
+use diesel::connection::LoadConnection;
+use diesel::migration::MigrationSource;
 use diesel::prelude::*;
+use diesel::row::{Field, Row};
+use diesel::sqlite::{Sqlite, SqliteType};
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 use eyre::{Result, anyhow};
+use owo_colors::OwoColorize;
 use std::path::Path;
+use std::sync::OnceLock;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
+static NO_MIGRATE: OnceLock<bool> = OnceLock::new();
+
+/// Fail instead of applying pending migrations when opening the database
+pub fn set_no_migrate(no_migrate: bool) {
+    let _ = NO_MIGRATE.set(no_migrate);
+}
+
 pub fn open(path: &Path) -> Result<SqliteConnection> {
+    let mut conn = open_unmigrated(path)?;
+    if NO_MIGRATE.get().copied().unwrap_or(false) {
+        let pending = conn
+            .pending_migrations(MIGRATIONS)
+            .map_err(migration_error)?;
+        if !pending.is_empty() {
+            eyre::bail!(WlogError::Validation(format!(
+                "The database has {} pending migrations, apply them with `wlog data migrate`",
+                pending.len()
+            )));
+        }
+    } else {
+        migrate(&mut conn)?;
+    }
+    Ok(conn)
+}
+
+/// Open the database as is, without applying pending migrations
+pub fn open_unmigrated(path: &Path) -> Result<SqliteConnection> {
     let mut conn = SqliteConnection::establish(
         path.as_os_str()
             .to_str()
             .ok_or_else(|| anyhow!("Invalid data path"))?,
     )?;
+    // SQLite doesn't enforce foreign keys unless enabled for every connection
+    diesel::sql_query("PRAGMA foreign_keys = ON").execute(&mut conn)?;
+    Ok(conn)
+}
+
+/// Migrated in-memory database for tests
+#[cfg(test)]
+pub fn open_memory() -> SqliteConnection {
+    open(Path::new(":memory:")).expect("in-memory database")
+}
+
+/// Migrated in-memory database with a project of the URL https://example.com for tests
+#[cfg(test)]
+pub fn open_memory_with_project() -> (SqliteConnection, crate::projects::ProjectId) {
+    let mut conn = open_memory();
+    let project = crate::projects::get_or_create_by_url(&mut conn, "https://example.com")
+        .expect("test project")
+        .id;
+    (conn, project)
+}
+
+/// Apply pending migrations, returns the number of applied ones
+pub fn migrate(conn: &mut SqliteConnection) -> Result<usize> {
     conn.run_pending_migrations(MIGRATIONS)
-        .map_err(|e| anyhow!("{e}"))?;
+        .map(|applied| applied.len())
+        .map_err(migration_error)
+}
+
+/// Print every migration known to this version or applied to the database with its state
+pub fn print_version(conn: &mut SqliteConnection, config: &Config) -> Result<()> {
+    let applied = conn.applied_migrations().map_err(migration_error)?;
+    let known = MigrationSource::<Sqlite>::migrations(&MIGRATIONS).map_err(migration_error)?;
+    let mut rows = known
+        .iter()
+        .map(|migration| {
+            let state = if applied.contains(&migration.name().version()) {
+                "applied".green().to_string()
+            } else {
+                "pending".yellow().to_string()
+            };
+            (
+                migration.name().version().to_string(),
+                migration.name().to_string(),
+                state,
+            )
+        })
+        .collect::<Vec<_>>();
+    // Applied by a newer version of wlog
+    for version in &applied {
+        if !known
+            .iter()
+            .any(|migration| migration.name().version() == *version)
+        {
+            rows.push((
+                version.to_string(),
+                "-".to_string(),
+                "unknown".red().to_string(),
+            ));
+        }
+    }
+    rows.sort_by(|a, b| a.0.cmp(&b.0));
+    OutputFormat::Table.print_table(
+        config.table_style(),
+        &["Version", "Migration", "State"],
+        rows.into_iter()
+            .map(|(version, name, state)| [version, name, state]),
+    )
+}
+
+fn migration_error(error: Box<dyn std::error::Error + Send + Sync>) -> eyre::Report {
+    match error.to_string() {
+        msg if msg.contains("database is locked") => WlogError::DbLocked.into(),
+        msg => anyhow!("{msg}"),
+    }
+}
+
+/// Open a database without migrating or otherwise modifying it, e.g. one of another person. Fails
+/// if the database has pending migrations, as queries expect the current schema.
+pub fn open_read_only(path: &Path) -> Result<SqliteConnection> {
+    if !path.is_file() {
+        eyre::bail!(WlogError::NotFound(format!(
+            "Database {} doesn't exist",
+            path.display()
+        )));
+    }
+    let uri = path
+        .as_os_str()
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid data path"))?;
+    // Characters with a meaning in URIs have to be escaped in the file name
+    let uri = uri
+        .replace('%', "%25")
+        .replace('?', "%3f")
+        .replace('#', "%23");
+    let mut conn = SqliteConnection::establish(&format!("file:{uri}?mode=ro"))?;
+    if !is_current(&mut conn)? {
+        eyre::bail!(WlogError::Validation(format!(
+            "Database {} isn't up to date with this version of wlog, its owner has to run \
+             `wlog data migrate` first",
+            path.display()
+        )));
+    }
     Ok(conn)
 }
+
+/// Open the database only if it exists and has no pending migrations, for checks running before
+/// commands like `wlog data migrate` that mustn't change the schema behind the user's back
+pub fn open_if_current(path: &Path) -> Result<Option<SqliteConnection>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let mut conn = open_unmigrated(path)?;
+    Ok(is_current(&mut conn)?.then_some(conn))
+}
+
+/// Whether all migrations are applied to the database
+fn is_current(conn: &mut SqliteConnection) -> Result<bool> {
+    // Checking for pending migrations creates the migrations table if it's missing, which would
+    // modify the database
+    let initialized = diesel::select(diesel::dsl::sql::<diesel::sql_types::Bool>(
+        "EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' \
+         AND name = '__diesel_schema_migrations')",
+    ))
+    .get_result::<bool>(conn)?;
+    Ok(initialized
+        && !conn
+            .has_pending_migration(MIGRATIONS)
+            .map_err(migration_error)?)
+}
+
+/// Run a single SQL statement and print the resulting rows. The connection is switched to
+/// read-only mode first, so statements that write are rejected by SQLite.
+pub fn query(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    sql: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    diesel::sql_query("PRAGMA query_only = ON").execute(conn)?;
+
+    let mut header = Vec::new();
+    let mut rows = Vec::new();
+    for row in LoadConnection::load(conn, diesel::sql_query(sql)).map_err(read_only_error)? {
+        let row = row.map_err(read_only_error)?;
+        if header.is_empty() {
+            header = (0..row.field_count())
+                .map(|i| {
+                    row.get(i)
+                        .and_then(|field| field.field_name().map(str::to_string))
+                        .unwrap_or_default()
+                })
+                .collect();
+        }
+        rows.push(
+            (0..row.field_count())
+                .map(|i| row.get(i).map(fmt_value).unwrap_or_default())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    if rows.is_empty() {
+        eprintln!("{} The query returned no rows", "Note:".cyan());
+        return Ok(());
+    }
+    let header = header.iter().map(String::as_str).collect::<Vec<_>>();
+    format.print_table(config.table_style(), &header, rows)
+}
+
+fn fmt_value<'a>(field: impl Field<'a, Sqlite>) -> String {
+    let Some(mut value) = field.value() else {
+        return "NULL".to_string();
+    };
+    match value.value_type() {
+        Some(SqliteType::Double | SqliteType::Float) => value.read_double().to_string(),
+        Some(SqliteType::Binary) => format!("<{} bytes>", value.read_blob().len()),
+        Some(SqliteType::Text) => value.read_text().to_string(),
+        Some(_) => value.read_long().to_string(),
+        None => "NULL".to_string(),
+    }
+}
+
+fn read_only_error(error: diesel::result::Error) -> eyre::Report {
+    match error {
+        diesel::result::Error::DatabaseError(_, info) if info.message().contains("readonly") => {
+            WlogError::Validation("Only statements that don't write are allowed".into()).into()
+        }
+        e => e.into(),
+    }
+}
@@ -0,0 +1,72 @@
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use rustyline::error::ReadlineError;
+
+/// Error categories reported with distinct exit codes, so that wrapper scripts don't have to
+/// parse error messages. Anything else exits with code 1, invalid arguments with 2.
+#[derive(Debug)]
+pub enum WlogError {
+    /// Configuration file can't be read or is invalid
+    Config(String),
+    /// Database is locked by another process
+    DbLocked,
+    /// Requested item doesn't exist
+    NotFound(String),
+    /// Input was rejected
+    Validation(String),
+    /// User declined or interrupted a prompt
+    Canceled(String),
+}
+
+const CONFIG: i32 = 3;
+const DB_LOCKED: i32 = 4;
+const NOT_FOUND: i32 = 5;
+const VALIDATION: i32 = 6;
+const CANCELED: i32 = 7;
+
+impl WlogError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WlogError::Config(_) => CONFIG,
+            WlogError::DbLocked => DB_LOCKED,
+            WlogError::NotFound(_) => NOT_FOUND,
+            WlogError::Validation(_) => VALIDATION,
+            WlogError::Canceled(_) => CANCELED,
+        }
+    }
+}
+
+impl std::fmt::Display for WlogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WlogError::Config(msg)
+            | WlogError::NotFound(msg)
+            | WlogError::Validation(msg)
+            | WlogError::Canceled(msg) => f.write_str(msg),
+            WlogError::DbLocked => f.write_str("Database is locked by another process"),
+        }
+    }
+}
+
+impl std::error::Error for WlogError {}
+
+/// Exit code for the error, also recognizing errors of the underlying libraries
+pub fn exit_code(error: &eyre::Report) -> i32 {
+    if let Some(e) = error.downcast_ref::<WlogError>() {
+        return e.exit_code();
+    }
+    if let Some(e) = error.downcast_ref::<DieselError>() {
+        return match e {
+            DieselError::NotFound => NOT_FOUND,
+            DieselError::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.message().contains("database is locked") =>
+            {
+                DB_LOCKED
+            }
+            _ => 1,
+        };
+    }
+    if let Some(ReadlineError::Interrupted | ReadlineError::Eof) = error.downcast_ref() {
+        return CANCELED;
+    }
+    1
+}
@@ -0,0 +1,124 @@
+use crate::error::WlogError;
+use diesel::connection::{Instrumentation, InstrumentationEvent, set_default_instrumentation};
+use eyre::Result;
+use owo_colors::OwoColorize;
+use serde_json::{Value, json};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// File given with `--log-file` or configured with `wlog config log-file`
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+/// Events are printed to stderr as well, set with `--verbose`
+static VERBOSE: OnceLock<bool> = OnceLock::new();
+
+const MIGRATIONS_TABLE: &str = "CREATE TABLE IF NOT EXISTS __diesel_schema_migrations";
+
+/// Append a JSON line for every statement changing the database to the file, along with the
+/// arguments of the command running it, and print it to stderr if verbose. Applies to every
+/// connection opened afterwards.
+pub fn enable(path: Option<PathBuf>, verbose: bool) -> Result<()> {
+    if path.is_none() && !verbose {
+        return Ok(());
+    }
+    if let Some(path) = path {
+        check(&path)?;
+        let _ = LOG_FILE.set(path);
+    }
+    let _ = VERBOSE.set(verbose);
+    set_default_instrumentation(|| Some(Box::new(EventLog::default())))?;
+    Ok(())
+}
+
+/// Fails early on unwritable paths rather than on the first change
+pub fn check(path: &Path) -> Result<()> {
+    open(path).map_err(|e| {
+        WlogError::Config(format!("Unable to open log file {}: {e}", path.display()))
+    })?;
+    Ok(())
+}
+
+fn open(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[derive(Default)]
+struct EventLog {
+    file: Option<File>,
+    started: Option<Instant>,
+}
+
+impl EventLog {
+    fn write(&mut self, mut event: Value) -> std::io::Result<()> {
+        event["time"] = json!(OffsetDateTime::now_utc().format(&Rfc3339).ok());
+        event["pid"] = json!(std::process::id());
+        event["args"] = json!(std::env::args().skip(1).collect::<Vec<_>>());
+        let line = format!("{event}\n");
+        if VERBOSE.get().copied().unwrap_or(false) {
+            eprint!("{line}");
+        }
+        let Some(path) = LOG_FILE.get() else {
+            return Ok(());
+        };
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(open(path)?),
+        };
+        // A single write keeps lines of concurrent commands from interleaving
+        file.write_all(line.as_bytes())
+    }
+}
+
+impl Instrumentation for EventLog {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        let event = match event {
+            InstrumentationEvent::StartQuery { .. } => {
+                self.started = Some(Instant::now());
+                return;
+            }
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let query = query.to_string();
+                // Run by the migration harness on every connection
+                if !is_mutation(&query) || query.starts_with(MIGRATIONS_TABLE) {
+                    return;
+                }
+                json!({
+                    "event": "query",
+                    "query": query,
+                    "duration_ms": self.started.take().map(|t| t.elapsed().as_secs_f64() * 1000.0),
+                    "error": error.map(|e| e.to_string()),
+                })
+            }
+            // Changes logged before a rollback never made it to the database
+            InstrumentationEvent::RollbackTransaction { depth, .. } => {
+                json!({ "event": "rollback", "depth": depth.get() })
+            }
+            _ => return,
+        };
+        if let Err(e) = self.write(event) {
+            eprintln!(
+                "{} Unable to write the event log: {e}",
+                "Warning:".yellow().bold()
+            );
+        }
+    }
+}
+
+/// Statements writing data or changing the schema. Reads, pragmas and transaction control are
+/// left out.
+fn is_mutation(query: &str) -> bool {
+    let keyword = query
+        .trim_start()
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+    [
+        "INSERT", "UPDATE", "DELETE", "REPLACE", "CREATE", "DROP", "ALTER",
+    ]
+    .iter()
+    .any(|k| keyword.eq_ignore_ascii_case(k))
+}
@@ -0,0 +1,178 @@
+use crate::comments;
+use crate::config::{Config, DurationFormat, Precision};
+use crate::log_entries::{self, EntryFilter, Period};
+use crate::projects::Project;
+use diesel::SqliteConnection;
+use eyre::Result;
+use rust_xlsxwriter::{ExcelDateTime, Format, Formula, Workbook};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::Path;
+use time::Duration;
+
+/// Write log entries and comments as CSV, with durations in the column of [`csv_duration`]
+pub fn export_csv(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: Option<&Period>,
+    filter: Option<&EntryFilter>,
+    anonymize: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    let entries = log_entries::get_by_day_expanded(conn, project.id, period, filter)?;
+    let comment_entries = comments::get_by_period(conn, project.id, period)?;
+    let anonymizer = anonymize.then(Anonymizer::new).transpose()?;
+    let text = |s: &str| match &anonymizer {
+        Some(anonymizer) => anonymizer.hash(s),
+        None => csv_field(s),
+    };
+
+    let (duration_column, duration) = csv_duration(config);
+    writeln!(out, "date,issue,task,comment,{duration_column}")?;
+    for entry in entries {
+        writeln!(
+            out,
+            "{},{},{},,{}",
+            entry.date,
+            entry
+                .issue_number
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            text(&entry.task_name),
+            duration(entry.duration),
+        )?;
+    }
+    for comment in comment_entries {
+        writeln!(
+            out,
+            "{},,,{},{}",
+            comment.date,
+            text(&comment.text),
+            comment.duration.map(duration).unwrap_or_default(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Name and formatter of the duration column of CSV exports: decimal hours with the industrial
+/// duration format, otherwise whole minutes, or seconds with the seconds precision, so that sums
+/// of the column match the totals
+pub fn csv_duration(config: &Config) -> (&'static str, fn(Duration) -> String) {
+    match (config.duration_format(), config.precision()) {
+        (DurationFormat::Industrial, _) => {
+            ("duration_hours", |d| DurationFormat::Industrial.format(d))
+        }
+        (DurationFormat::Standard, Precision::Minutes) => {
+            ("duration_minutes", |d| d.whole_minutes().to_string())
+        }
+        (DurationFormat::Standard, Precision::Seconds) => {
+            ("duration_seconds", |d| d.whole_seconds().to_string())
+        }
+    }
+}
+
+/// Write log entries of the month as an Excel workbook with a sheet per ISO week. Durations are
+/// numbers formatted as hours and minutes, or decimal hours with the industrial duration format,
+/// and weekly totals are formulas, so that they follow edits of the sheet.
+pub fn export_xlsx(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    month: &Period,
+    filter: Option<&EntryFilter>,
+    path: &Path,
+) -> Result<()> {
+    let entries = log_entries::get_by_day_expanded(conn, project.id, Some(month), filter)?;
+
+    let header = Format::new().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    // Excel stores times as fractions of a day
+    let (duration_format, duration): (_, fn(Duration) -> f64) = match config.duration_format() {
+        DurationFormat::Standard => ("[h]:mm", |d| d.as_seconds_f64() / 86400.0),
+        DurationFormat::Industrial => ("0.00", |d| d.as_seconds_f64() / 3600.0),
+    };
+    let duration_cell = Format::new().set_num_format(duration_format);
+    let total_cell = duration_cell.clone().set_bold();
+
+    let mut workbook = Workbook::new();
+    let mut days = month.days().peekable();
+    while let Some(&first) = days.peek() {
+        let week = first.iso_week();
+        let to = std::iter::from_fn(|| days.next_if(|day| day.iso_week() == week))
+            .last()
+            .unwrap_or(first);
+
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(format!("Week {week} ({}-{})", first.day(), to.day()))?;
+        for (col, title) in ["Date", "Issue", "Task", "Duration"]
+            .into_iter()
+            .enumerate()
+        {
+            sheet.write_string_with_format(0, col as u16, title, &header)?;
+        }
+        sheet.set_column_width(0, 12)?;
+        sheet.set_column_width(1, 10)?;
+        sheet.set_column_width(2, 48)?;
+        sheet.set_column_width(3, 10)?;
+        sheet.set_freeze_panes(1, 0)?;
+
+        let mut row = 1;
+        for entry in entries
+            .iter()
+            .filter(|entry| (first..=to).contains(&entry.date))
+        {
+            let day = ExcelDateTime::from_ymd(
+                entry.date.year() as u16,
+                entry.date.month() as u8,
+                entry.date.day(),
+            )?;
+            sheet.write_date_with_format(row, 0, &day, &date)?;
+            if let Some(issue) = entry.issue_number {
+                sheet.write_string(row, 1, project.issue_key(issue))?;
+            }
+            sheet.write_string(row, 2, &entry.task_name)?;
+            sheet.write_number_with_format(row, 3, duration(entry.duration), &duration_cell)?;
+            row += 1;
+        }
+        sheet.write_string_with_format(row, 2, "Total", &header)?;
+        // Rows are 1-based in formulas, the header is row 1
+        let sum = match row {
+            1 => Formula::new("=0"),
+            _ => Formula::new(format!("=SUM(D2:D{row})")),
+        };
+        sheet.write_formula_with_format(row, 3, sum, &total_cell)?;
+    }
+    workbook.save(path)?;
+    Ok(())
+}
+
+/// Replaces text with hashes salted per export. Equal texts get equal hashes within an export, but
+/// short or common names can't be recovered by hashing guesses, nor matched across exports.
+struct Anonymizer {
+    salt: [u8; 16],
+}
+
+impl Anonymizer {
+    fn new() -> Result<Self> {
+        let mut salt = [0; 16];
+        getrandom::fill(&mut salt).map_err(|e| eyre::anyhow!("Unable to generate a salt: {e}"))?;
+        Ok(Self { salt })
+    }
+
+    fn hash(&self, s: &str) -> String {
+        let digest = Sha256::new()
+            .chain_update(self.salt)
+            .chain_update(s.as_bytes())
+            .finalize();
+        digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
+
+pub fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -0,0 +1,136 @@
+use crate::Config;
+use crate::log_entries::{self, LogEntryExpanded, Period};
+use crate::projects::{self, ProjectId};
+use crate::schema::github_comments;
+use crate::webhook::{Delivery, Sender};
+use crate::{credentials, remote_issues};
+use diesel::prelude::*;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use time::{Duration, OffsetDateTime, PrimitiveDateTime};
+
+/// Authentication failures and GitHub's rate limits, which leave comments queued
+const KEEP_STATUSES: &[u16] = &[401, 403];
+
+/// Queue a summary of the week for every issue of the project with time logged during it. Issues
+/// already commented for the week are skipped. Returns number of queued comments.
+pub fn enqueue_week(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    week: &Period,
+) -> Result<usize> {
+    let mut issues = BTreeMap::<i32, Vec<LogEntryExpanded>>::new();
+    for entry in log_entries::get_by_day_expanded(conn, project, Some(week), None)? {
+        if let Some(issue) = entry.issue_number {
+            issues.entry(issue).or_default().push(entry);
+        }
+    }
+    let mut queued = 0;
+    for (issue, entries) in &issues {
+        queued += diesel::insert_into(github_comments::table)
+            .values((
+                github_comments::project_id.eq(project.0),
+                github_comments::issue.eq(issue),
+                github_comments::week.eq(week.from),
+                github_comments::body.eq(summary(config, week, entries)),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    Ok(queued)
+}
+
+/// Post queued comments in order. Stops at the first comment that can't be posted, so that it's
+/// retried by a later push, comments rejected by GitHub are dropped. Returns number of posted and
+/// still queued comments.
+pub fn flush(conn: &mut SqliteConnection) -> Result<(usize, usize)> {
+    let queued = github_comments::table
+        .filter(github_comments::posted_at.is_null())
+        .order_by(github_comments::id)
+        .select((
+            github_comments::id,
+            github_comments::project_id,
+            github_comments::issue,
+            github_comments::body,
+        ))
+        .load::<(i32, i32, i32, String)>(conn)?;
+    let mut tokens = HashMap::new();
+    let mut sender = Sender::new(KEEP_STATUSES);
+    let mut posted = 0;
+    for (i, (id, project, issue, body)) in queued.iter().enumerate() {
+        let project = projects::get(conn, ProjectId(*project))?;
+        let token = match tokens.entry(project.id.0) {
+            Entry::Occupied(slot) => slot.into_mut(),
+            Entry::Vacant(slot) => slot.insert(credentials::get_token(project.id)?),
+        };
+        let Some(token) = token else {
+            eprintln!(
+                "{} No API token for project {}, store one with `wlog project auth set`. {} comments queued",
+                "Warning:".yellow().bold(),
+                project.id.0,
+                queued.len() - i
+            );
+            return Ok((posted, queued.len() - i));
+        };
+
+        match post(&mut sender, &project.url, token, *issue, body) {
+            Ok(Delivery::Delivered) => posted += 1,
+            Ok(Delivery::Rejected(code)) => {
+                eprintln!(
+                    "{} GitHub rejected the comment on {} with status {code}, dropping it",
+                    "Warning:".yellow().bold(),
+                    project.issue_key(*issue)
+                );
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} Posting comments failed, {} comments queued: {e}",
+                    "Warning:".yellow().bold(),
+                    queued.len() - i
+                );
+                return Ok((posted, queued.len() - i));
+            }
+        }
+        let now = OffsetDateTime::now_utc();
+        diesel::update(github_comments::table.find(id))
+            .set(github_comments::posted_at.eq(PrimitiveDateTime::new(now.date(), now.time())))
+            .execute(conn)?;
+    }
+    Ok((posted, 0))
+}
+
+/// Markdown comment with the task's entries of the week and their total
+fn summary(config: &Config, week: &Period, entries: &[LogEntryExpanded]) -> String {
+    let total: Duration = entries.iter().map(|entry| entry.duration).sum();
+    let mut body = format!(
+        "**Time logged {} – {}: {}**\n\n| Date | Task | Duration |\n|---|---|---|\n",
+        week.from,
+        week.to,
+        config.fmt_duration(total)
+    );
+    for entry in entries {
+        body.push_str(&format!(
+            "| {} | {} | {} |\n",
+            entry.date,
+            entry.task_name.replace('|', "\\|"),
+            config.fmt_duration(entry.duration)
+        ));
+    }
+    body
+}
+
+fn post(sender: &mut Sender, url: &str, token: &str, issue: i32, body: &str) -> Result<Delivery> {
+    let (_, path) = remote_issues::split_repo_url(url)?;
+    let url = format!("https://api.github.com/repos/{path}/issues/{issue}/comments");
+    let request = |agent: &ureq::Agent| {
+        agent
+            .post(&url)
+            .set("Authorization", &format!("Bearer {token}"))
+            .set("Accept", "application/vnd.github+json")
+            .set("Content-Type", "application/json")
+    };
+    sender.send(request, &serde_json::json!({ "body": body }).to_string())
+}
@@ -0,0 +1,163 @@
+use crate::Config;
+use crate::log_entries;
+use crate::projects::Project;
+use crate::schedule;
+use crate::schema::{projects, tasks};
+use crate::tasks::{Task, TaskId};
+use diesel::prelude::*;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use serde_json::{Value, json};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use time::{Date, Duration};
+
+/// Event triggering the command configured for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Event {
+    /// Time is logged for a task
+    LogAdded,
+    /// Logged time of a day reaches the scheduled time
+    DayComplete,
+    /// A month is closed
+    MonthClose,
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::LogAdded => f.write_str("log_added"),
+            Event::DayComplete => f.write_str("day_complete"),
+            Event::MonthClose => f.write_str("month_close"),
+        }
+    }
+}
+
+/// Hook command along with its payload. Hooks run once the change is committed, so that commands
+/// calling wlog themselves see it and don't wait for the database.
+pub struct Hook {
+    command: String,
+    payload: Value,
+}
+
+impl Hook {
+    fn new(config: &Config, event: Event, mut payload: Value) -> Option<Self> {
+        let command = config.hook(event)?;
+        payload["event"] = json!(event.to_string());
+        Some(Self {
+            command: command.to_string(),
+            payload,
+        })
+    }
+}
+
+/// Hooks of time added to the task's entry on the date, including the day reaching its schedule
+pub fn log_added(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    date: Date,
+    task: TaskId,
+    added: Duration,
+) -> Result<Vec<Hook>> {
+    if config.hook(Event::LogAdded).is_none() && config.hook(Event::DayComplete).is_none() {
+        return Ok(Vec::new());
+    }
+    let (task, project) = tasks::table
+        .inner_join(projects::table)
+        .filter(tasks::id.eq(task.0))
+        .select((Task::as_select(), Project::as_select()))
+        .first::<(Task, Project)>(conn)?;
+
+    let mut hooks = Vec::new();
+    hooks.extend(Hook::new(
+        config,
+        Event::LogAdded,
+        json!({
+            "project": project.url,
+            "project_name": project.name,
+            "task_id": task.id.0,
+            "task": task.name,
+            "issue": task.issue,
+            "date": date.to_string(),
+            "duration_seconds": added.whole_seconds(),
+        }),
+    ));
+
+    let logged = log_entries::get_day_total(conn, project.id, date)?;
+    let scheduled = schedule::scheduled_duration(conn, project.id, date)?.unwrap_or_default();
+    if scheduled.is_positive() && logged - added < scheduled && logged >= scheduled {
+        hooks.extend(Hook::new(
+            config,
+            Event::DayComplete,
+            json!({
+                "project": project.url,
+                "project_name": project.name,
+                "date": date.to_string(),
+                "logged_seconds": logged.whole_seconds(),
+                "scheduled_seconds": scheduled.whole_seconds(),
+            }),
+        ));
+    }
+    Ok(hooks)
+}
+
+/// Hook of the month closed with the carryover
+pub fn month_closed(
+    config: &Config,
+    project: &Project,
+    month: Date,
+    carryover: Duration,
+) -> Option<Hook> {
+    Hook::new(
+        config,
+        Event::MonthClose,
+        json!({
+            "project": project.url,
+            "project_name": project.name,
+            "month": format!("{}-{:02}", month.year(), month.month() as u8),
+            "carryover_seconds": carryover.whole_seconds(),
+        }),
+    )
+}
+
+/// Run hooks in order with their payloads on stdin. Failures are reported without failing the
+/// command, the change they're about is already made.
+pub fn run(hooks: Vec<Hook>) {
+    for hook in hooks {
+        if let Err(e) = run_one(&hook) {
+            eprintln!(
+                "{} Hook `{}` failed: {e}",
+                "Warning:".yellow().bold(),
+                hook.command
+            );
+        }
+    }
+}
+
+fn run_one(hook: &Hook) -> Result<()> {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&hook.command);
+        command
+    };
+    #[cfg(not(unix))]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(&hook.command);
+        command
+    };
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks are free to ignore the payload
+        match stdin.write_all(hook.payload.to_string().as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        eyre::bail!("exited with {status}");
+    }
+    Ok(())
+}
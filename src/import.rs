@@ -0,0 +1,298 @@
+use crate::Config;
+use crate::error::WlogError;
+use crate::log_entries::{self, EntrySource, Limits, LogEntry};
+use crate::projects::{self, ProjectId};
+use crate::schema::imported_entries;
+use crate::tasks::{self, TaskChoice, TaskId};
+use crate::utils::{History, OutputFormat, prompt, prompt_opt};
+use crate::webhook;
+use diesel::prelude::*;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use time::{Date, Duration};
+
+const HARVEST: &str = "harvest";
+
+struct HarvestEntry {
+    id: String,
+    date: Date,
+    project: String,
+    task: String,
+    duration: Duration,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum HarvestJson {
+    Wrapped { time_entries: Vec<HarvestJsonEntry> },
+    Plain(Vec<HarvestJsonEntry>),
+}
+
+#[derive(Debug, Deserialize)]
+struct HarvestJsonEntry {
+    id: u64,
+    spent_date: String,
+    hours: f64,
+    project: HarvestJsonName,
+    task: HarvestJsonName,
+}
+
+#[derive(Debug, Deserialize)]
+struct HarvestJsonName {
+    name: String,
+}
+
+/// Import time entries from a Harvest CSV or JSON export. Harvest projects and tasks are mapped
+/// to wlog ones interactively, entries imported before are skipped.
+pub fn import_harvest(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    path: &Path,
+    limits: &Limits,
+) -> Result<()> {
+    let entries = if path.extension().is_some_and(|ext| ext == "json") {
+        read_harvest_json(path)?
+    } else {
+        read_harvest_csv(path)?
+    };
+
+    let mut imported = imported_entries::table
+        .filter(imported_entries::source.eq(HARVEST))
+        .select(imported_entries::source_id)
+        .load::<String>(conn)?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    // Entries listed twice in the export are skipped like ones imported before
+    let (entries, skipped): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .partition(|entry| imported.insert(entry.id.clone()));
+
+    // Mapping is asked for before writing, so the database isn't locked while waiting
+    let mut project_map = HashMap::<String, ProjectId>::new();
+    let mut choices = HashMap::<(String, String), TaskChoice>::new();
+    for entry in &entries {
+        if let Entry::Vacant(slot) = project_map.entry(entry.project.clone()) {
+            projects::list_all(conn, config, false, OutputFormat::Table)?;
+            let id: i32 = prompt(
+                &format!("Project ID for Harvest project \"{}\"", entry.project),
+                History::ProjectId,
+            )?;
+            let project = projects::get(conn, ProjectId(id))?;
+            slot.insert(project.id);
+        }
+        let key = (entry.project.clone(), entry.task.clone());
+        if let Entry::Vacant(slot) = choices.entry(key) {
+            let issue = prompt_opt(
+                &format!("Issue number for Harvest task \"{}\"", entry.task),
+                History::Issue,
+            )?;
+            let task = tasks::choose_interactive(
+                conn,
+                Some(project_map[&entry.project]),
+                issue,
+                Some(&entry.task),
+            )?;
+            slot.insert(task);
+        }
+    }
+
+    // Tasks created while mapping are rolled back if the import fails
+    conn.transaction(|conn| {
+        let mut task_map = HashMap::<(String, String), TaskId>::new();
+        for ((project, task), choice) in choices {
+            let id = choice.get_or_create(conn, project_map[&project])?;
+            task_map.insert((project, task), id);
+        }
+
+        for entry in &entries {
+            let project = project_map[&entry.project];
+            let task = task_map[&(entry.project.clone(), entry.task.clone())];
+            save_entry(conn, config, project, task, entry, limits)?;
+            webhook::enqueue(conn, config, entry.date, task)?;
+        }
+        eyre::Ok(())
+    })?;
+
+    eprintln!(
+        "{} {} entries imported",
+        "Success:".green().bold(),
+        entries.len()
+    );
+    if !skipped.is_empty() {
+        eprintln!(
+            "{} {} entries were imported before or listed twice and skipped",
+            "Note:".cyan(),
+            skipped.len()
+        );
+    }
+    Ok(())
+}
+
+/// Log the entry, adding to entries of the task already logged on the date, and remember it as
+/// imported
+fn save_entry(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    task: TaskId,
+    entry: &HarvestEntry,
+    limits: &Limits,
+) -> Result<()> {
+    log_entries::add_log(
+        conn,
+        project,
+        LogEntry {
+            date: entry.date,
+            task,
+            duration: config.precision().round(entry.duration),
+            utc_offset: None,
+            start: None,
+            source: EntrySource::Import(HARVEST),
+        },
+        limits,
+    )?;
+    diesel::insert_into(imported_entries::table)
+        .values((
+            imported_entries::source.eq(HARVEST),
+            imported_entries::source_id.eq(&entry.id),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Harvest CSV exports have no entry IDs, hash of the whole record is used instead. Identical
+/// records are told apart by their number of occurrence.
+fn read_harvest_csv(path: &Path) -> Result<Vec<HarvestEntry>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| WlogError::Validation(format!("Missing column \"{name}\"")))
+    };
+    let (date, project, task, hours) = (
+        column("Date")?,
+        column("Project")?,
+        column("Task")?,
+        column("Hours")?,
+    );
+
+    let mut occurrences = HashMap::<String, usize>::new();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            let hash = Sha256::digest(record.iter().collect::<Vec<_>>().join("\u{1f}"));
+            let hash = hash.iter().map(|b| format!("{b:02x}")).collect::<String>();
+            let occurrence = occurrences.entry(hash.clone()).or_default();
+            *occurrence += 1;
+            Ok(HarvestEntry {
+                id: match *occurrence {
+                    1 => hash,
+                    n => format!("{hash}-{n}"),
+                },
+                date: parse_date(&record[date])?,
+                project: record[project].to_string(),
+                task: record[task].to_string(),
+                duration: hours_duration(parse_hours(&record[hours])?)?,
+            })
+        })
+        .collect()
+}
+
+fn read_harvest_json(path: &Path) -> Result<Vec<HarvestEntry>> {
+    let json: HarvestJson = serde_json::from_reader(std::fs::File::open(path)?)?;
+    let (HarvestJson::Wrapped {
+        time_entries: entries,
+    }
+    | HarvestJson::Plain(entries)) = json;
+    entries
+        .into_iter()
+        .map(|entry| {
+            Ok(HarvestEntry {
+                id: entry.id.to_string(),
+                date: parse_date(&entry.spent_date)?,
+                project: entry.project.name,
+                task: entry.task.name,
+                duration: hours_duration(entry.hours)?,
+            })
+        })
+        .collect()
+}
+
+fn parse_date(v: &str) -> Result<Date> {
+    Date::parse(v, &time::format_description::well_known::Iso8601::DATE)
+        .map_err(|e| WlogError::Validation(format!("Invalid date \"{v}\": {e}")).into())
+}
+
+fn hours_duration(hours: f64) -> Result<Duration> {
+    Some(hours * 3600.0)
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .and_then(Duration::checked_seconds_f64)
+        .ok_or_else(|| WlogError::Validation(format!("Invalid hours {hours}")).into())
+}
+
+/// Harvest exports hours either as decimal or as HH:MM
+fn parse_hours(v: &str) -> Result<f64> {
+    let parsed = match v.split_once(':') {
+        Some((h, m)) => h
+            .parse::<f64>()
+            .and_then(|h| Ok(h + m.parse::<f64>()? / 60.0)),
+        None => v.parse(),
+    };
+    parsed.map_err(|_| WlogError::Validation(format!("Invalid hours \"{v}\"")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data;
+    use crate::log_entries::Limits;
+
+    #[test]
+    fn invalid_hours() {
+        assert_eq!(hours_duration(1.5).ok(), Some(Duration::minutes(90)));
+        assert_eq!(hours_duration(0.0).ok(), Some(Duration::ZERO));
+        for hours in [-1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300] {
+            assert!(hours_duration(hours).is_err(), "{hours}");
+        }
+        assert!(parse_hours("NaN").and_then(hours_duration).is_err());
+        assert!(parse_hours("-1:30").and_then(hours_duration).is_err());
+    }
+
+    #[test]
+    fn duplicate_records() {
+        let path = std::env::temp_dir().join(format!("wlog-harvest-{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "Date,Project,Task,Hours\n\
+             2026-10-16,Site,Design,1.5\n\
+             2026-10-16,Site,Design,1.5\n\
+             2026-10-16,Site,Design,0:30\n",
+        )
+        .unwrap();
+        let entries = read_harvest_csv(&path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let ids = entries.iter().map(|e| &e.id).collect::<HashSet<_>>();
+        assert_eq!(ids.len(), 3);
+
+        // Entries of a task on the same date add up
+        let (mut conn, project) = data::open_memory_with_project();
+        let config = Config::default();
+        let task = tasks::create(&mut conn, project, "Design", None).unwrap();
+        let limits = Limits::unlimited();
+        for entry in &entries {
+            save_entry(&mut conn, &config, project, task, entry, &limits).unwrap();
+        }
+        assert_eq!(
+            log_entries::get_duration(&mut conn, entries[0].date, task).unwrap(),
+            Duration::hours(3) + Duration::minutes(30)
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use crate::Config;
+use crate::error::WlogError;
+use crate::export::csv_field;
+use crate::log_entries::{Period, month_index};
+use crate::projects::{Project, ProjectId};
+use crate::schema::{invoice_items, invoices, log_entries, tasks};
+use crate::utils::{OutputFormat, fmt_timestamp};
+use diesel::prelude::*;
+use eyre::{Result, bail};
+use owo_colors::OwoColorize;
+use std::collections::HashMap;
+use std::io::Write;
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::invoices)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Invoice {
+    pub id: i32,
+    /// Number counting up from 1 within the project
+    pub number: i32,
+    // Month number since 1BCE, year * 12 + month
+    pub month: i32,
+    pub created_at: PrimitiveDateTime,
+}
+
+impl Invoice {
+    /// Invoiced month in YYYY-MM format
+    pub fn month_str(&self) -> String {
+        format!("{}-{:02}", (self.month - 1) / 12, (self.month - 1) % 12 + 1)
+    }
+}
+
+/// Time of a log entry billed by an invoice. Task name and issue are copied, so that the invoice
+/// stays the same when the task changes.
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::invoice_items)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct InvoiceItem {
+    pub invoice_id: i32,
+    pub date: Date,
+    pub task_id: i32,
+    pub task_name: String,
+    pub issue: Option<i32>,
+    pub duration_seconds: i32,
+}
+
+impl InvoiceItem {
+    pub fn duration(&self) -> Duration {
+        Duration::seconds(self.duration_seconds as i64)
+    }
+}
+
+/// Entry that was invoiced for more time than is logged now, because it was reduced or removed
+/// after invoicing
+#[derive(Debug, PartialEq)]
+pub struct Overbilled {
+    pub date: Date,
+    pub task_name: String,
+    /// Invoiced time above the logged time
+    pub duration: Duration,
+}
+
+/// Invoice every entry of the project up to the end of the month that isn't invoiced yet. Time
+/// added to an entry after it was invoiced is billed as well, entries of earlier months logged
+/// late are included. Entries invoiced for more time than is logged now are returned along with
+/// the invoice. Fails if there's nothing to invoice.
+pub fn generate(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    month: Date,
+) -> Result<(Invoice, Vec<InvoiceItem>, Vec<Overbilled>)> {
+    let period = Period::month(month);
+    let entries = log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.le(period.to))
+        .order_by((log_entries::date, tasks::id))
+        .select((
+            log_entries::date,
+            tasks::id,
+            tasks::name,
+            tasks::issue,
+            log_entries::duration_seconds,
+        ))
+        .load::<(Date, i32, String, Option<i32>, i32)>(conn)?;
+    // Tasks moved from another project keep the time invoiced there, time of removed tasks stays
+    // with the project of the invoice
+    let project_tasks = tasks::table
+        .filter(tasks::project_id.eq(project.0))
+        .select(tasks::id);
+    let mut invoiced = invoice_items::table
+        .inner_join(invoices::table)
+        .filter(
+            invoice_items::task_id
+                .eq_any(project_tasks)
+                .or(invoices::project_id
+                    .eq(project.0)
+                    .and(invoice_items::task_id.ne_all(tasks::table.select(tasks::id)))),
+        )
+        .filter(invoice_items::date.le(period.to))
+        .group_by((invoice_items::date, invoice_items::task_id))
+        .select((
+            invoice_items::date,
+            invoice_items::task_id,
+            diesel::dsl::max(invoice_items::task_name),
+            diesel::dsl::sum(invoice_items::duration_seconds),
+        ))
+        .load::<(Date, i32, Option<String>, Option<i64>)>(conn)?
+        .into_iter()
+        .map(|(date, task, name, seconds)| ((date, task), (name, seconds.unwrap_or(0))))
+        .collect::<HashMap<_, _>>();
+
+    let mut billable = Vec::new();
+    let mut overbilled = Vec::new();
+    for (date, task_id, task_name, issue, seconds) in entries {
+        let seconds = seconds as i64
+            - invoiced
+                .remove(&(date, task_id))
+                .map_or(0, |(_, invoiced)| invoiced);
+        if seconds > 0 {
+            billable.push((date, task_id, task_name, issue, seconds as i32));
+        } else if seconds < 0 {
+            overbilled.push(Overbilled {
+                date,
+                task_name,
+                duration: Duration::seconds(-seconds),
+            });
+        }
+    }
+    // Invoiced entries that were removed since
+    overbilled.extend(
+        invoiced
+            .into_iter()
+            .filter(|(_, (_, seconds))| *seconds > 0)
+            .map(|((date, _), (task_name, seconds))| Overbilled {
+                date,
+                task_name: task_name.unwrap_or_default(),
+                duration: Duration::seconds(seconds),
+            }),
+    );
+    overbilled.sort_by(|a, b| (a.date, &a.task_name).cmp(&(b.date, &b.task_name)));
+    if billable.is_empty() {
+        let mut message = format!("Nothing to invoice up to {}", period.to);
+        if !overbilled.is_empty() {
+            message += &format!(
+                ", {} entries were invoiced for more time than is logged now",
+                overbilled.len()
+            );
+        }
+        bail!(WlogError::NotFound(message));
+    }
+
+    let number = invoices::table
+        .filter(invoices::project_id.eq(project.0))
+        .select(diesel::dsl::max(invoices::number))
+        .get_result::<Option<i32>>(conn)?
+        .unwrap_or(0)
+        + 1;
+    let now = OffsetDateTime::now_utc();
+    let invoice = diesel::insert_into(invoices::table)
+        .values((
+            invoices::project_id.eq(project.0),
+            invoices::number.eq(number),
+            invoices::month.eq(month_index(month)),
+            invoices::created_at.eq(PrimitiveDateTime::new(now.date(), now.time())),
+        ))
+        .returning(Invoice::as_select())
+        .get_result(conn)?;
+    let items = billable
+        .into_iter()
+        .map(
+            |(date, task_id, task_name, issue, duration_seconds)| InvoiceItem {
+                invoice_id: invoice.id,
+                date,
+                task_id,
+                task_name,
+                issue,
+                duration_seconds,
+            },
+        )
+        .collect::<Vec<_>>();
+    diesel::insert_into(invoice_items::table)
+        .values(&items)
+        .execute(conn)?;
+    Ok((invoice, items, overbilled))
+}
+
+/// Invoice of the project with the number along with its items by date
+pub fn get(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    number: i32,
+) -> Result<(Invoice, Vec<InvoiceItem>)> {
+    let invoice = invoices::table
+        .filter(invoices::project_id.eq(project.0))
+        .filter(invoices::number.eq(number))
+        .select(Invoice::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| WlogError::NotFound(format!("Invoice {number} doesn't exist")))?;
+    let items = invoice_items::table
+        .filter(invoice_items::invoice_id.eq(invoice.id))
+        .order_by((invoice_items::date, invoice_items::task_id))
+        .select(InvoiceItem::as_select())
+        .load(conn)?;
+    Ok((invoice, items))
+}
+
+/// Print invoices of the project with their totals, oldest first
+pub fn list(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    format: OutputFormat,
+) -> Result<()> {
+    let invoices = invoices::table
+        .left_join(invoice_items::table)
+        .filter(invoices::project_id.eq(project.0))
+        .group_by(invoices::id)
+        .order_by(invoices::number)
+        .select((
+            Invoice::as_select(),
+            diesel::dsl::count(invoice_items::task_id.nullable()),
+            diesel::dsl::sum(invoice_items::duration_seconds.nullable()),
+        ))
+        .load::<(Invoice, i64, Option<i64>)>(conn)?;
+    if invoices.is_empty() {
+        eprintln!(
+            "{} No invoices, create one with `wlog invoice generate`",
+            "Note:".cyan()
+        );
+        return Ok(());
+    }
+    let offset = config.now()?.offset();
+    let rows = invoices.into_iter().map(|(invoice, entries, seconds)| {
+        [
+            invoice.number.to_string(),
+            invoice.month_str(),
+            fmt_timestamp(invoice.created_at, offset),
+            entries.to_string(),
+            config.fmt_duration(Duration::seconds(seconds.unwrap_or(0))),
+        ]
+    });
+    format.print_table(
+        config.table_style(),
+        &["Number", "Month", "Created", "Entries", "Total"],
+        rows,
+    )
+}
+
+/// Print entries billed by the invoice
+pub fn show(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    number: i32,
+    format: OutputFormat,
+) -> Result<()> {
+    let (invoice, items) = get(conn, project.id, number)?;
+    if format.has_totals() {
+        eprintln!(
+            "Invoice {} for {}, created {}",
+            invoice.number,
+            invoice.month_str(),
+            fmt_timestamp(invoice.created_at, config.now()?.offset())
+        );
+    }
+    let total: Duration = items.iter().map(InvoiceItem::duration).sum();
+    let footer = format.has_totals().then(|| {
+        [
+            format.strong("Total"),
+            String::new(),
+            String::new(),
+            format.strong(&config.fmt_duration(total)),
+        ]
+    });
+    let rows = items.iter().map(|item| {
+        [
+            item.date.to_string(),
+            item.issue
+                .map(|n| format.issue(n, project))
+                .unwrap_or_else(|| "-".to_string()),
+            format.task_name(&item.task_name),
+            config.fmt_duration(item.duration()),
+        ]
+    });
+    format.print_table(
+        config.table_style(),
+        &["Date", "Issue", "Task", "Duration"],
+        rows.chain(footer),
+    )
+}
+
+/// Write entries of an invoice as CSV, with durations formatted like `wlog export`
+pub fn export(config: &Config, items: &[InvoiceItem], out: &mut impl Write) -> Result<()> {
+    let (duration_column, duration) = crate::export::csv_duration(config);
+    writeln!(out, "date,issue,task,{duration_column}")?;
+    for item in items {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            item.date,
+            item.issue.map(|n| n.to_string()).unwrap_or_default(),
+            csv_field(&item.task_name),
+            duration(item.duration()),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_entries::{Limits, set_duration};
+    use crate::{data, projects};
+
+    fn day() -> Date {
+        Date::from_calendar_date(2026, time::Month::October, 1).unwrap()
+    }
+
+    fn billed(invoice: Result<(Invoice, Vec<InvoiceItem>, Vec<Overbilled>)>) -> Vec<i64> {
+        let (_, items, _) = invoice.unwrap();
+        items
+            .iter()
+            .map(|item| item.duration().whole_hours())
+            .collect()
+    }
+
+    #[test]
+    fn entries_are_billed_once() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Task", None).unwrap();
+        let limits = Limits::unlimited();
+        let log = |conn: &mut SqliteConnection, hours| {
+            set_duration(conn, project, day(), task, Duration::hours(hours), &limits).unwrap()
+        };
+
+        log(&mut conn, 5);
+        assert_eq!(billed(generate(&mut conn, project, day())), [5]);
+        let err = generate(&mut conn, project, day()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WlogError>(),
+            Some(WlogError::NotFound(_))
+        ));
+
+        // Only time added after invoicing is billed
+        log(&mut conn, 7);
+        assert_eq!(billed(generate(&mut conn, project, day())), [2]);
+
+        // Reduced and removed entries are reported rather than billed again
+        log(&mut conn, 3);
+        let next_day = day().next_day().unwrap();
+        set_duration(
+            &mut conn,
+            project,
+            next_day,
+            task,
+            Duration::hours(1),
+            &limits,
+        )
+        .unwrap();
+        let (_, items, overbilled) = generate(&mut conn, project, day()).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            overbilled,
+            [Overbilled {
+                date: day(),
+                task_name: "Task".to_string(),
+                duration: Duration::hours(4),
+            }]
+        );
+        log(&mut conn, 0);
+        let err = generate(&mut conn, project, day()).unwrap_err();
+        assert!(err.to_string().contains("1 entries were invoiced"));
+    }
+    #[test]
+    fn moved_tasks_are_not_billed_again() {
+        let (mut conn, from) = data::open_memory_with_project();
+        let to = projects::get_or_create_by_url(&mut conn, "https://example.org")
+            .unwrap()
+            .id;
+        let task = crate::tasks::create(&mut conn, from, "Task", None).unwrap();
+        let limits = Limits::unlimited();
+        set_duration(&mut conn, from, day(), task, Duration::hours(5), &limits).unwrap();
+        assert_eq!(billed(generate(&mut conn, from, day())), [5]);
+
+        crate::tasks::move_to_project(&mut conn, task, to, None, &limits).unwrap();
+        let err = generate(&mut conn, to, day()).unwrap_err();
+        assert_eq!(err.to_string(), "Nothing to invoice up to 2026-10-31");
+        // The old project doesn't report the moved time as overbilled
+        let err = generate(&mut conn, from, day()).unwrap_err();
+        assert_eq!(err.to_string(), "Nothing to invoice up to 2026-10-31");
+
+        // Time added after the move is billed by the new project
+        set_duration(&mut conn, to, day(), task, Duration::hours(7), &limits).unwrap();
+        assert_eq!(billed(generate(&mut conn, to, day())), [2]);
+    }
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use time::{Month, Weekday};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+const MONTHS: [Month; 12] = [
+    Month::January,
+    Month::February,
+    Month::March,
+    Month::April,
+    Month::May,
+    Month::June,
+    Month::July,
+    Month::August,
+    Month::September,
+    Month::October,
+    Month::November,
+    Month::December,
+];
+
+/// Language used for weekday and month names.
+///
+/// Input is accepted in every supported locale at once, so names must not be ambiguous between
+/// locales.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    pub fn weekday(self, weekday: Weekday) -> &'static str {
+        let names = match self {
+            Locale::En => [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+            Locale::De => [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+        };
+        names[weekday.number_days_from_monday() as usize]
+    }
+
+    pub fn weekday_short(self, weekday: Weekday) -> &'static str {
+        let names = match self {
+            Locale::En => ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"],
+            Locale::De => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+        };
+        names[weekday.number_days_from_monday() as usize]
+    }
+
+    pub fn month(self, month: Month) -> &'static str {
+        let names = match self {
+            Locale::En => [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            Locale::De => [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+        };
+        names[month as usize - 1]
+    }
+
+    fn parse_weekday(self, v: &str) -> Option<Weekday> {
+        let v = v.to_lowercase();
+        let abbreviations = match self {
+            Locale::En => ["mon", "tue", "wed", "thu", "fri", "sat", "sun"],
+            Locale::De => ["mo", "di", "mi", "do", "fr", "sa", "so"],
+        };
+        WEEKDAYS.into_iter().find(|&weekday| {
+            let i = weekday.number_days_from_monday() as usize;
+            v == abbreviations[i] || v == self.weekday(weekday).to_lowercase()
+        })
+    }
+
+    fn parse_month(self, v: &str) -> Option<Month> {
+        let v = v.to_lowercase();
+        MONTHS.into_iter().find(|&month| {
+            let name = self.month(month).to_lowercase();
+            v == name || (v.chars().count() >= 3 && name.starts_with(&v))
+        })
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::En => f.write_str("en"),
+            Locale::De => f.write_str("de"),
+        }
+    }
+}
+
+pub fn parse_weekday(v: &str) -> Option<Weekday> {
+    Locale::ALL
+        .into_iter()
+        .find_map(|locale| locale.parse_weekday(v))
+}
+
+pub fn parse_month(v: &str) -> Option<Month> {
+    Locale::ALL
+        .into_iter()
+        .find_map(|locale| locale.parse_month(v))
+}
@@ -1,20 +1,67 @@
+use crate::Config;
 use crate::comments;
+use crate::config::DurationFormat;
+use crate::error::WlogError;
 use crate::projects::{Project, ProjectId};
 use crate::schedule;
+use crate::schema::entry_history;
+use crate::schema::last_shown;
 use crate::schema::log_entries;
+use crate::schema::monthly_totals;
 use crate::schema::tasks;
-use crate::tasks::{Task, TaskId};
-use crate::utils::fmt_issue_linked;
+use crate::tasks::{Task, TaskFilter, TaskId};
+use crate::utils::{OutputFormat, escape_like, fmt_duration, fmt_timestamp};
 use diesel::prelude::*;
 use diesel::upsert::excluded;
-use eyre::Result;
-use time::{Date, Duration};
+use eyre::{Result, bail};
+use owo_colors::{OwoColorize, Style};
+use std::path::PathBuf;
+use time::{Date, Duration, PrimitiveDateTime, Time, UtcOffset, Weekday};
 
 #[derive(Debug)]
 pub struct LogEntry {
     pub date: Date,
     pub task: TaskId,
     pub duration: Duration,
+    /// Offset from UTC at the moment of logging
+    pub utc_offset: Option<UtcOffset>,
+    /// Time the work started, the earliest one is kept when entries are merged
+    pub start: Option<Time>,
+    pub source: EntrySource,
+}
+
+/// How a log entry was added. Merged entries keep the known source of the first one, replaced
+/// entries take the new one.
+#[derive(Debug, Clone, Copy)]
+pub enum EntrySource {
+    Cli,
+    /// JSON-RPC daemon
+    Api,
+    /// Journal of another machine
+    Sync,
+    /// Import from another tool, stored as "import:<tool>"
+    Import(&'static str),
+}
+
+impl std::fmt::Display for EntrySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntrySource::Cli => f.write_str("cli"),
+            EntrySource::Api => f.write_str("api"),
+            EntrySource::Sync => f.write_str("sync"),
+            EntrySource::Import(tool) => write!(f, "import:{tool}"),
+        }
+    }
+}
+
+/// Selects log entries to show
+#[derive(Debug, Clone, Default)]
+pub struct EntryFilter {
+    pub task: Option<TaskFilter>,
+    /// Source of the entries, "import" also matches imports from every tool
+    pub source: Option<String>,
+    /// Only entries added or changed after this UTC time
+    pub changed_since: Option<PrimitiveDateTime>,
 }
 
 #[derive(Debug)]
@@ -26,23 +73,324 @@ pub struct LogEntryExpanded {
     pub duration: Duration,
 }
 
+/// Logged time of a task over a period
+#[derive(Debug)]
+pub struct TaskTotal {
+    pub task_id: TaskId,
+    pub task_name: String,
+    pub issue_number: Option<i32>,
+    pub duration: Duration,
+}
+
+impl From<LogEntryExpanded> for TaskTotal {
+    fn from(entry: LogEntryExpanded) -> Self {
+        Self {
+            task_id: entry.task_id,
+            task_name: entry.task_name,
+            issue_number: entry.issue_number,
+            duration: entry.duration,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Period {
     pub from: Date,
     pub to: Date,
 }
 
-pub fn add_log(conn: &mut SqliteConnection, project: ProjectId, entry: LogEntry) -> Result<()> {
+impl Period {
+    /// Calendar month containing the date
+    pub fn month(date: Date) -> Self {
+        let from = date.replace_day(1).unwrap();
+        let to = date
+            .replace_day(time::util::days_in_month(date.month(), date.year()))
+            .unwrap();
+        Self { from, to }
+    }
+
+    /// Period from the first log entry on, up to the date. Queries treat it as unbounded, views
+    /// going through its days start it with [`Period::clamp_start`].
+    pub fn until(to: Date) -> Self {
+        Self {
+            from: Date::MIN,
+            to,
+        }
+    }
+
+    pub fn is_open_start(&self) -> bool {
+        self.from == Date::MIN
+    }
+
+    /// Period starting on the first date instead if it starts from the beginning
+    pub fn clamp_start(self, first: Option<Date>) -> Self {
+        if !self.is_open_start() {
+            return self;
+        }
+        Self {
+            from: first.map_or(self.to, |first| first.min(self.to)),
+            to: self.to,
+        }
+    }
+
+    /// Whether the period starts on the first and ends on the last day of a month
+    pub fn is_whole_months(&self) -> bool {
+        self.from.day() == 1 && self.to.next_day().is_none_or(|date| date.day() == 1)
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = Date> + use<> {
+        let to = self.to;
+        std::iter::successors(Some(self.from), |date| date.next_day())
+            .take_while(move |date| *date <= to)
+    }
+}
+
+// Month number since 1BCE, year * 12 + month
+pub fn month_index(date: Date) -> i32 {
+    date.year() * 12 + date.month() as i32
+}
+
+/// Limits checked on every change of log entries
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Longest total of a project on a day, see `wlog config max-daily-minutes`
+    pub max_daily: Duration,
+    /// Entries before the date are locked, see `wlog config edit-grace-days`
+    pub locked_before: Option<Date>,
+    /// Exceeding the daily maximum only warns and locked entries can be changed
+    pub force: bool,
+}
+
+impl Limits {
+    pub fn new(config: &Config, force: bool) -> Result<Self> {
+        let today = config.now()?.date();
+        Ok(Self {
+            max_daily: config.max_daily_duration(),
+            locked_before: config
+                .edit_grace_days
+                .map(|days| today.saturating_sub(Duration::days(days as i64))),
+            force,
+        })
+    }
+
+    /// Day maximum of 24 hours without locked entries, for tests
+    #[cfg(test)]
+    pub fn unlimited() -> Self {
+        Self {
+            max_daily: Duration::hours(24),
+            locked_before: None,
+            force: false,
+        }
+    }
+
+    /// Fails if the date is past the edit grace period, unless forced. Protects already reported
+    /// entries from accidental late edits.
+    pub fn check_date(&self, date: Date) -> Result<()> {
+        if let Some(locked_before) = self.locked_before
+            && date < locked_before
+            && !self.force
+        {
+            bail!(WlogError::Validation(format!(
+                "Entries before {locked_before} are locked, use --force to modify entries of {date}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_day_total(&self, date: Date, total: Duration) -> Result<()> {
+        if total <= self.max_daily {
+            return Ok(());
+        }
+        let msg = format!(
+            "Total logged on {date} would be {}, exceeding daily maximum of {}",
+            fmt_duration(total),
+            fmt_duration(self.max_daily)
+        );
+        if !self.force {
+            bail!(WlogError::Validation(format!(
+                "{msg}, use --force to log anyway"
+            )));
+        }
+        eprintln!("{} {msg}", "Warning:".yellow().bold());
+        Ok(())
+    }
+}
+
+pub fn add_log(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    entry: LogEntry,
+    limits: &Limits,
+) -> Result<()> {
+    schedule::check_workday(conn, project, entry.date)?;
     schedule::log(conn, project, entry.date)?;
-    new_log(conn, entry.into())
+    new_log(conn, project, entry.try_into()?, Upsert::Add, limits)
 }
 
+/// Log the entry replacing duration of an existing entry of the task on the date
+pub fn replace_log(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    entry: LogEntry,
+    limits: &Limits,
+) -> Result<()> {
+    schedule::check_workday(conn, project, entry.date)?;
+    schedule::log(conn, project, entry.date)?;
+    new_log(conn, project, entry.try_into()?, Upsert::Replace, limits)
+}
+
+/// Reassign entries of a task within the period to another task of the same project, merging
+/// durations of entries that end up on the same date. Returns dates of moved entries.
+pub fn move_entries(
+    conn: &mut SqliteConnection,
+    from: TaskId,
+    to: TaskId,
+    period: &Period,
+    limits: &Limits,
+) -> Result<Vec<Date>> {
+    if from == to {
+        eyre::bail!(WlogError::Validation(
+            "Source and destination tasks are the same".into()
+        ));
+    }
+    conn.transaction(|conn| {
+        let project_of = |conn: &mut SqliteConnection, task: TaskId| -> Result<i32> {
+            tasks::table
+                .find(task.0)
+                .select(tasks::project_id)
+                .first::<i32>(conn)
+                .optional()?
+                .ok_or_else(|| WlogError::NotFound(format!("Task {} doesn't exist", task.0)).into())
+        };
+        let project = project_of(conn, to)?;
+        if project_of(conn, from)? != project {
+            eyre::bail!(WlogError::Validation(format!(
+                "Tasks {} and {} belong to different projects, use `wlog task move` first",
+                from.0, to.0
+            )));
+        }
+        let entries = log_entries::table
+            .filter(log_entries::task_id.eq(from))
+            .filter(log_entries::date.ge(period.from))
+            .filter(log_entries::date.le(period.to))
+            .select((
+                DbLogEntry::as_select(),
+                log_entries::utc_offset_seconds,
+                log_entries::start_time,
+                log_entries::source,
+            ))
+            .load::<(DbLogEntry, Option<i32>, Option<Time>, Option<String>)>(conn)?;
+        // Removed first so that day totals don't count moved entries twice
+        diesel::delete(
+            log_entries::table
+                .filter(log_entries::task_id.eq(from))
+                .filter(log_entries::date.ge(period.from))
+                .filter(log_entries::date.le(period.to)),
+        )
+        .execute(conn)?;
+        for (entry, utc_offset_seconds, start_time, source) in &entries {
+            new_log(
+                conn,
+                ProjectId(project),
+                DbNewEntry {
+                    date: entry.date,
+                    task_id: to,
+                    duration_seconds: entry.duration_seconds,
+                    utc_offset_seconds: *utc_offset_seconds,
+                    start_time: *start_time,
+                    source: source.clone(),
+                },
+                Upsert::Add,
+                limits,
+            )?;
+        }
+        Ok(entries.into_iter().map(|(entry, ..)| entry.date).collect())
+    })
+}
+
+/// Replace the task's entry on the date with entries of other tasks. Durations of the pieces must
+/// add up to the original duration unless `uneven` is set. Pieces keep the time zone and source of
+/// the original entry and follow each other from its start time, pieces that would start past
+/// midnight are left without one.
+pub fn split_entry(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+    task: TaskId,
+    pieces: &[(Duration, TaskId)],
+    uneven: bool,
+    limits: &Limits,
+) -> Result<()> {
+    conn.transaction(|conn| {
+        let (original, utc_offset_seconds, start_time, source) = log_entries::table
+            .find((date, task.0))
+            .select((
+                log_entries::duration_seconds,
+                log_entries::utc_offset_seconds,
+                log_entries::start_time,
+                log_entries::source,
+            ))
+            .first::<(i32, Option<i32>, Option<Time>, Option<String>)>(conn)
+            .optional()?
+            .ok_or_else(|| {
+                WlogError::NotFound(format!("Task {} has no entry on {date}", task.0))
+            })?;
+        let original = Duration::seconds(original as i64);
+        let total: Duration = pieces.iter().map(|(duration, _)| *duration).sum();
+        if total != original && !uneven {
+            bail!(WlogError::Validation(format!(
+                "Pieces add up to {} instead of {}, use --uneven to split anyway",
+                fmt_duration(total),
+                fmt_duration(original)
+            )));
+        }
+
+        limits.check_date(date)?;
+        diesel::delete(log_entries::table.find((date, task.0))).execute(conn)?;
+        let mut start_time = start_time;
+        for &(duration, task) in pieces.iter().filter(|(d, _)| d.is_positive()) {
+            let duration_seconds = i32::try_from(duration.whole_seconds()).map_err(|_| {
+                WlogError::Validation(format!(
+                    "Duration {} is too long to log",
+                    fmt_duration(duration)
+                ))
+            })?;
+            new_log(
+                conn,
+                project,
+                DbNewEntry {
+                    date,
+                    task_id: task,
+                    duration_seconds,
+                    utc_offset_seconds,
+                    start_time,
+                    source: source.clone(),
+                },
+                Upsert::Add,
+                limits,
+            )?;
+            start_time = start_time.and_then(|start| {
+                let end = PrimitiveDateTime::new(date, start).checked_add(duration)?;
+                (end.date() == date).then_some(end.time())
+            });
+        }
+        Ok(())
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn show_by_day(
     conn: &mut SqliteConnection,
+    config: &Config,
     project: &Project,
     period: Option<&Period>,
+    filter: Option<&EntryFilter>,
     show_comments: bool,
+    fill: bool,
+    flat: bool,
+    format: OutputFormat,
 ) -> Result<()> {
-    let entries = get_by_day_expanded(conn, project.id, period)?;
+    let entries = get_by_day_expanded(conn, project.id, period, filter)?;
 
     let comment_entries = if show_comments {
         comments::get_by_period(conn, project.id, period)?
@@ -50,70 +398,484 @@ pub fn show_by_day(
         Vec::new()
     };
 
-    let rows = merge_entries_and_comments(&entries, &comment_entries);
-
-    let mut table = comfy_table::Table::new();
-    table.load_preset(crate::utils::TABLE_STYLE);
-    table.set_header(["Date", "Weekday", "Issue", "Task", "Duration"]);
-    table.add_rows(rows.iter().map(|row| {
-        match row {
-            DisplayRow::LogEntry(entry) => [
-                entry.date.to_string(),
-                entry.date.weekday().to_string(),
-                entry
-                    .issue_number
-                    .map(|n| fmt_issue_linked(n, &project.url))
-                    .unwrap_or_else(|| "-".to_string()),
-                entry.task_name.clone(),
-                entry.duration.to_string(),
-            ],
-            DisplayRow::Comment(comment) => [
-                comment.date.to_string(),
-                comment.date.weekday().to_string(),
-                "-".to_string(),
-                format!("~ {} ~", comment.text),
-                comment
-                    .duration
-                    .map(|d| d.to_string())
-                    .unwrap_or_else(|| "-".to_string()),
-            ],
-        }
-    }));
-
-    println!("{table}");
+    let mut rows = merge_entries_and_comments(&entries, &comment_entries);
+    if fill {
+        let first = rows.first().map(|row| row.date());
+        let bounds = period
+            .map(|p| (p.clamp_start(first).from, p.to))
+            .or_else(|| {
+                let first = rows.first()?.date();
+                let last = rows.last()?.date();
+                Some((first, last))
+            });
+        if let Some((from, to)) = bounds {
+            rows = fill_missing_days(rows, from, to);
+        }
+    }
 
+    let locale = config.locale();
+    let cells = |row: &DisplayRow| match row {
+        DisplayRow::LogEntry(entry) => [
+            entry
+                .issue_number
+                .map(|n| format.issue(n, project))
+                .unwrap_or_else(|| "-".to_string()),
+            format.task_name(&entry.task_name),
+            config.fmt_duration(entry.duration),
+        ],
+        DisplayRow::Comment(comment) => [
+            "-".to_string(),
+            format!("~ {} ~", comment.text),
+            comment
+                .duration
+                .map(|d| config.fmt_duration(d))
+                .unwrap_or_else(|| "-".to_string()),
+        ],
+        DisplayRow::Empty(_) => [
+            "-".to_string(),
+            "-".to_string(),
+            config.fmt_duration(Duration::ZERO),
+        ],
+    };
     let total_duration = entries
         .iter()
         .fold(Duration::ZERO, |total, log| total + log.duration);
-    eprintln!("Total: {}h", total_duration.whole_hours(),);
 
+    if !format.has_totals() {
+        let header = ["Date", "Weekday", "Issue", "Task", "Duration"];
+        let table = rows.iter().map(|row| {
+            let date = row.date();
+            [date.to_string(), locale.weekday(date.weekday()).to_string()]
+                .into_iter()
+                .chain(cells(row))
+        });
+        format.print_table(config.table_style(), &header, table)?;
+        match config.duration_format() {
+            DurationFormat::Standard => eprintln!("Total: {}h", total_duration.whole_hours()),
+            DurationFormat::Industrial => {
+                eprintln!("Total: {}", config.fmt_duration(total_duration))
+            }
+        }
+        return Ok(());
+    }
+
+    // Sections start with the date and end with the day total, the flat layout repeats the date in
+    // every row instead
+    let header: &[&str] = if flat {
+        &["Date", "Weekday", "Issue", "Task", "Duration"]
+    } else {
+        &["Date", "Issue", "Task", "Duration"]
+    };
+    let summary = |label_column: usize, label: &str, duration: Duration| {
+        let mut row = vec![String::new(); header.len()];
+        row[label_column] = format.strong(label);
+        row[header.len() - 1] = format.strong(&config.fmt_duration(duration));
+        row
+    };
+    let label_column = header.len() - 2;
+
+    // Subtotals after every ISO week, unless the whole table is a single week
+    let week_of = |date: Date| {
+        let (year, week, _) = date.to_iso_week_date();
+        (year, week)
+    };
+    let multiple_weeks =
+        rows.first().map(|row| week_of(row.date())) != rows.last().map(|row| week_of(row.date()));
+
+    let mut table = Vec::new();
+    let (mut day_total, mut week_total) = (Duration::ZERO, Duration::ZERO);
+    for (i, row) in rows.iter().enumerate() {
+        let date = row.date();
+        let next = rows.get(i + 1).map(DisplayRow::date);
+        if let DisplayRow::LogEntry(entry) = row {
+            day_total += entry.duration;
+            week_total += entry.duration;
+        }
+
+        if flat {
+            table.push(
+                [date.to_string(), locale.weekday(date.weekday()).to_string()]
+                    .into_iter()
+                    .chain(cells(row))
+                    .collect::<Vec<_>>(),
+            );
+        } else {
+            if i == 0 || rows[i - 1].date() != date {
+                let mut section = vec![String::new(); header.len()];
+                section[0] = format.strong(&format!("{date} {}", locale.weekday(date.weekday())));
+                table.push(section);
+            }
+            if !matches!(row, DisplayRow::Empty(_)) {
+                table.push(
+                    std::iter::once(String::new())
+                        .chain(cells(row))
+                        .collect::<Vec<_>>(),
+                );
+            }
+            if next != Some(date) {
+                table.push(summary(label_column, "Day total", day_total));
+            }
+        }
+        if next != Some(date) {
+            day_total = Duration::ZERO;
+        }
+
+        if multiple_weeks && next.is_none_or(|next| week_of(next) != week_of(date)) {
+            let (year, week) = week_of(date);
+            table.push(summary(
+                label_column,
+                &format!("Week {week}, {year}"),
+                week_total,
+            ));
+            week_total = Duration::ZERO;
+        }
+    }
+    table.push(summary(0, "Total", total_duration));
+    format.print_table(config.table_style(), header, table)
+}
+
+/// Calendars of the months of the period with days shaded by time logged relative to the
+/// scheduled time. Without a period months from the first entry to today are shown.
+pub fn show_heatmap(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: Option<&Period>,
+) -> Result<()> {
+    let period = match period {
+        Some(period) if period.is_open_start() => {
+            period.clamp_start(get_first_date(conn, project.id)?)
+        }
+        Some(period) => *period,
+        None => match get_first_date(conn, project.id)? {
+            Some(from) => Period {
+                from,
+                to: config.now()?.date(),
+            },
+            None => return Ok(()),
+        },
+    };
+    let totals = get_daily_totals(conn, project.id, &period)?;
+
+    let locale = config.locale();
+    let mut month = period.from.replace_day(1).unwrap();
+    while month <= period.to {
+        println!(" {} {}", locale.month(month.month()), month.year());
+        let mut weekday = Weekday::Monday;
+        for _ in 0..7 {
+            print!(" {}", locale.weekday_short(weekday));
+            weekday = weekday.next();
+        }
+        println!();
+        print!(
+            "{: <1$}",
+            "",
+            month.weekday().number_days_from_monday() as usize * 3
+        );
+        let days = Period::month(month);
+        for date in days.days() {
+            if date.weekday() == Weekday::Monday && date.day() != 1 {
+                println!();
+            }
+            let logged = totals
+                .iter()
+                .find(|(d, _)| *d == date)
+                .map(|(_, duration)| *duration)
+                .unwrap_or_default();
+            let scheduled =
+                schedule::scheduled_duration(conn, project.id, date)?.unwrap_or_default();
+            print!(
+                " {}",
+                format!("{: >2}", date.day()).style(heat_style(logged, scheduled))
+            );
+        }
+        println!();
+        month = days.to.next_day().unwrap();
+    }
+
+    print!(" Less");
+    for shade in HEAT_SHADES {
+        print!(" {}", "  ".on_truecolor(shade.0, shade.1, shade.2));
+    }
+    println!(" More");
     Ok(())
 }
 
-pub fn show_by_task(
+/// Background colors of heatmap cells from the least to the most logged time
+const HEAT_SHADES: [(u8, u8, u8); 4] = [(14, 68, 41), (0, 109, 50), (38, 166, 65), (57, 211, 83)];
+
+fn heat_style(logged: Duration, scheduled: Duration) -> Style {
+    if !logged.is_positive() {
+        return if scheduled.is_positive() {
+            Style::new().bold()
+        } else {
+            Style::new().dimmed()
+        };
+    }
+    let ratio = if scheduled.is_positive() {
+        logged / scheduled
+    } else {
+        1.0
+    };
+    let shade = match ratio {
+        r if r < 0.25 => HEAT_SHADES[0],
+        r if r < 0.5 => HEAT_SHADES[1],
+        r if r < 1.0 => HEAT_SHADES[2],
+        _ => HEAT_SHADES[3],
+    };
+    Style::new().black().on_truecolor(shade.0, shade.1, shade.2)
+}
+
+/// Logged time per task in the period next to the time in the compared period
+pub fn show_comparison(
     conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: &Period,
+    compared: &Period,
+    filter: Option<&EntryFilter>,
+    format: OutputFormat,
+) -> Result<()> {
+    let current = get_task_totals(conn, project.id, Some(period), filter)?;
+    let previous = get_task_totals(conn, project.id, Some(compared), filter)?;
+
+    let mut tasks = Vec::<&TaskTotal>::new();
+    for entry in current.iter().chain(&previous) {
+        if !tasks.iter().any(|task| task.task_id == entry.task_id) {
+            tasks.push(entry);
+        }
+    }
+
+    let duration_of = |entries: &[TaskTotal], task: TaskId| {
+        entries
+            .iter()
+            .find(|entry| entry.task_id == task)
+            .map(|entry| entry.duration)
+            .unwrap_or_default()
+    };
+    let header = [
+        "Issue".to_string(),
+        "Task".to_string(),
+        format!("{} - {}", period.from, period.to),
+        format!("{} - {}", compared.from, compared.to),
+        "Delta".to_string(),
+        "Change".to_string(),
+    ];
+    let rows = tasks.iter().map(|task| {
+        let now = duration_of(&current, task.task_id);
+        let then = duration_of(&previous, task.task_id);
+        let delta = now - then;
+        let change = if then.is_zero() {
+            "new".to_string()
+        } else {
+            format!("{:+.0}%", delta / then * 100.0)
+        };
+        [
+            task.issue_number
+                .map(|n| format.issue(n, project))
+                .unwrap_or_else(|| "-".to_string()),
+            format.task_name(&task.task_name),
+            config.fmt_duration(now),
+            config.fmt_duration(then),
+            format!(
+                "{}{}",
+                if delta.is_negative() { "" } else { "+" },
+                config.fmt_duration(delta)
+            ),
+            change,
+        ]
+    });
+    let header = header.iter().map(String::as_str).collect::<Vec<_>>();
+    format.print_table(config.table_style(), &header, rows)
+}
+
+/// Print the tasks with the most time in the period, longest first, with their shares of the
+/// total time
+pub fn show_top(
+    conn: &mut SqliteConnection,
+    config: &Config,
     project: &Project,
     period: Option<&Period>,
-    csv_to_clipboard: bool,
+    filter: Option<&EntryFilter>,
+    count: usize,
+    format: OutputFormat,
 ) -> Result<()> {
-    let entries = get_by_task_expanded(conn, project.id, period)?;
+    let mut entries = get_task_totals(conn, project.id, period, filter)?;
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.duration));
+    let total: Duration = entries.iter().map(|entry| entry.duration).sum();
 
-    let mut table = comfy_table::Table::new();
-    table.load_preset(crate::utils::TABLE_STYLE);
-    table.set_header(vec!["Issue", "Task", "Duration"]);
-    table.add_rows(entries.iter().map(|entry| {
+    let share = |duration: Duration| {
+        if total.is_zero() {
+            0.0
+        } else {
+            duration / total * 100.0
+        }
+    };
+    let mut cumulative = Duration::ZERO;
+    let rows = entries.iter().take(count).enumerate().map(|(i, entry)| {
+        cumulative += entry.duration;
         [
+            (i + 1).to_string(),
             entry
                 .issue_number
-                .map(|n| fmt_issue_linked(n, &project.url))
+                .map(|n| format.issue(n, project))
                 .unwrap_or_else(|| "-".to_string()),
-            entry.task_name.clone(),
-            entry.duration.to_string(),
+            format.task_name(&entry.task_name),
+            config.fmt_duration(entry.duration),
+            format!("{:.1}%", share(entry.duration)),
+            format!("{:.1}%", share(cumulative)),
         ]
-    }));
-    println!("{table}");
+    });
+    format.print_table(
+        config.table_style(),
+        &["#", "Issue", "Task", "Duration", "Share", "Cumulative"],
+        rows,
+    )
+}
 
-    if csv_to_clipboard {
+/// Print time per task label. Tasks with several labels count towards each of them, tasks
+/// without labels are summed up separately.
+pub fn show_by_label(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: ProjectId,
+    period: Option<&Period>,
+    filter: Option<&EntryFilter>,
+    format: OutputFormat,
+) -> Result<()> {
+    let entries = get_task_totals(conn, project, period, filter)?;
+    let ids = entries
+        .iter()
+        .map(|entry| entry.task_id)
+        .collect::<Vec<_>>();
+    let labels = crate::tasks::get_labels(conn, &ids)?;
+
+    let mut totals = std::collections::BTreeMap::<Option<&str>, (usize, Duration)>::new();
+    for entry in &entries {
+        let task_labels = labels.get(&entry.task_id).map(Vec::as_slice);
+        let keys = match task_labels {
+            Some(task_labels) => task_labels.iter().map(|l| Some(l.as_str())).collect(),
+            None => vec![None],
+        };
+        for key in keys {
+            let total = totals.entry(key).or_default();
+            total.0 += 1;
+            total.1 += entry.duration;
+        }
+    }
+    let mut totals = totals.into_iter().collect::<Vec<_>>();
+    totals.sort_by_key(|(_, (_, duration))| std::cmp::Reverse(*duration));
+
+    let total: Duration = entries.iter().map(|entry| entry.duration).sum();
+    let footer = format.has_totals().then(|| {
+        [
+            format.strong("Total"),
+            entries.len().to_string(),
+            format.strong(&config.fmt_duration(total)),
+        ]
+    });
+    format.print_table(
+        config.table_style(),
+        &["Label", "Tasks", "Duration"],
+        totals
+            .into_iter()
+            .map(|(label, (tasks, duration))| {
+                [
+                    label.unwrap_or("-").to_string(),
+                    tasks.to_string(),
+                    config.fmt_duration(duration),
+                ]
+            })
+            .chain(footer),
+    )
+}
+
+/// Print progress of tasks with weekly targets towards their targets over the period
+pub fn show_target_progress(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: &Period,
+) -> Result<()> {
+    const WIDTH: usize = 20;
+
+    let targets = crate::tasks::get_targets(conn, project.id)?;
+    if targets.is_empty() {
+        return Ok(());
+    }
+    let totals = get_task_totals(conn, project.id, Some(period), None)?;
+    let name_width = targets
+        .iter()
+        .map(|(task, _)| task.name.len())
+        .max()
+        .unwrap_or(0);
+
+    println!();
+    for (task, target) in targets {
+        let logged = totals
+            .iter()
+            .find(|total| total.task_id == task.id)
+            .map(|total| total.duration)
+            .unwrap_or_default();
+        let ratio = logged / target;
+        let filled = ((ratio * WIDTH as f64).round() as usize).min(WIDTH);
+        let bar = format!("{}{}", "█".repeat(filled), "░".repeat(WIDTH - filled));
+        let style = if ratio >= 1.0 {
+            Style::new().green()
+        } else {
+            Style::new().yellow()
+        };
+        println!(
+            "{:<name_width$}  {} {}/{} {:.0}%",
+            task.name,
+            bar.style(style),
+            config.fmt_duration(logged),
+            config.fmt_duration(target),
+            ratio * 100.0
+        );
+    }
+    Ok(())
+}
+
+pub fn show_by_task(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: Option<&Period>,
+    filter: Option<&EntryFilter>,
+    csv_target: Option<&CsvTarget>,
+    format: OutputFormat,
+) -> Result<()> {
+    let entries = get_task_totals(conn, project.id, period, filter)?;
+
+    let header = ["Issue", "Task", "Duration"];
+    let total: Duration = entries.iter().map(|entry| entry.duration).sum();
+    let footer = format.has_totals().then(|| {
+        [
+            format.strong("Total"),
+            String::new(),
+            format.strong(&config.fmt_duration(total)),
+        ]
+    });
+    format.print_table(
+        config.table_style(),
+        &header,
+        entries
+            .iter()
+            .map(|entry| {
+                [
+                    entry
+                        .issue_number
+                        .map(|n| format.issue(n, project))
+                        .unwrap_or_else(|| "-".to_string()),
+                    format.task_name(&entry.task_name),
+                    config.fmt_duration(entry.duration),
+                ]
+            })
+            .chain(footer),
+    )?;
+
+    if let Some(target) = csv_target {
         use std::io::Write;
         let csv = entries.iter().fold(Vec::new(), |mut csv, entry| {
             writeln!(
@@ -121,70 +883,546 @@ pub fn show_by_task(
                 "{}{};{}",
                 entry
                     .issue_number
-                    .map(|n| format!("[#{n}] "))
+                    .map(|n| format!("[{}] ", project.issue_key(n)))
                     .unwrap_or_default(),
                 entry.task_name.as_str(),
-                entry.duration.whole_hours(),
+                match config.duration_format() {
+                    DurationFormat::Standard => entry.duration.whole_hours().to_string(),
+                    DurationFormat::Industrial => config.fmt_duration(entry.duration),
+                },
             )
             .unwrap();
             csv
         });
         let csv = String::from_utf8(csv).unwrap();
         println!();
-        let mut clipboard = arboard::Clipboard::new().unwrap();
-        clipboard.set_text(&csv).unwrap();
+        target.write(&csv)?;
         println!("{csv}")
     }
 
     Ok(())
 }
 
-pub fn get_by_day_expanded(
+/// Destination of the task summary CSV
+#[derive(Debug, Clone)]
+pub enum CsvTarget {
+    /// Clipboard, or a temporary file if the clipboard is unavailable, e.g. in headless sessions
+    Clipboard,
+    File(PathBuf),
+}
+
+impl CsvTarget {
+    fn write(&self, csv: &str) -> Result<()> {
+        match self {
+            CsvTarget::Clipboard => {
+                let copied =
+                    arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(csv));
+                if let Err(e) = copied {
+                    let path = std::env::temp_dir()
+                        .join(format!("wlog-summary-{}.csv", std::process::id()));
+                    std::fs::write(&path, csv)?;
+                    eprintln!(
+                        "{} Unable to copy to the clipboard: {e}. Saved to {}",
+                        "Warning:".yellow().bold(),
+                        path.display()
+                    );
+                }
+            }
+            CsvTarget::File(path) => {
+                std::fs::write(path, csv)?;
+                eprintln!("{} Saved to {}", "Success:".green().bold(), path.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replace duration of the task's entry on the date, removing the entry if the duration is zero
+pub fn set_duration(
     conn: &mut SqliteConnection,
     project: ProjectId,
-    period: Option<&Period>,
-) -> Result<Vec<LogEntryExpanded>> {
-    let mut query = log_entries::table
+    date: Date,
+    task: TaskId,
+    duration: Duration,
+    limits: &Limits,
+) -> Result<()> {
+    limits.check_date(date)?;
+    if duration.is_zero() {
+        diesel::delete(log_entries::table.find((date, task.0))).execute(conn)?;
+        return Ok(());
+    }
+    schedule::log(conn, project, date)?;
+    let entry = LogEntry {
+        date,
+        task,
+        duration,
+        utc_offset: None,
+        start: None,
+        source: EntrySource::Sync,
+    };
+    new_log(conn, project, entry.try_into()?, Upsert::Replace, limits)
+}
+
+/// Print entries of the project on the date together with the values they replaced
+pub fn history(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    date: Date,
+) -> Result<()> {
+    let rows = get_history(conn, project.id, date)?;
+    if rows.is_empty() {
+        eprintln!("{} No log entries on {date}", "Note:".cyan());
+        return Ok(());
+    }
+
+    let offset = config.now()?.offset();
+    let rows = rows.into_iter().map(|(task, seconds, recorded, replaced)| {
+        [
+            task,
+            config.fmt_duration(Duration::seconds(seconds as i64)),
+            recorded
+                .map(|at| fmt_timestamp(at, offset))
+                .unwrap_or_else(|| "-".to_string()),
+            match replaced {
+                None => "current".to_string(),
+                Some((at, false)) => fmt_timestamp(at, offset),
+                Some((at, true)) => format!("{} (removed)", fmt_timestamp(at, offset)),
+            },
+        ]
+    });
+    OutputFormat::Table.print_table(
+        config.table_style(),
+        &["Task", "Duration", "Recorded", "Replaced"],
+        rows,
+    )
+}
+
+/// Task name, duration, recorded time and the time the value was replaced or removed at, if it
+/// isn't the current value
+type HistoryRow = (
+    String,
+    i32,
+    Option<PrimitiveDateTime>,
+    Option<(PrimitiveDateTime, bool)>,
+);
+
+/// Current and previous values of the entries of the day, ordered by task and time
+fn get_history(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+) -> Result<Vec<HistoryRow>> {
+    let current = log_entries::table
         .inner_join(tasks::table)
         .filter(tasks::project_id.eq(project.0))
-        .into_boxed();
-    if let Some(period) = period {
-        query = query
-            .filter(log_entries::date.ge(period.from))
-            .filter(log_entries::date.le(period.to));
+        .filter(log_entries::date.eq(date))
+        .select((
+            tasks::name,
+            log_entries::duration_seconds,
+            log_entries::updated_at,
+        ))
+        .load::<(String, i32, Option<PrimitiveDateTime>)>(conn)?;
+    // Removed tasks are shown with the name they had when the value was replaced
+    let previous = entry_history::table
+        .left_join(tasks::table)
+        .filter(entry_history::project_id.eq(project.0))
+        .filter(entry_history::date.eq(date))
+        .order_by(entry_history::id)
+        .select((
+            tasks::name.nullable(),
+            entry_history::task_name,
+            entry_history::duration_seconds,
+            entry_history::recorded_at,
+            entry_history::replaced_at,
+            entry_history::removed,
+        ))
+        .load::<(
+            Option<String>,
+            Option<String>,
+            i32,
+            Option<PrimitiveDateTime>,
+            PrimitiveDateTime,
+            bool,
+        )>(conn)?;
+
+    // Entries logged before timestamps were recorded have no recorded time and go first, current
+    // values go after the values they replaced
+    let mut rows = previous
+        .into_iter()
+        .map(|(task, task_name, seconds, recorded, replaced, removed)| {
+            let task = task.or(task_name).unwrap_or_default();
+            (task, seconds, recorded, Some((replaced, removed)))
+        })
+        .chain(
+            current
+                .into_iter()
+                .map(|(task, seconds, recorded)| (task, seconds, recorded, None)),
+        )
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| (&a.0, a.2, a.3.is_none()).cmp(&(&b.0, b.2, b.3.is_none())));
+    Ok(rows)
+}
+
+pub fn get_day_total(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+) -> Result<Duration> {
+    let seconds = log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.eq(date))
+        .select(diesel::dsl::sum(log_entries::duration_seconds))
+        .get_result::<Option<i64>>(conn)?;
+    Ok(Duration::seconds(seconds.unwrap_or(0)))
+}
+
+/// Logged duration of the task on the date, zero if there's no entry
+pub fn get_duration(conn: &mut SqliteConnection, date: Date, task: TaskId) -> Result<Duration> {
+    let seconds = log_entries::table
+        .find((date, task.0))
+        .select(log_entries::duration_seconds)
+        .first::<i32>(conn)
+        .optional()?;
+    Ok(Duration::seconds(seconds.unwrap_or(0) as i64))
+}
+
+/// Total logged duration of the project in the month containing the date
+pub fn get_month_total(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    month: Date,
+) -> Result<Duration> {
+    let seconds = monthly_totals::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(monthly_totals::month.eq(month_index(month)))
+        .select(diesel::dsl::sum(monthly_totals::duration_seconds))
+        .get_result::<Option<i64>>(conn)?;
+    Ok(Duration::seconds(seconds.unwrap_or(0)))
+}
+
+/// Date of the earliest log entry of the project
+pub fn get_first_date(conn: &mut SqliteConnection, project: ProjectId) -> Result<Option<Date>> {
+    log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .select(diesel::dsl::min(log_entries::date))
+        .get_result(conn)
+        .map_err(Into::into)
+}
+
+/// Date of the latest log entry of the project
+pub fn get_last_date(conn: &mut SqliteConnection, project: ProjectId) -> Result<Option<Date>> {
+    log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .select(diesel::dsl::max(log_entries::date))
+        .get_result(conn)
+        .map_err(Into::into)
+}
+
+/// Latest log entry of the project with its task, the last updated one of the latest date
+pub fn get_last_entry(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+) -> Result<Option<(Date, Task, Duration)>> {
+    log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .order_by((log_entries::date.desc(), log_entries::updated_at.desc()))
+        .select((
+            log_entries::date,
+            Task::as_select(),
+            log_entries::duration_seconds,
+        ))
+        .first::<(Date, Task, i32)>(conn)
+        .optional()
+        .map(|entry| {
+            entry.map(|(date, task, seconds)| (date, task, Duration::seconds(seconds as i64)))
+        })
+        .map_err(Into::into)
+}
+
+/// UTC time entries of the project were last shown at
+pub fn get_last_shown(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+) -> Result<Option<PrimitiveDateTime>> {
+    last_shown::table
+        .find(project.0)
+        .select(last_shown::shown_at)
+        .first(conn)
+        .optional()
+        .map_err(Into::into)
+}
+
+/// Record that entries of the project were shown at the UTC time. Returns false without recording
+/// anything if the database is read-only.
+pub fn mark_shown(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    at: PrimitiveDateTime,
+) -> Result<bool> {
+    let result = diesel::replace_into(last_shown::table)
+        .values((
+            last_shown::project_id.eq(project.0),
+            last_shown::shown_at.eq(at),
+        ))
+        .execute(conn);
+    match result {
+        Ok(_) => Ok(true),
+        Err(diesel::result::Error::DatabaseError(_, info))
+            if info.message().contains("readonly") =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
     }
-    query
-        .select((DbLogEntry::as_select(), Task::as_select()))
+}
+
+/// Task with the most time on the last day with entries before the date
+pub fn get_previous_task(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+) -> Result<Option<Task>> {
+    let previous = log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.lt(date))
+        .select(diesel::dsl::max(log_entries::date))
+        .get_result::<Option<Date>>(conn)?;
+    let Some(previous) = previous else {
+        return Ok(None);
+    };
+    log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.eq(previous))
+        .order_by(log_entries::duration_seconds.desc())
+        .select(Task::as_select())
+        .first(conn)
+        .optional()
+        .map_err(Into::into)
+}
+
+/// Tasks of the project with log entries along with their last entry date, most recently logged
+/// first
+pub fn get_recent_tasks(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    limit: i64,
+) -> Result<Vec<(Task, Date)>> {
+    log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .group_by(tasks::id)
+        .select((Task::as_select(), diesel::dsl::max(log_entries::date)))
+        .order_by((
+            diesel::dsl::max(log_entries::date).desc(),
+            diesel::dsl::max(log_entries::updated_at).desc(),
+        ))
+        .limit(limit)
+        .load_iter::<(Task, Option<Date>), _>(conn)?
+        .map(|res| res.map(|(task, date)| (task, date.unwrap_or(Date::MIN))))
+        .collect::<Result<_, _>>()
+        .map_err(Into::into)
+}
+
+/// Total duration logged on the date and within the period, summed in one query
+pub fn get_day_and_period_total(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    date: Date,
+    period: &Period,
+) -> Result<(Duration, Duration)> {
+    let (day, period) = log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.ge(period.from))
+        .filter(log_entries::date.le(period.to))
+        .select((
+            diesel::dsl::sql::<diesel::sql_types::Nullable<diesel::sql_types::BigInt>>(
+                "SUM(CASE WHEN log_entries.date = ",
+            )
+            .bind::<diesel::sql_types::Date, _>(date)
+            .sql(" THEN log_entries.duration_seconds ELSE 0 END)"),
+            diesel::dsl::sum(log_entries::duration_seconds),
+        ))
+        .get_result::<(Option<i64>, Option<i64>)>(conn)?;
+    Ok((
+        Duration::seconds(day.unwrap_or(0)),
+        Duration::seconds(period.unwrap_or(0)),
+    ))
+}
+
+/// Total logged duration for every day of the period that has entries
+pub fn get_daily_totals(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    period: &Period,
+) -> Result<Vec<(Date, Duration)>> {
+    log_entries::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(log_entries::date.ge(period.from))
+        .filter(log_entries::date.le(period.to))
+        .group_by(log_entries::date)
+        .select((
+            log_entries::date,
+            diesel::dsl::sum(log_entries::duration_seconds),
+        ))
         .order_by(log_entries::date)
-        .load_iter::<(DbLogEntry, Task), _>(conn)?
-        .map(|res| res.map(Into::into).map_err(Into::into))
+        .load_iter::<(Date, Option<i64>), _>(conn)?
+        .map(|res| {
+            res.map(|(date, seconds)| (date, Duration::seconds(seconds.unwrap_or(0))))
+                .map_err(Into::into)
+        })
         .collect()
 }
 
+pub fn get_by_day_expanded(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    period: Option<&Period>,
+    filter: Option<&EntryFilter>,
+) -> Result<Vec<LogEntryExpanded>> {
+    let mut entries = Vec::new();
+    for_each_entry(conn, project, period, filter, |log, task| {
+        entries.push(LogEntryExpanded::from((log, task)))
+    })?;
+    Ok(entries)
+}
+
 pub fn get_by_task_expanded(
     conn: &mut SqliteConnection,
     project: ProjectId,
     period: Option<&Period>,
+    filter: Option<&EntryFilter>,
 ) -> Result<Vec<LogEntryExpanded>> {
-    let mut query = log_entries::table
+    let mut entries = Vec::<LogEntryExpanded>::new();
+    for_each_entry(conn, project, period, filter, |log, task| {
+        if let Some(el) = entries.iter_mut().find(|el| el.task_id == log.task_id) {
+            el.duration += Duration::seconds(log.duration_seconds as i64);
+        } else {
+            entries.push(LogEntryExpanded::from((log, task)))
+        }
+    })?;
+    Ok(entries)
+}
+
+/// Visit entries in order of date and task ID. Entries are loaded in pages continuing after the
+/// last seen key, so that every page is an index range lookup rather than a scan with offset.
+fn for_each_entry(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    period: Option<&Period>,
+    filter: Option<&EntryFilter>,
+    mut f: impl FnMut(DbLogEntry, Task),
+) -> Result<()> {
+    const PAGE_SIZE: i64 = 1000;
+
+    let mut last = None::<(Date, TaskId)>;
+    loop {
+        let mut query = log_entries::table
+            .inner_join(tasks::table)
+            .filter(tasks::project_id.eq(project.0))
+            .into_boxed();
+        if let Some(period) = period {
+            query = query
+                .filter(log_entries::date.ge(period.from))
+                .filter(log_entries::date.le(period.to));
+        }
+        if let Some(task) = filter.and_then(|filter| filter.task.as_ref()) {
+            query = query.filter(log_entries::task_id.eq_any(task.ids()));
+        }
+        if let Some(source) = filter.and_then(|filter| filter.source.as_ref()) {
+            query = query.filter(
+                log_entries::source
+                    .eq(source.clone())
+                    .or(log_entries::source
+                        .like(format!("{}:%", escape_like(source)))
+                        .escape('\\')),
+            );
+        }
+        if let Some(since) = filter.and_then(|filter| filter.changed_since) {
+            // Timestamps are stored at a precision of seconds, entries changed in the second of
+            // the previous run are included again rather than missed
+            query = query.filter(log_entries::updated_at.ge(since));
+        }
+        if let Some((date, task)) = last {
+            query = query.filter(
+                log_entries::date.gt(date).or(log_entries::date
+                    .eq(date)
+                    .and(log_entries::task_id.gt(task.0))),
+            );
+        }
+        let page = query
+            .select((DbLogEntry::as_select(), Task::as_select()))
+            .order_by((log_entries::date, log_entries::task_id))
+            .limit(PAGE_SIZE)
+            .load::<(DbLogEntry, Task)>(conn)?;
+        let len = page.len() as i64;
+        for (log, task) in page {
+            last = Some((log.date, log.task_id));
+            f(log, task);
+        }
+        if len < PAGE_SIZE {
+            return Ok(());
+        }
+    }
+}
+
+/// Logged time per task in order of the first month with entries. Periods of whole months are
+/// summed up from monthly totals instead of individual entries.
+pub fn get_task_totals(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    period: Option<&Period>,
+    filter: Option<&EntryFilter>,
+) -> Result<Vec<TaskTotal>> {
+    let mut query = monthly_totals::table
         .inner_join(tasks::table)
         .filter(tasks::project_id.eq(project.0))
         .into_boxed();
-    if let Some(period) = period {
-        query = query
-            .filter(log_entries::date.ge(period.from))
-            .filter(log_entries::date.le(period.to));
+    // Monthly totals don't know sources or timestamps of the entries
+    let by_entry =
+        filter.is_some_and(|filter| filter.source.is_some() || filter.changed_since.is_some());
+    match period {
+        None if !by_entry => {}
+        Some(period) if period.is_whole_months() && !by_entry => {
+            query = query
+                .filter(monthly_totals::month.ge(month_index(period.from)))
+                .filter(monthly_totals::month.le(month_index(period.to)));
+        }
+        _ => {
+            return Ok(get_by_task_expanded(conn, project, period, filter)?
+                .into_iter()
+                .map(Into::into)
+                .collect());
+        }
+    }
+    if let Some(task) = filter.and_then(|filter| filter.task.as_ref()) {
+        query = query.filter(monthly_totals::task_id.eq_any(task.ids()));
     }
     query
-        .select((DbLogEntry::as_select(), Task::as_select()))
-        .order_by(log_entries::date)
-        .load_iter::<(DbLogEntry, Task), _>(conn)?
-        .try_fold(Vec::<LogEntryExpanded>::new(), |mut acc, entry| {
-            let (log, task) = entry?;
-            if let Some(el) = acc.iter_mut().find(|el| el.task_id == log.task_id) {
-                el.duration += Duration::minutes(log.duration_minutes as i64);
+        .select((Task::as_select(), monthly_totals::duration_seconds))
+        .order_by((monthly_totals::month, monthly_totals::task_id))
+        .load_iter::<(Task, i32), _>(conn)?
+        .try_fold(Vec::<TaskTotal>::new(), |mut acc, row| {
+            let (task, seconds) = row?;
+            let duration = Duration::seconds(seconds as i64);
+            if let Some(el) = acc.iter_mut().find(|el| el.task_id == task.id) {
+                el.duration += duration;
             } else {
-                acc.push(LogEntryExpanded::from((log, task)))
+                acc.push(TaskTotal {
+                    task_id: task.id,
+                    task_name: task.name,
+                    issue_number: task.issue,
+                    duration,
+                })
             }
             Ok(acc)
         })
@@ -193,6 +1431,17 @@ pub fn get_by_task_expanded(
 enum DisplayRow<'a> {
     LogEntry(&'a LogEntryExpanded),
     Comment(&'a comments::CommentExpanded),
+    Empty(Date),
+}
+
+impl DisplayRow<'_> {
+    fn date(&self) -> Date {
+        match self {
+            DisplayRow::LogEntry(entry) => entry.date,
+            DisplayRow::Comment(comment) => comment.date,
+            DisplayRow::Empty(date) => *date,
+        }
+    }
 }
 
 fn merge_entries_and_comments<'a>(
@@ -216,16 +1465,104 @@ fn merge_entries_and_comments<'a>(
     rows
 }
 
-fn new_log(conn: &mut SqliteConnection, entry: DbNewEntry) -> Result<()> {
-    diesel::insert_into(log_entries::table)
+fn fill_missing_days(rows: Vec<DisplayRow<'_>>, from: Date, to: Date) -> Vec<DisplayRow<'_>> {
+    let mut filled = Vec::with_capacity(rows.len());
+    let mut rows = rows.into_iter().peekable();
+    let mut date = from;
+    while date <= to {
+        if rows.peek().is_none_or(|row| row.date() != date) {
+            filled.push(DisplayRow::Empty(date));
+        }
+        while let Some(row) = rows.next_if(|row| row.date() == date) {
+            filled.push(row);
+        }
+        let Some(next) = date.next_day() else { break };
+        date = next;
+    }
+    filled.extend(rows);
+    filled
+}
+
+/// Handling of an entry of the same task and date that already exists
+#[derive(Debug, Clone, Copy)]
+enum Upsert {
+    /// Add the duration to the existing entry, keeping the earliest start time
+    Add,
+    /// Replace the duration, and the start time and offset if the new entry has them
+    Replace,
+}
+
+/// Duration of an entry after adding to or replacing the existing duration, also when it's
+/// negative or too long to store. Computed wider than the stored `i32` so that sums can't overflow.
+fn upserted_seconds(existing: Option<i32>, seconds: i32, upsert: Upsert) -> i64 {
+    match upsert {
+        Upsert::Add => existing.unwrap_or(0) as i64 + seconds as i64,
+        Upsert::Replace => seconds as i64,
+    }
+}
+
+/// Insert or update the entry, checking its date and the resulting day total of the project against
+/// the limits
+fn new_log(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    entry: DbNewEntry,
+    upsert: Upsert,
+    limits: &Limits,
+) -> Result<()> {
+    limits.check_date(entry.date)?;
+    let existing = log_entries::table
+        .find((entry.date, entry.task_id.0))
+        .select(log_entries::duration_seconds)
+        .first::<i32>(conn)
+        .optional()?;
+    let total = upserted_seconds(existing, entry.duration_seconds, upsert);
+    if !(0..=i32::MAX as i64).contains(&total) {
+        bail!(WlogError::Validation(format!(
+            "Entry of task {} on {} would be {}, entries can't be negative or this long",
+            entry.task_id.0,
+            entry.date,
+            fmt_duration(Duration::seconds(total))
+        )));
+    }
+    let day_total = get_day_total(conn, project, entry.date)?
+        + Duration::seconds(total - existing.unwrap_or(0) as i64);
+    limits.check_day_total(entry.date, day_total)?;
+
+    type StartTime = diesel::sql_types::Nullable<diesel::sql_types::Time>;
+    type Source = diesel::sql_types::Nullable<diesel::sql_types::Text>;
+    type UtcOffsetSeconds = diesel::sql_types::Nullable<diesel::sql_types::Integer>;
+    let query = diesel::insert_into(log_entries::table)
         .values(entry)
         .on_conflict((log_entries::date, log_entries::task_id))
-        .do_update()
-        .set(
-            log_entries::duration_minutes
-                .eq(log_entries::duration_minutes + excluded(log_entries::duration_minutes)),
-        )
-        .execute(conn)?;
+        .do_update();
+    match upsert {
+        Upsert::Add => query
+            .set((
+                log_entries::duration_seconds
+                    .eq(log_entries::duration_seconds + excluded(log_entries::duration_seconds)),
+                log_entries::utc_offset_seconds.eq(excluded(log_entries::utc_offset_seconds)),
+                log_entries::start_time.eq(diesel::dsl::sql::<StartTime>(
+                    "coalesce(min(start_time, excluded.start_time), start_time, excluded.start_time)",
+                )),
+                log_entries::source.eq(diesel::dsl::sql::<Source>(
+                    "coalesce(source, excluded.source)",
+                )),
+            ))
+            .execute(conn)?,
+        Upsert::Replace => query
+            .set((
+                log_entries::duration_seconds.eq(excluded(log_entries::duration_seconds)),
+                log_entries::utc_offset_seconds.eq(diesel::dsl::sql::<UtcOffsetSeconds>(
+                    "coalesce(excluded.utc_offset_seconds, utc_offset_seconds)",
+                )),
+                log_entries::start_time.eq(diesel::dsl::sql::<StartTime>(
+                    "coalesce(excluded.start_time, start_time)",
+                )),
+                log_entries::source.eq(excluded(log_entries::source)),
+            ))
+            .execute(conn)?,
+    };
     Ok(())
 }
 
@@ -236,7 +1573,7 @@ fn new_log(conn: &mut SqliteConnection, entry: DbNewEntry) -> Result<()> {
 struct DbLogEntry {
     date: time::Date,
     task_id: TaskId,
-    duration_minutes: i32,
+    duration_seconds: i32,
 }
 
 #[derive(Insertable)]
@@ -245,16 +1582,30 @@ struct DbLogEntry {
 struct DbNewEntry {
     date: time::Date,
     task_id: TaskId,
-    duration_minutes: i32,
+    duration_seconds: i32,
+    utc_offset_seconds: Option<i32>,
+    start_time: Option<Time>,
+    source: Option<String>,
 }
 
-impl From<LogEntry> for DbNewEntry {
-    fn from(value: LogEntry) -> Self {
-        DbNewEntry {
+impl TryFrom<LogEntry> for DbNewEntry {
+    type Error = eyre::Error;
+
+    fn try_from(value: LogEntry) -> Result<Self> {
+        let duration_seconds = i32::try_from(value.duration.whole_seconds()).map_err(|_| {
+            WlogError::Validation(format!(
+                "Duration {} is too long to log",
+                fmt_duration(value.duration)
+            ))
+        })?;
+        Ok(DbNewEntry {
             date: value.date,
             task_id: value.task,
-            duration_minutes: value.duration.whole_minutes() as i32,
-        }
+            duration_seconds,
+            utc_offset_seconds: value.utc_offset.map(UtcOffset::whole_seconds),
+            start_time: value.start,
+            source: Some(value.source.to_string()),
+        })
     }
 }
 
@@ -263,7 +1614,10 @@ impl From<DbLogEntry> for LogEntry {
         LogEntry {
             date: value.date,
             task: value.task_id,
-            duration: Duration::minutes(value.duration_minutes as i64),
+            duration: Duration::seconds(value.duration_seconds as i64),
+            utc_offset: None,
+            start: None,
+            source: EntrySource::Cli,
         }
     }
 }
@@ -275,7 +1629,298 @@ impl From<(DbLogEntry, Task)> for LogEntryExpanded {
             task_name: task.name,
             issue_number: task.issue,
             date: log.date,
-            duration: Duration::minutes(log.duration_minutes as i64),
+            duration: Duration::seconds(log.duration_seconds as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data, projects};
+    use proptest::prelude::*;
+
+    fn day() -> Date {
+        Date::from_calendar_date(2026, time::Month::October, 16).unwrap()
+    }
+
+    fn entry(task: TaskId, hours: i64) -> DbNewEntry {
+        LogEntry {
+            date: day(),
+            task,
+            duration: Duration::hours(hours),
+            utc_offset: None,
+            start: None,
+            source: EntrySource::Cli,
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    #[test]
+    fn history_of_removed_tasks() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Removed", None).unwrap();
+        let limits = Limits::unlimited();
+        new_log(&mut conn, project, entry(task, 1), Upsert::Add, &limits).unwrap();
+        new_log(&mut conn, project, entry(task, 2), Upsert::Replace, &limits).unwrap();
+        diesel::delete(tasks::table.find(task.0))
+            .execute(&mut conn)
+            .unwrap();
+
+        let rows = get_history(&mut conn, project, day())
+            .unwrap()
+            .into_iter()
+            .map(|(task, seconds, _, replaced)| {
+                (task, seconds, replaced.map(|(_, removed)| removed))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                ("Removed".to_string(), 3600, Some(false)),
+                ("Removed".to_string(), 7200, Some(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rigid_schedule() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Task", None).unwrap();
+        let limits = Limits::unlimited();
+        let set_schedule = |conn: &mut SqliteConnection, flexible| {
+            let weekdays = [
+                time::Weekday::Monday,
+                time::Weekday::Tuesday,
+                time::Weekday::Wednesday,
+                time::Weekday::Thursday,
+                time::Weekday::Friday,
+            ];
+            let schedule = schedule::WeekBasedSchedule::new(&weekdays, flexible);
+            schedule::set(conn, project, schedule, None, Duration::hours(8), None).unwrap();
+        };
+        let log = |conn: &mut SqliteConnection, date: Date| {
+            let entry = LogEntry {
+                date,
+                task,
+                duration: Duration::hours(1),
+                utc_offset: None,
+                start: None,
+                source: EntrySource::Cli,
+            };
+            add_log(conn, project, entry, &limits)
+        };
+        let saturday = day().next_day().unwrap();
+        let next_month = Date::from_calendar_date(2026, time::Month::November, 7).unwrap();
+
+        set_schedule(&mut conn, true);
+        log(&mut conn, saturday).unwrap();
+
+        set_schedule(&mut conn, false);
+        log(&mut conn, day()).unwrap();
+        let err = log(&mut conn, next_month).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WlogError>(),
+            Some(WlogError::Validation(_))
+        ));
+        // The schedule recorded for the month applies over the current one
+        diesel::update(crate::schema::schedule_logs::table)
+            .set(crate::schema::schedule_logs::bitmap.eq(0))
+            .execute(&mut conn)
+            .unwrap();
+        log(&mut conn, saturday).unwrap();
+    }
+
+    #[test]
+    fn filter_wildcards() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let limits = Limits::unlimited();
+        for (name, source) in [
+            ("Sprint_1", EntrySource::Import("harvest")),
+            ("Sprint 1", EntrySource::Cli),
+        ] {
+            let task = crate::tasks::create(&mut conn, project, name, None).unwrap();
+            let entry = LogEntry {
+                date: day(),
+                task,
+                duration: Duration::hours(1),
+                utc_offset: None,
+                start: None,
+                source,
+            };
+            add_log(&mut conn, project, entry, &limits).unwrap();
+        }
+
+        let mut names = |task: Option<&str>, source: Option<&str>| {
+            let filter = EntryFilter {
+                task: task.map(|name| TaskFilter::Name(name.to_string())),
+                source: source.map(str::to_string),
+                changed_since: None,
+            };
+            get_by_day_expanded(&mut conn, project, None, Some(&filter))
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.task_name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(Some("t_1"), None), ["Sprint_1"]);
+        assert_eq!(names(None, Some("import")), ["Sprint_1"]);
+        assert!(names(None, Some("imp_rt")).is_empty());
+        assert!(names(None, Some("%")).is_empty());
+    }
+
+    #[test]
+    fn daily_maximum() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let first = crate::tasks::create(&mut conn, project, "First", None).unwrap();
+        let second = crate::tasks::create(&mut conn, project, "Second", None).unwrap();
+        let limits = Limits {
+            max_daily: Duration::hours(10),
+            locked_before: None,
+            force: false,
+        };
+
+        new_log(&mut conn, project, entry(first, 6), Upsert::Add, &limits).unwrap();
+        let err = new_log(&mut conn, project, entry(second, 5), Upsert::Add, &limits).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<WlogError>(),
+            Some(WlogError::Validation(_))
+        ));
+        // Replaced durations don't count towards the total
+        new_log(
+            &mut conn,
+            project,
+            entry(first, 10),
+            Upsert::Replace,
+            &limits,
+        )
+        .unwrap();
+        let forced = Limits {
+            force: true,
+            ..limits
+        };
+        new_log(&mut conn, project, entry(second, 1), Upsert::Add, &forced).unwrap();
+        assert_eq!(
+            get_day_total(&mut conn, project, day()).unwrap(),
+            Duration::hours(11)
+        );
+    }
+
+    #[test]
+    fn moved_entries_keep_start() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let other = projects::get_or_create_by_url(&mut conn, "https://example.org")
+            .unwrap()
+            .id;
+        let from = crate::tasks::create(&mut conn, project, "From", None).unwrap();
+        let to = crate::tasks::create(&mut conn, project, "To", None).unwrap();
+        let foreign = crate::tasks::create(&mut conn, other, "Foreign", None).unwrap();
+        let limits = Limits::unlimited();
+        let start = Time::from_hms(9, 30, 0).unwrap();
+        let started = DbNewEntry {
+            utc_offset_seconds: Some(3600),
+            start_time: Some(start),
+            ..entry(from, 2)
+        };
+        new_log(&mut conn, project, started, Upsert::Add, &limits).unwrap();
+        new_log(&mut conn, project, entry(to, 1), Upsert::Add, &limits).unwrap();
+
+        let period = Period::until(Date::MAX);
+        assert!(move_entries(&mut conn, from, foreign, &period, &limits).is_err());
+        assert_eq!(
+            move_entries(&mut conn, from, to, &period, &limits).unwrap(),
+            vec![day()]
+        );
+        let moved = log_entries::table
+            .find((day(), to.0))
+            .select((
+                log_entries::duration_seconds,
+                log_entries::utc_offset_seconds,
+                log_entries::start_time,
+            ))
+            .first::<(i32, Option<i32>, Option<Time>)>(&mut conn)
+            .unwrap();
+        assert_eq!(moved, (3 * 3600, Some(3600), Some(start)));
+    }
+
+    #[test]
+    fn split_entries_keep_metadata() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Task", None).unwrap();
+        let first = crate::tasks::create(&mut conn, project, "First", None).unwrap();
+        let second = crate::tasks::create(&mut conn, project, "Second", None).unwrap();
+        let limits = Limits::unlimited();
+        let start = Time::from_hms(9, 30, 0).unwrap();
+        let started = DbNewEntry {
+            utc_offset_seconds: Some(3600),
+            start_time: Some(start),
+            source: Some("import:harvest".to_string()),
+            ..entry(task, 3)
+        };
+        new_log(&mut conn, project, started, Upsert::Add, &limits).unwrap();
+
+        let pieces = [(Duration::hours(1), first), (Duration::hours(1), second)];
+        let err = split_entry(&mut conn, project, day(), task, &pieces, false, &limits);
+        assert!(err.unwrap_err().to_string().contains("--uneven"));
+        split_entry(&mut conn, project, day(), task, &pieces, true, &limits).unwrap();
+        let pieces = log_entries::table
+            .filter(log_entries::date.eq(day()))
+            .order_by(log_entries::task_id)
+            .select((
+                log_entries::task_id,
+                log_entries::utc_offset_seconds,
+                log_entries::start_time,
+                log_entries::source,
+            ))
+            .load::<(TaskId, Option<i32>, Option<Time>, Option<String>)>(&mut conn)
+            .unwrap();
+        let source = Some("import:harvest".to_string());
+        assert_eq!(
+            pieces,
+            [
+                (first, Some(3600), Some(start), source.clone()),
+                (second, Some(3600), Some(start + Duration::hours(1)), source),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_pieces_start_before_midnight() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Task", None).unwrap();
+        let first = crate::tasks::create(&mut conn, project, "First", None).unwrap();
+        let second = crate::tasks::create(&mut conn, project, "Second", None).unwrap();
+        let limits = Limits::unlimited();
+        let late = DbNewEntry {
+            start_time: Some(Time::from_hms(22, 0, 0).unwrap()),
+            ..entry(task, 4)
+        };
+        new_log(&mut conn, project, late, Upsert::Add, &limits).unwrap();
+
+        let pieces = [(Duration::hours(2), first), (Duration::hours(2), second)];
+        split_entry(&mut conn, project, day(), task, &pieces, false, &limits).unwrap();
+        let starts = log_entries::table
+            .filter(log_entries::date.eq(day()))
+            .order_by(log_entries::task_id)
+            .select(log_entries::start_time)
+            .load::<Option<Time>>(&mut conn)
+            .unwrap();
+        assert_eq!(starts, [Some(Time::from_hms(22, 0, 0).unwrap()), None]);
+    }
+
+    proptest! {
+        #[test]
+        fn long_durations_are_rejected(hours in (i32::MAX as i64 / 3600 + 1)..i64::MAX / 3600) {
+            let entry = LogEntry {
+                date: Date::MIN,
+                task: TaskId(1),
+                duration: Duration::hours(hours),
+                utc_offset: None,
+                start: None,
+                source: EntrySource::Cli,
+            };
+            prop_assert!(DbNewEntry::try_from(entry).is_err());
         }
     }
 }
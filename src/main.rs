@@ -1,24 +1,52 @@
 use clap::Parser;
 use owo_colors::OwoColorize;
 
+mod balance;
+mod caps;
+mod check;
 mod cli;
+mod clients;
 mod comments;
 mod config;
+mod credentials;
+#[cfg(unix)]
+mod daemon;
 mod data;
+mod error;
+mod event_log;
+mod export;
+mod github_comments;
+mod hooks;
+mod import;
+mod invoices;
+mod locale;
 mod log_entries;
+mod merge_report;
+#[cfg(unix)]
+mod pager;
+mod parsers;
 mod projects;
+mod reminder;
+mod remote_issues;
+mod report;
 mod schedule;
 mod schema;
+mod sprint;
+mod streak;
+mod sync;
 mod tasks;
 mod utils;
+mod webhook;
 
 use cli::Cli;
 use config::Config;
 
 fn main() {
-    let result = Cli::parse().dispatch();
+    // Loaded before parsing, which depends on the configured duration syntaxes
+    let config = Cli::load_config();
+    let result = Cli::parse().dispatch(config);
     if let Err(e) = result {
         eprintln!("{} {e}", "Error:".red().bold());
-        std::process::exit(1);
+        std::process::exit(error::exit_code(&e));
     }
 }
@@ -0,0 +1,118 @@
+use crate::Config;
+use crate::data;
+use crate::log_entries::Period;
+use crate::projects::Project;
+use crate::schema::{log_entries, projects, tasks};
+use crate::utils::OutputFormat;
+use diesel::prelude::*;
+use eyre::Result;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use time::{Date, Duration};
+
+/// Database of one person
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub person: String,
+    pub path: PathBuf,
+}
+
+/// Time of a person is summed per issue, and per task name for tasks without an issue
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Work {
+    Issue(i32),
+    Task(String),
+}
+
+/// Print time per person and issue summed over all databases. Projects of different databases are
+/// matched by URL. The databases are only read, so they can belong to other running instances.
+pub fn show(
+    config: &Config,
+    sources: &[Source],
+    period: Option<&Period>,
+    format: OutputFormat,
+) -> Result<()> {
+    let period = period.copied().unwrap_or(Period {
+        from: Date::MIN,
+        to: Date::MAX,
+    });
+    // Person, project URL, issue or task -> task name, duration
+    let mut totals = BTreeMap::<(&str, String, Work), (String, Duration)>::new();
+    // Project URL -> project as found first, to format its issues
+    let mut projects = BTreeMap::<String, Project>::new();
+    for source in sources {
+        let mut conn = data::open_read_only(&source.path)?;
+        let rows = log_entries::table
+            .inner_join(tasks::table.inner_join(projects::table))
+            .filter(log_entries::date.between(period.from, period.to))
+            .select((
+                Project::as_select(),
+                tasks::issue,
+                tasks::name,
+                log_entries::duration_seconds,
+            ))
+            .load::<(Project, Option<i32>, String, i32)>(&mut conn)?;
+        for (project, issue, name, seconds) in rows {
+            let url = project.url.clone();
+            projects.entry(url.clone()).or_insert(project);
+            let work = match issue {
+                Some(issue) => Work::Issue(issue),
+                None => Work::Task(name.clone()),
+            };
+            let total = totals
+                .entry((source.person.as_str(), url, work))
+                .or_insert_with(|| (name, Duration::ZERO));
+            total.1 += Duration::seconds(seconds as i64);
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut person_total = Duration::ZERO;
+    let mut total = Duration::ZERO;
+    let mut totals = totals.into_iter().peekable();
+    while let Some(((person, url, work), (name, duration))) = totals.next() {
+        person_total += duration;
+        total += duration;
+        let issue = match work {
+            Work::Issue(issue) => format.issue(issue, &projects[&url]),
+            Work::Task(_) => String::new(),
+        };
+        rows.push([
+            person.to_string(),
+            url,
+            issue,
+            name,
+            config.fmt_duration(duration),
+        ]);
+        let last_of_person = totals
+            .peek()
+            .is_none_or(|((next, _, _), _)| *next != person);
+        if last_of_person && format.has_totals() {
+            rows.push([
+                format.strong(person),
+                String::new(),
+                String::new(),
+                String::new(),
+                format.strong(&config.fmt_duration(person_total)),
+            ]);
+        }
+        if last_of_person {
+            person_total = Duration::ZERO;
+        }
+    }
+    if format.has_totals() {
+        rows.push([
+            format.strong("Total"),
+            String::new(),
+            String::new(),
+            String::new(),
+            format.strong(&config.fmt_duration(total)),
+        ]);
+    }
+
+    format.print_table(
+        config.table_style(),
+        &["Person", "Project", "Issue", "Task", "Duration"],
+        rows,
+    )
+}
@@ -0,0 +1,129 @@
+use console::Term;
+use owo_colors::OwoColorize;
+use std::io::{IsTerminal, Read, Write};
+use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::process::{Command, Stdio};
+use std::thread::JoinHandle;
+
+/// Collects everything printed to stdout while alive. When dropped, the output is shown in
+/// `$PAGER`, or `less -R` if it's unset, if it doesn't fit the terminal, and printed as is
+/// otherwise. Messages printed to stderr in the terminal are collected along with it, so that
+/// they keep their place, e.g. totals below a table.
+pub struct Pager {
+    stdout: OwnedFd,
+    /// Original stderr, if it was redirected to the pager as well
+    stderr: Option<OwnedFd>,
+    reader: Option<JoinHandle<std::io::Result<Vec<u8>>>>,
+}
+
+/// Start collecting output, unless stdout isn't a terminal and paging makes no sense
+pub fn start() -> eyre::Result<Option<Pager>> {
+    let stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        return Ok(None);
+    }
+    stdout.lock().flush()?;
+    // Tables are wrapped to the terminal, which stdout no longer refers to once redirected
+    if let Some((_, width)) = Term::stdout().size_checked() {
+        crate::config::set_terminal_width(width);
+    }
+    let saved = stdout.as_fd().try_clone_to_owned()?;
+    let stderr = std::io::stderr();
+    let saved_stderr = match stderr.is_terminal() {
+        true => Some(stderr.as_fd().try_clone_to_owned()?),
+        false => None,
+    };
+    let (mut reader, writer) = std::io::pipe()?;
+    // SAFETY: both descriptors are open, stdout is flushed and now refers to the pipe
+    if unsafe { libc::dup2(writer.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    // SAFETY: both descriptors are open, stderr is unbuffered
+    if saved_stderr.is_some() && unsafe { libc::dup2(writer.as_raw_fd(), libc::STDERR_FILENO) } < 0
+    {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    drop(writer);
+    // Reading in a thread keeps large outputs from filling the pipe and blocking the writer
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output)?;
+        Ok(output)
+    });
+    Ok(Some(Pager {
+        stdout: saved,
+        stderr: saved_stderr,
+        reader: Some(reader),
+    }))
+}
+
+impl Pager {
+    fn finish(&mut self) -> std::io::Result<()> {
+        std::io::stdout().lock().flush()?;
+        // Restoring stdout closes the last write end of the pipe and ends the reader
+        // SAFETY: both descriptors are open and stdout is flushed
+        if unsafe { libc::dup2(self.stdout.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if let Some(stderr) = &self.stderr
+            // SAFETY: both descriptors are open, stderr is unbuffered
+            && unsafe { libc::dup2(stderr.as_raw_fd(), libc::STDERR_FILENO) } < 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+        let Some(reader) = self.reader.take() else {
+            return Ok(());
+        };
+        let output = reader
+            .join()
+            .map_err(|_| std::io::Error::other("Output reader panicked"))??;
+
+        let lines = output.iter().filter(|b| **b == b'\n').count();
+        let fits = Term::stdout()
+            .size_checked()
+            .is_none_or(|(height, _)| lines < height as usize);
+        if fits || !page(&output)? {
+            std::io::stdout().lock().write_all(&output)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            eprintln!("{} Unable to show output: {e}", "Warning:".yellow().bold());
+        }
+    }
+}
+
+/// Show the output in the pager. Returns false if there's no pager to run.
+fn page(output: &[u8]) -> std::io::Result<bool> {
+    let pager = std::env::var("PAGER").unwrap_or_default();
+    let mut args = pager.split_whitespace();
+    let mut command = match args.next() {
+        Some(program) => {
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        None => {
+            // Passes color escape sequences through
+            let mut command = Command::new("less");
+            command.arg("-R");
+            command
+        }
+    };
+    let Ok(mut child) = command.stdin(Stdio::piped()).spawn() else {
+        return Ok(false);
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when quit before reaching the end
+        match stdin.write_all(output) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(true)
+}
@@ -0,0 +1,450 @@
+use eyre::{Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use time::Duration;
+
+/// Grammar of durations given on the command line
+pub trait DurationParser {
+    /// Input written in the grammar, shown when suggesting to enable it
+    fn example(&self) -> &'static str;
+
+    /// Whether the input is meant to be in this grammar, regardless of whether it's valid
+    fn recognizes(&self, input: &str) -> bool;
+
+    /// Duration of the input. The length of a day is only determined if the input has days.
+    fn parse(&self, input: &str, day: &dyn Fn() -> Duration) -> Result<Duration>;
+}
+
+/// Grammars of durations that can be enabled in addition to units like `1h30m`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationSyntax {
+    /// Hours and minutes separated by a colon, e.g. 1:30
+    Clock,
+    /// Minutes followed by an apostrophe, e.g. 90'
+    Minutes,
+    /// ISO 8601 durations, e.g. PT1H30M
+    Iso8601,
+}
+
+impl DurationSyntax {
+    pub const ALL: [Self; 3] = [Self::Clock, Self::Minutes, Self::Iso8601];
+
+    pub fn parser(self) -> &'static dyn DurationParser {
+        match self {
+            DurationSyntax::Clock => &Clock,
+            DurationSyntax::Minutes => &Minutes,
+            DurationSyntax::Iso8601 => &Iso8601,
+        }
+    }
+}
+
+impl std::fmt::Display for DurationSyntax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationSyntax::Clock => f.write_str("clock"),
+            DurationSyntax::Minutes => f.write_str("minutes"),
+            DurationSyntax::Iso8601 => f.write_str("iso8601"),
+        }
+    }
+}
+
+/// Parse the input with the enabled grammar it's written in, or as units like `1h30m`. Enabled
+/// grammars are only determined if the input isn't in units.
+pub fn parse_duration(
+    input: &str,
+    enabled: impl FnOnce() -> Vec<DurationSyntax>,
+    day: impl Fn() -> Duration,
+) -> Result<Duration> {
+    if !Units.recognizes(input)
+        && let Some(syntax) = enabled().into_iter().find(|s| s.parser().recognizes(input))
+    {
+        return syntax.parser().parse(input, &day);
+    }
+    Units.parse(input, &day).map_err(|error| {
+        match DurationSyntax::ALL
+            .into_iter()
+            .find(|s| s.parser().recognizes(input))
+        {
+            Some(syntax) => anyhow!(
+                "{error}. Durations like {} are accepted once {syntax} is added to \
+                 `wlog config duration-syntaxes`",
+                syntax.parser().example()
+            ),
+            None => error,
+        }
+    })
+}
+
+/// Numbers followed by units, with weeks of five days of the configured length, e.g. `1w2d` or
+/// `1h30m`. The unit after a number defaults to the next smaller one after hours or days, and to
+/// hours without any.
+struct Units;
+
+impl DurationParser for Units {
+    fn example(&self) -> &'static str {
+        "1h30m"
+    }
+
+    fn recognizes(&self, input: &str) -> bool {
+        input
+            .chars()
+            .all(|c| c.is_ascii_digit() || "wdhms".contains(c))
+    }
+
+    fn parse(&self, input: &str, day: &dyn Fn() -> Duration) -> Result<Duration> {
+        let mut unit = 60 * 60;
+        let mut result = None;
+        let mut number = None;
+        for c in input.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                number = Some(push_digit(input, number, digit)?);
+                continue;
+            }
+            let (scale, next) = match c {
+                'w' => (checked_mul(input, 5, day().whole_seconds())?, 0),
+                'd' => (day().whole_seconds(), 60 * 60),
+                'h' => (60 * 60, 60),
+                'm' => (60, 0),
+                's' => (1, 0),
+                unexpected => bail!("Unexpected character in duration: '{unexpected}'"),
+            };
+            let acc = number.ok_or_else(|| anyhow!("Number expected before unit"))?;
+            result = Some(checked_add(
+                input,
+                result.unwrap_or(0),
+                checked_mul(input, acc, scale)?,
+            )?);
+            number = None;
+            unit = next;
+        }
+        if let Some(number) = number
+            && unit == 0
+        {
+            bail!("Unable to parse duration, unknown unit for value {number}",);
+        }
+        let seconds = match (result, number) {
+            (Some(r), Some(n)) => checked_add(input, r, checked_mul(input, n, unit)?)?,
+            (Some(r), None) => r,
+            (None, Some(n)) => checked_mul(input, n, unit)?,
+            (None, None) => bail!("Number expected"),
+        };
+
+        Ok(Duration::seconds(seconds))
+    }
+}
+
+/// Hours and minutes as on a clock, optionally with seconds, e.g. `1:30` or `0:45:30`
+struct Clock;
+
+impl DurationParser for Clock {
+    fn example(&self) -> &'static str {
+        "1:30"
+    }
+
+    fn recognizes(&self, input: &str) -> bool {
+        input.contains(':')
+    }
+
+    fn parse(&self, input: &str, _day: &dyn Fn() -> Duration) -> Result<Duration> {
+        let mut parts = input.split(':');
+        let hours = parts.next().unwrap_or_default();
+        if hours.is_empty() || !hours.chars().all(|c| c.is_ascii_digit()) {
+            bail!("Hours expected before ':' in \"{input}\"");
+        }
+        let hours = hours.parse::<i64>().map_err(|_| too_long(input))?;
+        let mut seconds = checked_mul(input, hours, 60 * 60)?;
+        let mut unit = 60;
+        for part in parts {
+            if unit == 0 {
+                bail!("Too many ':' in \"{input}\", expected h:mm or h:mm:ss");
+            }
+            if part.len() != 2 || !part.chars().all(|c| c.is_ascii_digit()) {
+                bail!("Two digits expected after ':' in \"{input}\"");
+            }
+            let value = part.parse::<i64>()?;
+            if value >= 60 {
+                bail!("Minutes and seconds must be below 60 in \"{input}\"");
+            }
+            seconds = checked_add(input, seconds, value * unit)?;
+            unit /= 60;
+        }
+        Ok(Duration::seconds(seconds))
+    }
+}
+
+/// Minutes marked with an apostrophe, e.g. `90'`
+struct Minutes;
+
+impl DurationParser for Minutes {
+    fn example(&self) -> &'static str {
+        "90'"
+    }
+
+    fn recognizes(&self, input: &str) -> bool {
+        input.ends_with('\'')
+    }
+
+    fn parse(&self, input: &str, _day: &dyn Fn() -> Duration) -> Result<Duration> {
+        let minutes = input.strip_suffix('\'').unwrap_or(input);
+        if minutes.is_empty() || !minutes.chars().all(|c| c.is_ascii_digit()) {
+            bail!("Minutes expected before the apostrophe in \"{input}\"");
+        }
+        let minutes = minutes.parse::<i64>().map_err(|_| too_long(input))?;
+        Ok(Duration::seconds(checked_mul(input, minutes, 60)?))
+    }
+}
+
+/// ISO 8601 durations of whole weeks, days, hours, minutes and seconds, e.g. `PT1H30M` or `P1DT4H`.
+/// Days have the configured length and weeks five days, like in units. Years and months have no
+/// fixed length and aren't supported.
+struct Iso8601;
+
+impl DurationParser for Iso8601 {
+    fn example(&self) -> &'static str {
+        "PT1H30M"
+    }
+
+    fn recognizes(&self, input: &str) -> bool {
+        input.starts_with(['P', 'p'])
+    }
+
+    fn parse(&self, input: &str, day: &dyn Fn() -> Duration) -> Result<Duration> {
+        let invalid = || anyhow!("Invalid ISO 8601 duration \"{input}\", expected e.g. PT1H30M");
+        let upper = input.to_ascii_uppercase();
+        let rest = upper.strip_prefix('P').ok_or_else(invalid)?;
+        let (date, time) = match rest.split_once('T') {
+            Some((_, "")) => return Err(invalid()),
+            Some((date, time)) => (date, time),
+            None => (rest, ""),
+        };
+        if date.is_empty() && time.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut seconds = 0;
+        for (part, units, is_date) in [(date, "WD", true), (time, "HMS", false)] {
+            let mut units = units.chars();
+            let mut number = None::<i64>;
+            for c in part.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    number = Some(push_digit(input, number, digit)?);
+                    continue;
+                }
+                if c == 'Y' || (c == 'M' && is_date) {
+                    bail!("Years and months in \"{input}\" have no fixed length");
+                }
+                let value = number.take().ok_or_else(invalid)?;
+                // Units are in order and appear at most once
+                if !units.by_ref().any(|unit| unit == c) {
+                    return Err(invalid());
+                }
+                let scale = match c {
+                    'W' => checked_mul(input, 5, day().whole_seconds())?,
+                    'D' => day().whole_seconds(),
+                    'H' => 60 * 60,
+                    'M' => 60,
+                    _ => 1,
+                };
+                seconds = checked_add(input, seconds, checked_mul(input, value, scale)?)?;
+            }
+            if number.is_some() {
+                return Err(invalid());
+            }
+        }
+        Ok(Duration::seconds(seconds))
+    }
+}
+
+fn too_long(input: &str) -> eyre::Report {
+    anyhow!("Duration \"{input}\" is too long")
+}
+
+/// Number with the digit appended, failing instead of overflowing
+fn push_digit(input: &str, number: Option<i64>, digit: u32) -> Result<i64> {
+    number
+        .unwrap_or(0)
+        .checked_mul(10)
+        .and_then(|n| n.checked_add(digit as i64))
+        .ok_or_else(|| too_long(input))
+}
+
+fn checked_mul(input: &str, a: i64, b: i64) -> Result<i64> {
+    a.checked_mul(b).ok_or_else(|| too_long(input))
+}
+
+fn checked_add(input: &str, a: i64, b: i64) -> Result<i64> {
+    a.checked_add(b).ok_or_else(|| too_long(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parser: &dyn DurationParser, input: &str) -> Option<Duration> {
+        parser.parse(input, &|| Duration::hours(6)).ok()
+    }
+
+    #[test]
+    fn units() {
+        let data = [
+            ("1", Some(60 * 60)),
+            ("8h30", Some((8 * 60 + 30) * 60)),
+            ("90m", Some(90 * 60)),
+            ("1m30s", Some(90)),
+            ("1h0m5s", Some(60 * 60 + 5)),
+            ("0", Some(0)),
+            ("1d", Some(6 * 60 * 60)),
+            ("1d2", Some(8 * 60 * 60)),
+            ("1d30m", Some((6 * 60 + 30) * 60)),
+            ("1w", Some(30 * 60 * 60)),
+            ("1w1d", Some(36 * 60 * 60)),
+            ("1m30", None),
+            ("1w2", None),
+            ("d", None),
+            ("hm", None),
+            ("10a", None),
+            ("", None),
+            ("99999999999999999999", None),
+            ("9999999999999999h", None),
+            ("2562047788015215w", None),
+            ("9223372036854775807s1s", None),
+        ];
+        for (input, seconds) in data {
+            assert_eq!(
+                parse(&Units, input),
+                seconds.map(Duration::seconds),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn clock() {
+        let data = [
+            ("1:30", Some((60 + 30) * 60)),
+            ("0:45", Some(45 * 60)),
+            ("10:05", Some((10 * 60 + 5) * 60)),
+            ("0:00", Some(0)),
+            ("1:30:15", Some((60 + 30) * 60 + 15)),
+            ("25:00", Some(25 * 60 * 60)),
+            ("1:5", None),
+            ("1:60", None),
+            ("1:30:60", None),
+            ("1:30:15:00", None),
+            (":30", None),
+            ("1:", None),
+            ("1h:30", None),
+            ("-1:30", None),
+            ("1:3a", None),
+            ("99999999999999999:00", None),
+            ("2562047788015215:59:59", None),
+            ("99999999999999999999:00", None),
+        ];
+        for (input, seconds) in data {
+            assert_eq!(
+                parse(&Clock, input),
+                seconds.map(Duration::seconds),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn minutes() {
+        let data = [
+            ("90'", Some(90)),
+            ("5'", Some(5)),
+            ("0'", Some(0)),
+            ("480'", Some(480)),
+            ("'", None),
+            ("1.5'", None),
+            ("90''", None),
+            ("1h'", None),
+            ("-5'", None),
+            ("999999999999999999'", None),
+            ("99999999999999999999'", None),
+        ];
+        for (input, minutes) in data {
+            assert_eq!(
+                parse(&Minutes, input),
+                minutes.map(Duration::minutes),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn iso8601() {
+        let data = [
+            ("PT1H30M", Some((60 + 30) * 60)),
+            ("PT90M", Some(90 * 60)),
+            ("PT45S", Some(45)),
+            ("PT1H0M5S", Some(60 * 60 + 5)),
+            ("PT2H", Some(2 * 60 * 60)),
+            ("pt1h30m", Some((60 + 30) * 60)),
+            ("P1D", Some(6 * 60 * 60)),
+            ("P1DT2H", Some(8 * 60 * 60)),
+            ("P1W", Some(30 * 60 * 60)),
+            ("P1W1D", Some(36 * 60 * 60)),
+            ("PT0S", Some(0)),
+            ("P", None),
+            ("PT", None),
+            ("P1DT", None),
+            ("PT1H30", None),
+            ("P1H", None),
+            ("PT1D", None),
+            ("PT30M1H", None),
+            ("PT1H1H", None),
+            ("P1M", None),
+            ("P1Y", None),
+            ("PT1.5H", None),
+            ("PTH", None),
+            ("1H30M", None),
+            ("P99999999999999999W", None),
+            ("PT99999999999999999999S", None),
+            ("PT2562047788015215H59M", None),
+        ];
+        for (input, seconds) in data {
+            assert_eq!(
+                parse(&Iso8601, input),
+                seconds.map(Duration::seconds),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn enabled_syntaxes() {
+        let day = || Duration::hours(8);
+        let all = || DurationSyntax::ALL.to_vec();
+        let none = Vec::new;
+
+        assert_eq!(
+            parse_duration("1h30m", none, day).ok(),
+            Some(Duration::minutes(90))
+        );
+        assert_eq!(
+            parse_duration("1:30", all, day).ok(),
+            Some(Duration::minutes(90))
+        );
+        assert_eq!(
+            parse_duration("90'", all, day).ok(),
+            Some(Duration::minutes(90))
+        );
+        assert_eq!(
+            parse_duration("PT1H30M", all, day).ok(),
+            Some(Duration::minutes(90))
+        );
+        for input in ["1:30", "90'", "PT1H30M"] {
+            let error = parse_duration(input, none, day).unwrap_err().to_string();
+            assert!(
+                error.contains("wlog config duration-syntaxes"),
+                "{input}: {error}"
+            );
+        }
+        assert!(parse_duration("1:30", || vec![DurationSyntax::Minutes], day).is_err());
+        // Input of an enabled grammar fails with its own error
+        let error = parse_duration("1:75", all, day).unwrap_err().to_string();
+        assert!(error.contains("below 60"), "{error}");
+    }
+}
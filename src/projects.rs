@@ -1,5 +1,8 @@
+use crate::Config;
+use crate::error::WlogError;
 use crate::schema::{default_project, projects};
-use crate::utils::{prompt, prompt_opt, yn_prompt};
+use crate::utils::{History, OutputFormat, prompt, prompt_opt, yn_prompt};
+use crate::{log_entries, tasks};
 use diesel::deserialize::{FromSql, FromSqlRow};
 use diesel::expression::AsExpression;
 use diesel::prelude::*;
@@ -8,7 +11,7 @@ use diesel::sqlite::Sqlite;
 use eyre::{Result, bail};
 use owo_colors::OwoColorize;
 
-#[derive(Debug, Copy, Clone, AsExpression, FromSqlRow)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, AsExpression, FromSqlRow)]
 #[diesel(sql_type = diesel::sql_types::Integer)]
 pub struct ProjectId(pub i32);
 
@@ -19,21 +22,227 @@ pub struct Project {
     pub id: ProjectId,
     pub url: String,
     pub name: Option<String>,
+    pub archived: bool,
+    pub client_id: Option<i32>,
+    /// Prefix of issue keys in Jira-style trackers, e.g. "PROJ-"
+    pub issue_prefix: Option<String>,
+    /// Weekly time summaries are posted as comments on GitHub issues
+    pub github_comments: bool,
+    /// Browse URL of issues with `{issue}` in place of the issue number, e.g.
+    /// "https://jira.example.com/browse/PROJ-{issue}"
+    pub issue_url_template: Option<String>,
 }
 
-pub fn get_default_or_create_interactive(conn: &mut SqliteConnection) -> Result<Project> {
-    if let Some(default) = get_default(conn)? {
-        Ok(default)
-    } else {
-        let project = create_interactive(conn)?;
-        set_default(conn, project.id)?;
-        Ok(project)
+impl Project {
+    /// Issue key as shown by the tracker, "PROJ-123" with an issue prefix or "#123" otherwise
+    pub fn issue_key(&self, issue: i32) -> String {
+        match &self.issue_prefix {
+            Some(prefix) => format!("{prefix}{issue}"),
+            None => format!("#{issue}"),
+        }
+    }
+
+    /// Browse URL of the issue from the project's template. Without one, trackers with issue keys
+    /// use `/browse/KEY`, or `/issue/KEY` on YouTrack
+    pub fn issue_url(&self, issue: i32) -> String {
+        let url = self.url.trim_end_matches('/');
+        if let Some(template) = &self.issue_url_template {
+            return fill_issue_url_template(template, url, issue);
+        }
+        match &self.issue_prefix {
+            Some(prefix) if url.contains("youtrack") => format!("{url}/issue/{prefix}{issue}"),
+            Some(prefix) => format!("{url}/browse/{prefix}{issue}"),
+            None => format!("{url}/issues/{issue}"),
+        }
+    }
+
+    /// Whether the value refers to an issue rather than a task, i.e. starts with `#` or an issue
+    /// prefix
+    pub fn is_issue_ref(&self, config: &Config, v: &str) -> bool {
+        v.starts_with('#')
+            || [
+                self.issue_prefix.as_deref(),
+                config.local.issue_prefix.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|prefix| v.starts_with(prefix))
+    }
+
+    /// Parse issue number, optionally prefixed with the project's issue prefix, `#` or the issue
+    /// prefix of the directory settings
+    pub fn parse_issue(&self, config: &Config, v: &str) -> Result<i32> {
+        match (self.issue_prefix.as_deref()).and_then(|prefix| v.strip_prefix(prefix)) {
+            Some(number) => number.parse().map_err(|_| {
+                WlogError::Validation(format!("Invalid issue number: \"{v}\"")).into()
+            }),
+            None => config.local.parse_issue(v),
+        }
+    }
+}
+
+fn fill_issue_url_template(template: &str, project_url: &str, issue: i32) -> String {
+    template
+        .replace("{project_url}", project_url.trim_end_matches('/'))
+        .replace("{issue}", &issue.to_string())
+}
+
+/// Fails unless the template contains `{issue}`, has no other placeholders than `{issue}` and
+/// `{project_url}`, and results in an HTTP URL for the project
+pub fn validate_issue_url_template(template: &str, project_url: &str) -> Result<()> {
+    if !template.contains("{issue}") {
+        bail!(WlogError::Validation(format!(
+            "Issue URL template \"{template}\" has no {{issue}} placeholder"
+        )));
+    }
+    let stripped = template.replace("{issue}", "").replace("{project_url}", "");
+    if stripped.contains(['{', '}']) {
+        bail!(WlogError::Validation(format!(
+            "Issue URL template \"{template}\" has unknown placeholders, only {{issue}} and \
+             {{project_url}} are supported"
+        )));
+    }
+    let url = fill_issue_url_template(template, project_url, 1);
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        bail!(WlogError::Validation(format!(
+            "Issue URL template \"{template}\" doesn't result in an HTTP URL: {url}"
+        )));
+    }
+    Ok(())
+}
+
+pub fn get(conn: &mut SqliteConnection, id: ProjectId) -> Result<Project> {
+    projects::table
+        .find(id.0)
+        .select(Project::as_select())
+        .get_result(conn)
+        .optional()?
+        .ok_or_else(|| WlogError::NotFound(format!("Project {} doesn't exist", id.0)).into())
+}
+
+/// Project pinned by the directory settings, or the default project
+pub fn get_current(conn: &mut SqliteConnection, config: &Config) -> Result<Option<Project>> {
+    let Some(id) = config.local.project else {
+        return get_default(conn);
+    };
+    projects::table
+        .find(id)
+        .select(Project::as_select())
+        .get_result(conn)
+        .optional()?
+        .ok_or_else(|| WlogError::NotFound(format!("Project {id} doesn't exist")).into())
+        .map(Some)
+}
+
+/// Same as [`get_current`], fails if there's no project to read from
+pub fn require_current(conn: &mut SqliteConnection, config: &Config) -> Result<Project> {
+    get_current(conn, config)?.ok_or_else(|| {
+        WlogError::NotFound("No project configured, run `wlog project create`".into()).into()
+    })
+}
+
+/// Same as [`get_current`], offers to create the default project interactively if there's none
+pub fn get_default_or_create_interactive(
+    conn: &mut SqliteConnection,
+    config: &Config,
+) -> Result<Project> {
+    let choice = choose_default_interactive(conn, config)?;
+    conn.transaction(|conn| choice.get_or_create(conn))
+}
+
+/// Project to log to, chosen with prompts before anything is written, so that the database isn't
+/// locked while waiting for answers. It's created by [`ProjectChoice::get_or_create`].
+#[derive(Debug)]
+pub enum ProjectChoice {
+    Existing(Project),
+    /// Created without a name if there's still no project with the URL
+    Url(String),
+    /// Created as the default project
+    New(NewProject),
+}
+
+impl ProjectChoice {
+    /// The project if it exists already
+    pub fn existing(&self) -> Option<&Project> {
+        match self {
+            ProjectChoice::Existing(project) => Some(project),
+            ProjectChoice::Url(_) | ProjectChoice::New(_) => None,
+        }
+    }
+
+    /// Parse issue number like [`Project::parse_issue`], new projects have no issue prefix
+    pub fn parse_issue(&self, config: &Config, v: &str) -> Result<i32> {
+        match self.existing() {
+            Some(project) => project.parse_issue(config, v),
+            None => config.local.parse_issue(v),
+        }
+    }
+
+    pub fn get_or_create(self, conn: &mut SqliteConnection) -> Result<Project> {
+        match self {
+            ProjectChoice::Existing(project) => Ok(project),
+            ProjectChoice::Url(url) => get_or_create_by_url(conn, &url),
+            ProjectChoice::New(project) => {
+                let project = create(conn, project.url, project.name, project.issue_url_template)?;
+                set_default(conn, project.id)?;
+                eprintln!("{} New project created", "Success:".green().bold());
+                Ok(project)
+            }
+        }
+    }
+}
+
+/// Current project, or a new default project described with prompts
+pub fn choose_default_interactive(
+    conn: &mut SqliteConnection,
+    config: &Config,
+) -> Result<ProjectChoice> {
+    if let Some(project) = get_current(conn, config)? {
+        return Ok(ProjectChoice::Existing(project));
+    }
+    if !yn_prompt("No project is configured yet. Create one now?")? {
+        bail!(WlogError::Canceled(
+            "No project configured, run `wlog project create`".into()
+        ));
+    }
+    prompt_new().map(ProjectChoice::New)
+}
+
+/// Project with the URL, created later if there's none
+pub fn choose_by_url(conn: &mut SqliteConnection, url: &str) -> Result<ProjectChoice> {
+    let project = projects::table
+        .filter(projects::url.eq(url))
+        .select(Project::as_select())
+        .first(conn)
+        .optional()?;
+    Ok(match project {
+        Some(project) => ProjectChoice::Existing(project),
+        None => ProjectChoice::Url(url.to_string()),
+    })
+}
+
+/// Project with the given URL, created without a name if there's none
+pub fn get_or_create_by_url(conn: &mut SqliteConnection, url: &str) -> Result<Project> {
+    if let ProjectChoice::Existing(project) = choose_by_url(conn, url)? {
+        return Ok(project);
     }
+    let project = create(conn, url.to_string(), None, None)?;
+    eprintln!(
+        "{} New project {} created for {url}",
+        "Info:".cyan(),
+        project.id.0
+    );
+    Ok(project)
 }
 
-pub fn set_default_interactive(conn: &mut SqliteConnection) -> Result<()> {
-    list_all(conn)?;
-    let project_id = prompt("New default project ID")?;
+pub fn set_default_interactive(conn: &mut SqliteConnection, config: &Config) -> Result<()> {
+    list_all(conn, config, false, OutputFormat::Table)?;
+    let project_id = prompt("New default project ID", History::ProjectId)?;
+    let project = get(conn, ProjectId(project_id))?;
+    print_summary(conn, config, &project)?;
+    if !yn_prompt("Make it the default project?")? {
+        bail!(WlogError::Canceled("Default project wasn't changed".into()));
+    }
     set_default(conn, ProjectId(project_id))?;
     eprintln!(
         "{} Default project set to {}",
@@ -43,9 +252,51 @@ pub fn set_default_interactive(conn: &mut SqliteConnection) -> Result<()> {
     Ok(())
 }
 
+/// Print when the project was last logged, the time logged this month and the number of tasks
+pub fn print_summary(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+) -> Result<()> {
+    let today = config.now()?.date();
+    let last = log_entries::get_last_date(conn, project.id)?;
+    let month = log_entries::get_month_total(conn, project.id, today)?;
+    let tasks = tasks::count(conn, project.id)?;
+
+    eprintln!(
+        "Project {}: {}",
+        project.id.0,
+        project.name.as_deref().unwrap_or(&project.url)
+    );
+    eprintln!(
+        "  Last logged: {}",
+        last.map(|date| date.to_string())
+            .unwrap_or_else(|| "never".to_string())
+    );
+    eprintln!("  This month:  {}", config.fmt_duration(month));
+    eprintln!("  Tasks:       {tasks}");
+    Ok(())
+}
+
 pub fn create_interactive(conn: &mut SqliteConnection) -> Result<Project> {
-    let project_name = prompt_opt("Project name")?;
-    let project_url = prompt("URL")?;
+    let project = prompt_new()?;
+    let project = create(conn, project.url, project.name, project.issue_url_template)?;
+    eprintln!("{} New project created", "Success:".green().bold());
+    Ok(project)
+}
+
+/// Ask for the name, URL and issue URL template of a new project and confirm them
+fn prompt_new() -> Result<NewProject> {
+    let project_name = prompt_opt("Project name", History::ProjectName)?;
+    let project_url: String = prompt("URL", History::ProjectUrl)?;
+    let issue_url_template = prompt_opt::<String>(
+        "Issue URL template with {issue} for the issue number, e.g. \
+         https://jira.example.com/browse/PROJ-{issue}. By default issues link to the project URL",
+        History::ProjectUrl,
+    )?;
+    if let Some(template) = &issue_url_template {
+        validate_issue_url_template(template, &project_url)?;
+    }
 
     let msg = if let Some(ref n) = project_name {
         format!("Create a new project with name \"{n}\" and URL {project_url}?")
@@ -53,42 +304,56 @@ pub fn create_interactive(conn: &mut SqliteConnection) -> Result<Project> {
         format!("Create a new project with URL {project_url} and no name?")
     };
     if yn_prompt(&msg)? {
-        let pid = create(conn, project_url, project_name)?;
-        eprintln!("{} New project created", "Success:".green().bold());
-        Ok(pid)
+        Ok(NewProject {
+            url: project_url,
+            name: project_name,
+            issue_url_template,
+        })
     } else {
-        bail!("A project wasn't created")
+        bail!(WlogError::Canceled("A project wasn't created".into()))
     }
 }
 
-pub fn list_all(conn: &mut SqliteConnection) -> Result<()> {
+pub fn list_all(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    include_archived: bool,
+    format: OutputFormat,
+) -> Result<()> {
     let default_id = default_project::table
         .select(default_project::project_id)
         .find(0)
         .get_result(conn)
         .optional()?;
-    let mut table = comfy_table::Table::new();
-    table.load_preset(crate::utils::TABLE_STYLE);
-    table.set_header(vec![" ", "ID", "Name", "URL"]);
-    for project in get_all(conn)? {
+    let rows = get_all(conn, include_archived)?.into_iter().map(|project| {
         let mark = if Some(project.id.0) == default_id {
             "*"
+        } else if project.archived {
+            "A"
         } else {
             " "
         };
-        table.add_row(vec![
-            mark,
-            &project.id.0.to_string(),
-            project.name.as_deref().unwrap_or(""),
-            &project.url,
-        ]);
-    }
-    println!("{table}");
-    Ok(())
+        [
+            mark.to_string(),
+            project.id.0.to_string(),
+            project.name.unwrap_or_default(),
+            project.url,
+        ]
+    });
+    format.print_table(config.table_style(), &[" ", "ID", "Name", "URL"], rows)
 }
 
-fn create(conn: &mut SqliteConnection, url: String, name: Option<String>) -> Result<Project> {
-    let project = NewProject { url, name };
+fn create(
+    conn: &mut SqliteConnection,
+    url: String,
+    name: Option<String>,
+    issue_url_template: Option<String>,
+) -> Result<Project> {
+    let project = NewProject {
+        url,
+        name,
+        issue_url_template,
+    };
     diesel::insert_into(projects::table)
         .values(project)
         .returning(Project::as_select())
@@ -96,11 +361,92 @@ fn create(conn: &mut SqliteConnection, url: String, name: Option<String>) -> Res
         .map_err(Into::into)
 }
 
-fn get_all(conn: &mut SqliteConnection) -> Result<Vec<Project>> {
-    projects::table.load(conn).map_err(Into::into)
+pub fn get_all(conn: &mut SqliteConnection, include_archived: bool) -> Result<Vec<Project>> {
+    let mut query = projects::table.select(Project::as_select()).into_boxed();
+    if !include_archived {
+        query = query.filter(projects::archived.eq(false));
+    }
+    query.load(conn).map_err(Into::into)
+}
+
+/// Subquery selecting IDs of projects that aren't archived
+pub fn active_ids() -> projects::BoxedQuery<'static, Sqlite, diesel::sql_types::Integer> {
+    projects::table
+        .select(projects::id)
+        .filter(projects::archived.eq(false))
+        .into_boxed()
+}
+
+/// Set or remove the prefix of issue keys
+pub fn set_issue_prefix(
+    conn: &mut SqliteConnection,
+    id: ProjectId,
+    prefix: Option<&str>,
+) -> Result<()> {
+    let updated = diesel::update(projects::table.find(id.0))
+        .set(projects::issue_prefix.eq(prefix))
+        .execute(conn)?;
+    if updated == 0 {
+        bail!(WlogError::NotFound(format!(
+            "Project {} doesn't exist",
+            id.0
+        )));
+    }
+    Ok(())
+}
+
+/// Set or remove the template of issue URLs, validated against the project URL
+pub fn set_issue_url_template(
+    conn: &mut SqliteConnection,
+    project: &Project,
+    template: Option<&str>,
+) -> Result<()> {
+    if let Some(template) = template {
+        validate_issue_url_template(template, &project.url)?;
+    }
+    diesel::update(projects::table.find(project.id.0))
+        .set(projects::issue_url_template.eq(template))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Enable or disable posting weekly time summaries on GitHub issues of the project
+pub fn set_github_comments(
+    conn: &mut SqliteConnection,
+    id: ProjectId,
+    enabled: bool,
+) -> Result<()> {
+    let updated = diesel::update(projects::table.find(id.0))
+        .set(projects::github_comments.eq(enabled))
+        .execute(conn)?;
+    if updated == 0 {
+        bail!(WlogError::NotFound(format!(
+            "Project {} doesn't exist",
+            id.0
+        )));
+    }
+    Ok(())
+}
+
+/// Hide the project from listings. Unsets the default project if it's the one being archived
+pub fn set_archived(conn: &mut SqliteConnection, id: ProjectId, archived: bool) -> Result<()> {
+    let updated = diesel::update(projects::table.find(id.0))
+        .set(projects::archived.eq(archived))
+        .execute(conn)?;
+    if updated == 0 {
+        bail!(WlogError::NotFound(format!(
+            "Project {} doesn't exist",
+            id.0
+        )));
+    }
+    if archived {
+        diesel::delete(default_project::table.filter(default_project::project_id.eq(id.0)))
+            .execute(conn)?;
+    }
+    Ok(())
 }
 
-fn get_default(conn: &mut SqliteConnection) -> Result<Option<Project>> {
+pub fn get_default(conn: &mut SqliteConnection) -> Result<Option<Project>> {
     default_project::table
         .find(0)
         .inner_join(projects::table)
@@ -111,8 +457,21 @@ fn get_default(conn: &mut SqliteConnection) -> Result<Option<Project>> {
 }
 
 fn set_default(conn: &mut SqliteConnection, id: ProjectId) -> Result<()> {
-    if !diesel::select(diesel::dsl::exists(projects::table.find(id.0))).get_result(conn)? {
-        bail!("Project {} doesn't exist", id.0);
+    let archived = projects::table
+        .find(id.0)
+        .select(projects::archived)
+        .get_result::<bool>(conn)
+        .optional()?;
+    match archived {
+        None => bail!(WlogError::NotFound(format!(
+            "Project {} doesn't exist",
+            id.0
+        ))),
+        Some(true) => bail!(WlogError::Validation(format!(
+            "Project {} is archived",
+            id.0
+        ))),
+        Some(false) => {}
     }
 
     diesel::insert_into(default_project::table)
@@ -133,6 +492,7 @@ fn set_default(conn: &mut SqliteConnection, id: ProjectId) -> Result<()> {
 pub struct NewProject {
     url: String,
     name: Option<String>,
+    issue_url_template: Option<String>,
 }
 
 impl FromSql<diesel::sql_types::Integer, Sqlite> for ProjectId {
@@ -151,3 +511,43 @@ impl ToSql<diesel::sql_types::Integer, Sqlite> for ProjectId {
         <i32 as ToSql<diesel::sql_types::Integer, Sqlite>>::to_sql(&self.0, out)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue_url_template() {
+        let url = "https://jira.example.com/";
+        let valid = [
+            (
+                "https://jira.example.com/browse/PROJ-{issue}",
+                "https://jira.example.com/browse/PROJ-42",
+            ),
+            (
+                "{project_url}/tickets/{issue}",
+                "https://jira.example.com/tickets/42",
+            ),
+        ];
+        for (template, expected) in valid {
+            assert!(
+                validate_issue_url_template(template, url).is_ok(),
+                "{template}"
+            );
+            assert_eq!(fill_issue_url_template(template, url, 42), expected);
+        }
+
+        let invalid = [
+            "https://jira.example.com/browse/PROJ-",
+            "https://jira.example.com/browse/{key}-{issue}",
+            "jira.example.com/browse/PROJ-{issue}",
+            "https://jira.example.com/{issue",
+        ];
+        for template in invalid {
+            assert!(
+                validate_issue_url_template(template, url).is_err(),
+                "{template}"
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::log_entries::Period;
+use crate::schema::auto_summary;
+use crate::utils::OutputFormat;
+use crate::{Config, data, log_entries, projects, schedule};
+use diesel::prelude::*;
+use eyre::{Result, bail};
+use owo_colors::OwoColorize;
+use std::io::IsTerminal;
+use time::{Date, Duration};
+
+pub fn run_daemon(config: &Config) -> Result<()> {
+    let reminder_time = config.reminder_time();
+    eprintln!(
+        "{} Reminder scheduled daily at {reminder_time}",
+        "Info:".cyan()
+    );
+    loop {
+        let now = config.now()?;
+        let mut next = now.replace_time(reminder_time);
+        if next <= now {
+            next += Duration::days(1);
+        }
+        std::thread::sleep((next - now).unsigned_abs());
+
+        if let Err(e) = remind(config, next.date()) {
+            eprintln!("{} {e}", "Error:".red().bold());
+        }
+    }
+}
+
+fn remind(config: &Config, date: Date) -> Result<()> {
+    let mut conn = data::open(config.data_path.as_ref())?;
+    let Some(project) = projects::get_default(&mut conn)? else {
+        return Ok(());
+    };
+    let Some(scheduled) = schedule::scheduled_duration(&mut conn, project.id, date)? else {
+        return Ok(());
+    };
+    let logged = log_entries::get_day_total(&mut conn, project.id, date)?;
+    if logged < scheduled {
+        notify(&format!(
+            "{logged} of {scheduled} logged today, run `wlog log` to add the rest"
+        ))?;
+    }
+    Ok(())
+}
+
+/// Warn if the last scheduled workday before today has no log entries. Failures are ignored so
+/// that the warning never gets in the way of the actual command, and a database with pending
+/// migrations is skipped.
+pub fn nag(config: &Config) {
+    let Ok(Some(date)) = unlogged_workday(config) else {
+        return;
+    };
+    eprintln!(
+        "{} Nothing logged on {date}, {}",
+        "Warning:".yellow().bold(),
+        config.locale().weekday(date.weekday())
+    );
+}
+
+fn unlogged_workday(config: &Config) -> Result<Option<Date>> {
+    // Pending migrations are left to the command, e.g. `wlog data migrate`
+    let Some(mut conn) = data::open_if_current(config.data_path.as_ref())? else {
+        return Ok(None);
+    };
+    let Some(project) = projects::get_current(&mut conn, config)? else {
+        return Ok(None);
+    };
+    let mut date = config.now()?.date();
+    // A month without a single workday means there's nothing to nag about
+    for _ in 0..31 {
+        date = date.previous_day().unwrap();
+        let Some(scheduled) = schedule::scheduled_duration(&mut conn, project.id, date)? else {
+            return Ok(None);
+        };
+        if scheduled.is_positive() {
+            let logged = log_entries::get_day_total(&mut conn, project.id, date)?;
+            return Ok(logged.is_zero().then_some(date));
+        }
+    }
+    Ok(None)
+}
+
+/// Print the summary of the week once per week, on the configured weekday or the first day after
+/// it with any command. Skipped if the output isn't a terminal, failures are ignored like in
+/// [`nag`].
+pub fn auto_summary(config: &Config) {
+    if !std::io::stdout().is_terminal() {
+        return;
+    }
+    if let Err(e) = print_weekly_summary(config) {
+        eprintln!("{} Weekly summary failed: {e}", "Warning:".yellow().bold());
+    }
+}
+
+fn print_weekly_summary(config: &Config) -> Result<()> {
+    let Some(day) = config.auto_summary() else {
+        return Ok(());
+    };
+    // Pending migrations are left to the command, e.g. `wlog data migrate`
+    let Some(mut conn) = data::open_if_current(config.data_path.as_ref())? else {
+        return Ok(());
+    };
+    let Some(project) = projects::get_current(&mut conn, config)? else {
+        return Ok(());
+    };
+    let today = config.now()?.date();
+    let trigger = if today.weekday() == day {
+        today
+    } else {
+        today.prev_occurrence(day)
+    };
+    let last = auto_summary::table
+        .select(auto_summary::last_date)
+        .first::<Date>(&mut conn)
+        .optional()?;
+    if last.is_some_and(|last| last >= trigger) {
+        return Ok(());
+    }
+
+    let from = trigger - Duration::days(trigger.weekday().number_days_from_monday() as i64);
+    let period = Period {
+        from,
+        to: from + Duration::days(6),
+    };
+    eprintln!(
+        "{} Summary of the week {} - {}",
+        "Info:".cyan(),
+        period.from,
+        period.to
+    );
+    log_entries::show_by_task(
+        &mut conn,
+        config,
+        &project,
+        Some(&period),
+        None,
+        config
+            .auto_summary_copy()
+            .then_some(&log_entries::CsvTarget::Clipboard),
+        OutputFormat::Table,
+    )?;
+    diesel::insert_into(auto_summary::table)
+        .values((auto_summary::id.eq(0), auto_summary::last_date.eq(today)))
+        .on_conflict(auto_summary::id)
+        .do_update()
+        .set(auto_summary::last_date.eq(today))
+        .execute(&mut conn)?;
+    Ok(())
+}
+
+fn notify(body: &str) -> Result<()> {
+    let status = std::process::Command::new("notify-send")
+        .args(["--app-name=wlog", "Unlogged work time", body])
+        .status()?;
+    if !status.success() {
+        bail!("notify-send exited with {status}");
+    }
+    Ok(())
+}
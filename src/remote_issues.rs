@@ -0,0 +1,138 @@
+use crate::credentials;
+use crate::error::WlogError;
+use crate::projects::{Project, ProjectId};
+use crate::schema::remote_issues;
+use diesel::prelude::*;
+use eyre::{Result, bail};
+use serde::Deserialize;
+
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct GithubIssue {
+    number: i32,
+    title: String,
+    /// Present if the issue is a pull request
+    pull_request: Option<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabIssue {
+    iid: i32,
+    title: String,
+}
+
+/// Replace cached open issues of the project with the current list from its tracker. Returns
+/// number of cached issues.
+pub fn fetch(conn: &mut SqliteConnection, project: &Project) -> Result<usize> {
+    let token = credentials::get_token(project.id)?;
+    let issues = fetch_open_issues(&project.url, token.as_deref(), None)?;
+
+    conn.transaction(|conn| {
+        diesel::delete(remote_issues::table.filter(remote_issues::project_id.eq(project.id.0)))
+            .execute(conn)?;
+        for (number, title) in &issues {
+            diesel::insert_into(remote_issues::table)
+                .values((
+                    remote_issues::project_id.eq(project.id.0),
+                    remote_issues::number.eq(number),
+                    remote_issues::title.eq(title),
+                ))
+                .execute(conn)?;
+        }
+        Ok(issues.len())
+    })
+}
+
+/// Open issues of a GitHub project, only the ones with the label if it's given
+pub fn fetch_github(project: &Project, label: Option<&str>) -> Result<Vec<(i32, String)>> {
+    let (host, _) = split_repo_url(&project.url)?;
+    if host != "github.com" {
+        bail!(WlogError::Validation(format!(
+            "Project {} isn't hosted on GitHub",
+            project.id.0
+        )));
+    }
+    let token = credentials::get_token(project.id)?;
+    fetch_open_issues(&project.url, token.as_deref(), label)
+}
+
+/// Title of the issue if it's in the cache
+pub fn get_title(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    issue: i32,
+) -> Result<Option<String>> {
+    remote_issues::table
+        .find((project.0, issue))
+        .select(remote_issues::title)
+        .get_result(conn)
+        .optional()
+        .map_err(Into::into)
+}
+
+/// Host and repository path of a project URL, e.g. "github.com" and "owner/repo"
+pub fn split_repo_url(url: &str) -> Result<(&str, &str)> {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    match url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split_once('/')
+    {
+        Some(parts) => Ok(parts),
+        None => bail!("Unable to find repository path in project URL {url}"),
+    }
+}
+
+fn fetch_open_issues(
+    url: &str,
+    token: Option<&str>,
+    label: Option<&str>,
+) -> Result<Vec<(i32, String)>> {
+    let (host, path) = split_repo_url(url)?;
+
+    let mut issues = Vec::new();
+    for page in 1.. {
+        let count = if host == "github.com" {
+            let mut request = ureq::get(&format!("https://api.github.com/repos/{path}/issues"))
+                .query("state", "open")
+                .query("per_page", &PAGE_SIZE.to_string())
+                .query("page", &page.to_string());
+            if let Some(label) = label {
+                request = request.query("labels", label);
+            }
+            if let Some(token) = token {
+                request = request.set("Authorization", &format!("Bearer {token}"));
+            }
+            let page: Vec<GithubIssue> = request.call()?.into_json()?;
+            let count = page.len();
+            issues.extend(
+                page.into_iter()
+                    .filter(|issue| issue.pull_request.is_none())
+                    .map(|issue| (issue.number, issue.title)),
+            );
+            count
+        } else {
+            let project = path.replace('/', "%2F");
+            let mut request =
+                ureq::get(&format!("https://{host}/api/v4/projects/{project}/issues"))
+                    .query("state", "opened")
+                    .query("per_page", &PAGE_SIZE.to_string())
+                    .query("page", &page.to_string());
+            if let Some(label) = label {
+                request = request.query("labels", label);
+            }
+            if let Some(token) = token {
+                request = request.set("PRIVATE-TOKEN", token);
+            }
+            let page: Vec<GitlabIssue> = request.call()?.into_json()?;
+            let count = page.len();
+            issues.extend(page.into_iter().map(|issue| (issue.iid, issue.title)));
+            count
+        };
+        if count < PAGE_SIZE {
+            break;
+        }
+    }
+    Ok(issues)
+}
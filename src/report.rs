@@ -0,0 +1,138 @@
+use crate::Config;
+use crate::log_entries::{self, Period};
+use crate::projects::Project;
+use crate::schedule;
+use diesel::SqliteConnection;
+use eyre::Result;
+use serde::Serialize;
+use time::Duration;
+
+#[derive(Debug, Serialize)]
+struct ReportProject<'a> {
+    id: i32,
+    name: Option<&'a str>,
+    url: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportPeriod {
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportEntry {
+    date: String,
+    weekday: &'static str,
+    issue: Option<i32>,
+    /// Issue as shown by the tracker, e.g. "#123" or "PROJ-123"
+    issue_key: Option<String>,
+    task: String,
+    duration: String,
+    seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportDay {
+    date: String,
+    weekday: &'static str,
+    scheduled: String,
+    scheduled_seconds: i64,
+    logged: String,
+    logged_seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportTotals {
+    logged: String,
+    logged_seconds: i64,
+    scheduled: String,
+    scheduled_seconds: i64,
+}
+
+/// Render a Tera template with log entries of the period. Without a period the schedule covers
+/// days from the first to the last entry.
+pub fn render(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: Option<&Period>,
+    template: &str,
+) -> Result<String> {
+    let locale = config.locale();
+    let by_day = log_entries::get_by_day_expanded(conn, project.id, period, None)?;
+    let period = period.map(|p| p.clamp_start(by_day.first().map(|entry| entry.date)));
+    let period = period.as_ref();
+    let by_task = log_entries::get_by_task_expanded(conn, project.id, period, None)?;
+
+    let entry = |entry: &log_entries::LogEntryExpanded| ReportEntry {
+        date: entry.date.to_string(),
+        weekday: locale.weekday(entry.date.weekday()),
+        issue: entry.issue_number,
+        issue_key: entry.issue_number.map(|n| project.issue_key(n)),
+        task: entry.task_name.clone(),
+        duration: config.fmt_duration(entry.duration),
+        seconds: entry.duration.whole_seconds(),
+    };
+    let entries = by_day.iter().map(entry).collect::<Vec<_>>();
+    let tasks = by_task.iter().map(entry).collect::<Vec<_>>();
+
+    let bounds = period
+        .map(|p| (p.from, p.to))
+        .or_else(|| Some((by_day.first()?.date, by_day.last()?.date)));
+    let mut days = Vec::new();
+    let mut scheduled_total = Duration::ZERO;
+    if let Some((from, to)) = bounds {
+        let period = Period { from, to };
+        let totals = log_entries::get_daily_totals(conn, project.id, &period)?;
+        for date in period.days() {
+            let scheduled =
+                schedule::scheduled_duration(conn, project.id, date)?.unwrap_or_default();
+            let logged = totals
+                .iter()
+                .find(|(d, _)| *d == date)
+                .map(|(_, duration)| *duration)
+                .unwrap_or_default();
+            scheduled_total += scheduled;
+            days.push(ReportDay {
+                date: date.to_string(),
+                weekday: locale.weekday(date.weekday()),
+                scheduled: config.fmt_duration(scheduled),
+                scheduled_seconds: scheduled.whole_seconds(),
+                logged: config.fmt_duration(logged),
+                logged_seconds: logged.whole_seconds(),
+            });
+        }
+    }
+
+    let logged_total = by_day.iter().map(|entry| entry.duration).sum();
+    let totals = ReportTotals {
+        logged: config.fmt_duration(logged_total),
+        logged_seconds: logged_total.whole_seconds(),
+        scheduled: config.fmt_duration(scheduled_total),
+        scheduled_seconds: scheduled_total.whole_seconds(),
+    };
+
+    let mut context = tera::Context::new();
+    context.insert(
+        "project",
+        &ReportProject {
+            id: project.id.0,
+            name: project.name.as_deref(),
+            url: &project.url,
+        },
+    );
+    context.insert(
+        "period",
+        &period.map(|p| ReportPeriod {
+            from: p.from.to_string(),
+            to: p.to.to_string(),
+        }),
+    );
+    context.insert("entries", &entries);
+    context.insert("tasks", &tasks);
+    context.insert("schedule", &days);
+    context.insert("totals", &totals);
+
+    tera::Tera::one_off(template, &context, false).map_err(Into::into)
+}
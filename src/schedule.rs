@@ -1,9 +1,11 @@
+use crate::error::WlogError;
 use crate::projects::ProjectId;
 use crate::schema::{schedule_logs, schedule_settings};
 use diesel::prelude::*;
 use diesel::upsert::excluded;
-use eyre::Result;
-use time::{Date, Weekday};
+use eyre::{Result, bail};
+use std::collections::HashMap;
+use time::{Date, Duration, Weekday};
 
 #[derive(Debug, Clone, Copy)]
 pub struct WeekBasedSchedule(pub u8);
@@ -44,11 +46,105 @@ impl WeekBasedSchedule {
             .collect()
     }
 
+    /// Spread `total` evenly across scheduled workdays of the week containing `date`. Leftover
+    /// minutes are assigned to the earliest days of the week.
+    pub fn distribute(&self, date: Date, total: Duration) -> Vec<(Date, Duration)> {
+        let days = week_of(date)
+            .filter(|day| self.to_weekdays().contains(&day.weekday()))
+            .collect::<Vec<_>>();
+        distribute(&days, total)
+    }
+
     fn from_bitmap(v: i32) -> Self {
         Self(v.to_le_bytes()[0])
     }
 }
 
+/// Cycle of workdays and days off repeating from an anchor date, for schedules that don't repeat
+/// every week, e.g. 4 days on and 4 off or every other Friday off
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rotation {
+    pub anchor: Date,
+    days: Vec<bool>,
+}
+
+impl Rotation {
+    /// Parse comma-separated runs of workdays and days off, e.g. "5on,2off,4on,3off"
+    pub fn parse(pattern: &str, anchor: Date) -> Result<Self> {
+        let mut days = Vec::new();
+        for run in pattern.split(',').map(str::trim) {
+            let (count, workday) = if let Some(count) = run.strip_suffix("on") {
+                (count, true)
+            } else if let Some(count) = run.strip_suffix("off") {
+                (count, false)
+            } else {
+                bail!(WlogError::Validation(format!(
+                    "Invalid rotation run \"{run}\", expected e.g. \"4on\" or \"3off\""
+                )));
+            };
+            let count: usize = count.trim().parse().map_err(|_| {
+                WlogError::Validation(format!("Invalid number of days in \"{run}\""))
+            })?;
+            days.extend(std::iter::repeat_n(workday, count));
+        }
+        if days.is_empty() || days.len() > 366 {
+            bail!(WlogError::Validation(
+                "Rotation must last between 1 and 366 days".into()
+            ));
+        }
+        Ok(Self { anchor, days })
+    }
+
+    pub fn is_workday(&self, date: Date) -> bool {
+        let i = (date - self.anchor)
+            .whole_days()
+            .rem_euclid(self.days.len() as i64);
+        self.days[i as usize]
+    }
+
+    /// Spread `total` evenly across workdays of the week containing `date`, same as
+    /// [`WeekBasedSchedule::distribute`]
+    pub fn distribute(&self, date: Date, total: Duration) -> Vec<(Date, Duration)> {
+        let days = week_of(date)
+            .filter(|day| self.is_workday(*day))
+            .collect::<Vec<_>>();
+        distribute(&days, total)
+    }
+}
+
+impl std::fmt::Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let runs = self.days.chunk_by(|a, b| a == b).map(|run| {
+            let kind = if run[0] { "on" } else { "off" };
+            format!("{}{kind}", run.len())
+        });
+        f.write_str(&runs.collect::<Vec<_>>().join(","))
+    }
+}
+
+/// Days of the ISO week containing the date
+fn week_of(date: Date) -> impl Iterator<Item = Date> {
+    let monday = date - Duration::days(date.weekday().number_days_from_monday() as i64);
+    (0..7).map(move |i| monday + Duration::days(i))
+}
+
+/// Spread `total` evenly across the days. Leftover minutes are assigned to the earliest days.
+fn distribute(days: &[Date], total: Duration) -> Vec<(Date, Duration)> {
+    if days.is_empty() {
+        return Vec::new();
+    }
+    let total = total.whole_minutes();
+    let per_day = total / days.len() as i64;
+    let remainder = total % days.len() as i64;
+    days.iter()
+        .enumerate()
+        .map(|(i, date)| {
+            let minutes = per_day + (i < remainder as usize) as i64;
+            (*date, Duration::minutes(minutes))
+        })
+        .collect()
+}
+
 pub struct ScheduleLog(u32);
 
 impl ScheduleLog {
@@ -61,34 +157,56 @@ impl ScheduleLog {
     }
 
     fn from_weekly(schedule: WeekBasedSchedule, date: time::Date) -> Self {
-        let first_weekday = date
-            .replace_day(1)
-            .unwrap()
-            .weekday()
-            .number_days_from_monday();
+        Self::from_days(date, schedule.is_flexible(), |day| {
+            ((1 << day.weekday().number_days_from_monday()) & schedule.0) > 0
+        })
+    }
+
+    fn from_rotation(rotation: &Rotation, flexible: bool, date: time::Date) -> Self {
+        Self::from_days(date, flexible, |day| rotation.is_workday(day))
+    }
+
+    /// Bitmap of workdays of the month containing `date`
+    fn from_days(date: time::Date, flexible: bool, is_workday: impl Fn(Date) -> bool) -> Self {
+        let first = date.replace_day(1).unwrap();
         let bitmap =
             (0..time::util::days_in_month(date.month(), date.year())).fold(0u32, |acc, i| {
-                let weekday = (i + first_weekday) % 7;
-                let is_workday = ((1 << weekday) & schedule.0) > 0;
+                let is_workday = is_workday(first + Duration::days(i as i64));
                 acc | (is_workday as u32) << i
-            }) | ((schedule.is_flexible() as u32) << 31);
+            }) | ((flexible as u32) << 31);
         Self(bitmap)
     }
 
     pub fn is_workday(&self, ord: u8) -> bool {
         self.0 & (1 << (ord - 1)) != 0
     }
+
+    pub fn is_flexible(&self) -> bool {
+        self.0 & (1 << 31) != 0
+    }
+
+    /// Whether time can be logged on the day of the month. Rigid schedules only allow workdays,
+    /// months logged without a schedule allow any day.
+    pub fn allows_log(&self, ord: u8) -> bool {
+        self.0 == 0 || self.is_flexible() || self.is_workday(ord)
+    }
 }
 
 pub fn set(
     conn: &mut SqliteConnection,
     project_id: ProjectId,
     schedule: WeekBasedSchedule,
+    rotation: Option<&Rotation>,
+    workday: Duration,
+    weekly_target: Option<Duration>,
 ) -> Result<()> {
     let schedule = Schedule {
         project_id,
         weekdays: Some(schedule.0 as i32),
-        workday_minutes: Some(8 * 60),
+        workday_minutes: Some(workday.whole_minutes() as i32),
+        weekly_minutes: weekly_target.map(|d| d.whole_minutes() as i32),
+        rotation: rotation.map(Rotation::to_string),
+        rotation_anchor: rotation.map(|rotation| rotation.anchor),
     };
     diesel::insert_into(schedule_settings::table)
         .values(&schedule)
@@ -100,21 +218,10 @@ pub fn set(
 }
 
 pub fn log(conn: &mut SqliteConnection, project_id: ProjectId, date: Date) -> Result<()> {
-    let schedule: Option<Schedule> = schedule_settings::table
-        .find(project_id)
-        .get_result(conn)
-        .optional()?;
-    let bitmap = if let Some(schedule) = schedule {
-        let schedule = WeekBasedSchedule::from_bitmap(schedule.weekdays.unwrap());
-        ScheduleLog::from_weekly(schedule, date).to_bitmap()
-    } else {
-        0
-    };
-
     let log = ScheduleLogEntry {
         project_id,
         month: date.year() * 12 + date.month() as i32,
-        bitmap,
+        bitmap: current_log(conn, project_id, date)?.map_or(0, |log| log.to_bitmap()),
     };
 
     diesel::insert_into(schedule_logs::table)
@@ -126,6 +233,44 @@ pub fn log(conn: &mut SqliteConnection, project_id: ProjectId, date: Date) -> Re
     Ok(())
 }
 
+/// Workdays of the month containing `date` by current schedule settings, `None` if the project has
+/// no schedule
+fn current_log(
+    conn: &mut SqliteConnection,
+    project_id: ProjectId,
+    date: Date,
+) -> Result<Option<ScheduleLog>> {
+    let schedule: Option<Schedule> = schedule_settings::table
+        .find(project_id)
+        .get_result(conn)
+        .optional()?;
+    let Some(schedule) = schedule else {
+        return Ok(None);
+    };
+    let weekly = WeekBasedSchedule::from_bitmap(schedule.weekdays.unwrap());
+    Ok(Some(match schedule.rotation()? {
+        Some(rotation) => ScheduleLog::from_rotation(&rotation, weekly.is_flexible(), date),
+        None => ScheduleLog::from_weekly(weekly, date),
+    }))
+}
+
+/// Fail if the project's schedule is rigid and the date isn't one of its workdays. Uses the
+/// schedule recorded for the month if it exists, current schedule settings otherwise.
+pub fn check_workday(conn: &mut SqliteConnection, project_id: ProjectId, date: Date) -> Result<()> {
+    let log = match get_log(conn, project_id, date)? {
+        Some(log) => Some(log),
+        None => current_log(conn, project_id, date)?,
+    };
+    if let Some(log) = log
+        && !log.allows_log(date.day())
+    {
+        bail!(WlogError::Validation(format!(
+            "{date} is not a workday of the project's rigid schedule"
+        )));
+    }
+    Ok(())
+}
+
 pub fn get(
     conn: &mut SqliteConnection,
     project_id: ProjectId,
@@ -140,6 +285,100 @@ pub fn get(
         .map_err(Into::into)
 }
 
+/// Scheduled work duration for the given date, `None` if the project has no schedule. Uses the
+/// schedule recorded for the month if it exists, current schedule settings otherwise.
+pub fn scheduled_duration(
+    conn: &mut SqliteConnection,
+    project_id: ProjectId,
+    date: Date,
+) -> Result<Option<Duration>> {
+    scheduled_durations(conn, project_id, date, date)
+        .map(|durations| durations.map(|durations| durations[0].1))
+}
+
+/// Scheduled work duration of every day from `from` to `to`, see [`scheduled_duration`]. Settings
+/// and recorded months are read once for the whole range.
+pub fn scheduled_durations(
+    conn: &mut SqliteConnection,
+    project_id: ProjectId,
+    from: Date,
+    to: Date,
+) -> Result<Option<Vec<(Date, Duration)>>> {
+    let schedule: Option<Schedule> = schedule_settings::table
+        .find(project_id)
+        .get_result(conn)
+        .optional()?;
+    let Some(schedule) = schedule else {
+        return Ok(None);
+    };
+    let month = |date: Date| date.year() * 12 + date.month() as i32;
+    let logs = schedule_logs::table
+        .filter(schedule_logs::project_id.eq(project_id))
+        .filter(schedule_logs::month.between(month(from), month(to)))
+        .select((schedule_logs::month, schedule_logs::bitmap))
+        .load::<(i32, i32)>(conn)?
+        .into_iter()
+        .map(|(month, bitmap)| (month, ScheduleLog::from_bitmap(bitmap)))
+        .collect::<HashMap<_, _>>();
+    let rotation = schedule.rotation()?;
+    let weekdays = WeekBasedSchedule::from_bitmap(schedule.weekdays.unwrap_or(0)).to_weekdays();
+    let workday = Duration::minutes(schedule.workday_minutes.unwrap_or(8 * 60) as i64);
+
+    let days = std::iter::successors(Some(from), |date| date.next_day().filter(|d| *d <= to));
+    Ok(Some(
+        days.map(|date| {
+            let is_workday = if let Some(log) = logs.get(&month(date)) {
+                log.is_workday(date.day())
+            } else if let Some(rotation) = &rotation {
+                rotation.is_workday(date)
+            } else {
+                weekdays.contains(&date.weekday())
+            };
+            (date, if is_workday { workday } else { Duration::ZERO })
+        })
+        .collect(),
+    ))
+}
+
+/// Rotation replacing the weekdays of the schedule, `None` if the schedule is weekly or unset
+pub fn get_rotation(
+    conn: &mut SqliteConnection,
+    project_id: ProjectId,
+) -> Result<Option<Rotation>> {
+    let schedule: Option<Schedule> = schedule_settings::table
+        .find(project_id)
+        .get_result(conn)
+        .optional()?;
+    match schedule {
+        Some(schedule) => schedule.rotation(),
+        None => Ok(None),
+    }
+}
+
+/// Scheduled duration of a workday, `None` if the project has no schedule
+pub fn get_workday(conn: &mut SqliteConnection, project_id: ProjectId) -> Result<Option<Duration>> {
+    schedule_settings::table
+        .find(project_id)
+        .select(schedule_settings::workday_minutes)
+        .get_result::<Option<i32>>(conn)
+        .optional()
+        .map(|minutes| minutes.map(|m| Duration::minutes(m.unwrap_or(8 * 60) as i64)))
+        .map_err(Into::into)
+}
+
+pub fn get_weekly_target(
+    conn: &mut SqliteConnection,
+    project_id: ProjectId,
+) -> Result<Option<Duration>> {
+    schedule_settings::table
+        .find(project_id)
+        .select(schedule_settings::weekly_minutes)
+        .get_result::<Option<i32>>(conn)
+        .optional()
+        .map(|minutes| minutes.flatten().map(|m| Duration::minutes(m as i64)))
+        .map_err(Into::into)
+}
+
 pub fn get_log(
     conn: &mut SqliteConnection,
     project_id: ProjectId,
@@ -168,10 +407,25 @@ pub struct ScheduleLogEntry {
 #[derive(Debug, Queryable, Selectable, Insertable, AsChangeset)]
 #[diesel(table_name = crate::schema::schedule_settings)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(treat_none_as_null = true)]
 pub struct Schedule {
     project_id: ProjectId,
     weekdays: Option<i32>,
     workday_minutes: Option<i32>,
+    /// Hours required per ISO week regardless of weekdays worked
+    weekly_minutes: Option<i32>,
+    /// Runs of workdays and days off used instead of weekdays, see [`Rotation::parse`]
+    rotation: Option<String>,
+    rotation_anchor: Option<Date>,
+}
+
+impl Schedule {
+    fn rotation(&self) -> Result<Option<Rotation>> {
+        match (&self.rotation, self.rotation_anchor) {
+            (Some(pattern), Some(anchor)) => Rotation::parse(pattern, anchor).map(Some),
+            _ => Ok(None),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -189,4 +443,53 @@ mod tests {
             panic!("expected: {expected:#034b}\n  actual: {bitmap:#034b}");
         }
     }
+
+    #[test]
+    fn rotation_to_log() {
+        let anchor = time::Date::from_calendar_date(2024, time::Month::November, 29).unwrap();
+        let rotation = Rotation::parse("4on,4off", anchor).unwrap();
+        let date = time::Date::from_calendar_date(2024, time::Month::December, 1).unwrap();
+
+        let bitmap = ScheduleLog::from_rotation(&rotation, false, date).to_bitmap();
+        let expected = 0b01000011110000111100001111000011;
+        if bitmap != expected {
+            panic!("expected: {expected:#034b}\n  actual: {bitmap:#034b}");
+        }
+        assert_eq!(rotation.to_string(), "4on,4off");
+    }
+
+    #[test]
+    fn rigid_schedule_log() {
+        let weekdays = [Weekday::Monday, Weekday::Tuesday];
+        // Monday
+        let date = time::Date::from_calendar_date(2024, time::Month::December, 2).unwrap();
+
+        let rigid = ScheduleLog::from_weekly(WeekBasedSchedule::new(&weekdays, false), date);
+        assert!(!rigid.is_flexible());
+        assert!(rigid.allows_log(2));
+        assert!(rigid.allows_log(3));
+        assert!(!rigid.allows_log(4));
+        assert!(!rigid.allows_log(1));
+
+        let flexible = ScheduleLog::from_weekly(WeekBasedSchedule::new(&weekdays, true), date);
+        assert!(flexible.is_flexible());
+        assert!((1..=31).all(|day| flexible.allows_log(day)));
+
+        // Months logged without a schedule
+        assert!((1..=31).all(|day| ScheduleLog(0).allows_log(day)));
+    }
+
+    #[test]
+    fn distribute_week() {
+        let schedule = WeekBasedSchedule::new(
+            &[Weekday::Monday, Weekday::Wednesday, Weekday::Friday],
+            true,
+        );
+        let date = time::Date::from_calendar_date(2025, time::Month::January, 9).unwrap();
+
+        let days = schedule.distribute(date, Duration::minutes(10 * 60 + 1));
+        let expected = [(6, 3 * 60 + 21), (8, 3 * 60 + 20), (10, 3 * 60 + 20)]
+            .map(|(day, minutes)| (date.replace_day(day).unwrap(), Duration::minutes(minutes)));
+        assert_eq!(days, expected);
+    }
 }
@@ -1,5 +1,19 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    auto_summary (id) {
+        id -> Integer,
+        last_date -> Date,
+    }
+}
+
+diesel::table! {
+    clients (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
 diesel::table! {
     comments (id) {
         id -> Integer,
@@ -17,11 +31,100 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    entry_history (id) {
+        id -> Integer,
+        date -> Date,
+        task_id -> Integer,
+        duration_seconds -> Integer,
+        recorded_at -> Nullable<Timestamp>,
+        replaced_at -> Timestamp,
+        removed -> Bool,
+        project_id -> Nullable<Integer>,
+        task_name -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    github_comments (id) {
+        id -> Integer,
+        project_id -> Integer,
+        issue -> Integer,
+        week -> Date,
+        body -> Text,
+        posted_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    imported_entries (source, source_id) {
+        source -> Text,
+        source_id -> Text,
+    }
+}
+
+diesel::table! {
+    invoice_items (invoice_id, date, task_id) {
+        invoice_id -> Integer,
+        date -> Date,
+        task_id -> Integer,
+        task_name -> Text,
+        issue -> Nullable<Integer>,
+        duration_seconds -> Integer,
+    }
+}
+
+diesel::table! {
+    invoices (id) {
+        id -> Integer,
+        project_id -> Integer,
+        number -> Integer,
+        month -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    label_caps (project_id, label) {
+        project_id -> Integer,
+        label -> Text,
+        monthly_seconds -> Integer,
+    }
+}
+
+diesel::table! {
+    last_shown (project_id) {
+        project_id -> Integer,
+        shown_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     log_entries (date, task_id) {
         date -> Date,
         task_id -> Integer,
-        duration_minutes -> Integer,
+        duration_seconds -> Integer,
+        utc_offset_seconds -> Nullable<Integer>,
+        start_time -> Nullable<Time>,
+        created_at -> Nullable<Timestamp>,
+        updated_at -> Nullable<Timestamp>,
+        source -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    month_closings (project_id, month) {
+        project_id -> Integer,
+        month -> Integer,
+        carryover_seconds -> Integer,
+    }
+}
+
+diesel::table! {
+    monthly_totals (task_id, month) {
+        task_id -> Integer,
+        month -> Integer,
+        duration_seconds -> Integer,
     }
 }
 
@@ -30,6 +133,19 @@ diesel::table! {
         id -> Integer,
         url -> Text,
         name -> Nullable<Text>,
+        archived -> Bool,
+        client_id -> Nullable<Integer>,
+        issue_prefix -> Nullable<Text>,
+        github_comments -> Bool,
+        issue_url_template -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    remote_issues (project_id, number) {
+        project_id -> Integer,
+        number -> Integer,
+        title -> Text,
     }
 }
 
@@ -46,6 +162,65 @@ diesel::table! {
         project_id -> Integer,
         weekdays -> Nullable<Integer>,
         workday_minutes -> Nullable<Integer>,
+        weekly_minutes -> Nullable<Integer>,
+        rotation -> Nullable<Text>,
+        rotation_anchor -> Nullable<Date>,
+    }
+}
+
+diesel::table! {
+    sync_machine (id) {
+        id -> Integer,
+        machine_id -> Text,
+    }
+}
+
+diesel::table! {
+    sync_state (project_url, task_name, date) {
+        project_url -> Text,
+        task_name -> Text,
+        date -> Date,
+        duration_seconds -> Integer,
+        updated_ms -> BigInt,
+    }
+}
+
+diesel::table! {
+    task_estimates (task_id) {
+        task_id -> Integer,
+        seconds -> Integer,
+    }
+}
+
+diesel::table! {
+    task_issues (task_id, issue) {
+        task_id -> Integer,
+        issue -> Integer,
+    }
+}
+
+diesel::table! {
+    task_labels (task_id, label) {
+        task_id -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    task_renames (id) {
+        id -> Integer,
+        task_id -> Integer,
+        changed_at -> Timestamp,
+        change -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    task_targets (task_id) {
+        task_id -> Integer,
+        weekly_seconds -> Integer,
     }
 }
 
@@ -55,22 +230,65 @@ diesel::table! {
         project_id -> Integer,
         name -> Text,
         issue -> Nullable<Integer>,
+        description -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    webhook_outbox (id) {
+        id -> Integer,
+        payload -> Text,
     }
 }
 
 diesel::joinable!(comments -> projects (project_id));
 diesel::joinable!(default_project -> projects (project_id));
+diesel::joinable!(entry_history -> tasks (task_id));
+diesel::joinable!(github_comments -> projects (project_id));
+diesel::joinable!(invoice_items -> invoices (invoice_id));
+diesel::joinable!(invoices -> projects (project_id));
+diesel::joinable!(label_caps -> projects (project_id));
+diesel::joinable!(last_shown -> projects (project_id));
 diesel::joinable!(log_entries -> tasks (task_id));
+diesel::joinable!(month_closings -> projects (project_id));
+diesel::joinable!(monthly_totals -> tasks (task_id));
+diesel::joinable!(projects -> clients (client_id));
+diesel::joinable!(remote_issues -> projects (project_id));
 diesel::joinable!(schedule_logs -> projects (project_id));
 diesel::joinable!(schedule_settings -> projects (project_id));
+diesel::joinable!(task_estimates -> tasks (task_id));
+diesel::joinable!(task_issues -> tasks (task_id));
+diesel::joinable!(task_labels -> tasks (task_id));
+diesel::joinable!(task_renames -> tasks (task_id));
+diesel::joinable!(task_targets -> tasks (task_id));
 diesel::joinable!(tasks -> projects (project_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    auto_summary,
+    clients,
     comments,
     default_project,
+    entry_history,
+    github_comments,
+    imported_entries,
+    invoice_items,
+    invoices,
+    label_caps,
+    last_shown,
     log_entries,
+    month_closings,
+    monthly_totals,
     projects,
+    remote_issues,
     schedule_logs,
     schedule_settings,
+    sync_machine,
+    sync_state,
+    task_estimates,
+    task_issues,
+    task_labels,
+    task_renames,
+    task_targets,
     tasks,
+    webhook_outbox,
 );
@@ -0,0 +1,103 @@
+use crate::Config;
+use crate::balance::fmt_balance;
+use crate::log_entries::{self, Period};
+use crate::projects::Project;
+use crate::tasks::{self, TaskId};
+use crate::utils::OutputFormat;
+use diesel::SqliteConnection;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use time::Duration;
+
+struct Row {
+    issue: Option<i32>,
+    task: String,
+    estimate: Option<Duration>,
+    logged: Duration,
+}
+
+/// Print estimates against time logged in the period for tasks worked on in the period, as well as
+/// estimated tasks without any time logged yet
+pub fn report(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: &Project,
+    period: &Period,
+    format: OutputFormat,
+) -> Result<()> {
+    let estimates = tasks::get_estimates(conn, project.id)?;
+    let estimate = |id: TaskId| {
+        estimates
+            .iter()
+            .find(|(task, _)| task.id == id)
+            .map(|(_, estimate)| *estimate)
+    };
+
+    let mut rows = log_entries::get_task_totals(conn, project.id, Some(period), None)?
+        .into_iter()
+        .map(|total| Row {
+            issue: total.issue_number,
+            task: total.task_name,
+            estimate: estimate(total.task_id),
+            logged: total.duration,
+        })
+        .collect::<Vec<_>>();
+    let started = log_entries::get_task_totals(conn, project.id, None, None)?;
+    for (task, estimate) in &estimates {
+        if !started.iter().any(|total| total.task_id == task.id) {
+            rows.push(Row {
+                issue: task.issue,
+                task: task.name.clone(),
+                estimate: Some(*estimate),
+                logged: Duration::ZERO,
+            });
+        }
+    }
+
+    let estimated = rows.iter().filter(|row| row.estimate.is_some());
+    let total_estimate: Duration = estimated.clone().filter_map(|row| row.estimate).sum();
+    let total_variance: Duration =
+        estimated.map(|row| row.logged).sum::<Duration>() - total_estimate;
+    let total_logged: Duration = rows.iter().map(|row| row.logged).sum();
+    let unestimated = rows.iter().filter(|row| row.estimate.is_none()).count();
+
+    let footer = format.has_totals().then(|| {
+        [
+            format.strong("Total"),
+            String::new(),
+            format.strong(&config.fmt_duration(total_estimate)),
+            format.strong(&config.fmt_duration(total_logged)),
+            format.strong(&fmt_balance(config, total_variance)),
+        ]
+    });
+    format.print_table(
+        config.table_style(),
+        &["Issue", "Task", "Estimate", "Logged", "Variance"],
+        rows.iter()
+            .map(|row| {
+                [
+                    row.issue
+                        .map(|n| format.issue(n, project))
+                        .unwrap_or_else(|| "-".to_string()),
+                    format.task_name(&row.task),
+                    row.estimate
+                        .map(|estimate| config.fmt_duration(estimate))
+                        .unwrap_or_else(|| "-".to_string()),
+                    config.fmt_duration(row.logged),
+                    row.estimate
+                        .map(|estimate| fmt_balance(config, row.logged - estimate))
+                        .unwrap_or_else(|| "-".to_string()),
+                ]
+            })
+            .chain(footer),
+    )?;
+
+    if unestimated > 0 {
+        eprintln!(
+            "{} {unestimated} tasks without estimates are left out of the total variance, see \
+             `wlog task estimate`",
+            "Note:".cyan()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::log_entries::{self, Period};
+use crate::projects::ProjectId;
+use crate::schedule;
+use diesel::SqliteConnection;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use std::collections::HashMap;
+use time::Date;
+
+/// Numbers of consecutive scheduled workdays with the scheduled time logged in full
+#[derive(Debug, Default)]
+pub struct Streak {
+    pub current: u32,
+    pub longest: u32,
+}
+
+impl Streak {
+    /// Colored one-line summary shown below `show` output
+    pub fn badge(&self) -> String {
+        let text = format!(
+            " {} workday streak, longest {} ",
+            self.current, self.longest
+        );
+        if self.current == 0 {
+            text.white().on_red().to_string()
+        } else if self.current == self.longest {
+            text.black().on_yellow().to_string()
+        } else {
+            text.black().on_green().to_string()
+        }
+    }
+}
+
+/// Streaks from the first log entry of the project up to the date, `None` if the project has no
+/// schedule. Days off don't break a streak, and neither does the date itself until it's over.
+pub fn get(conn: &mut SqliteConnection, project: ProjectId, today: Date) -> Result<Option<Streak>> {
+    let totals = log_entries::get_daily_totals(conn, project, &Period::until(today))?;
+    let mut streak = Streak::default();
+    let Some(&(from, _)) = totals.first() else {
+        return Ok(schedule::get(conn, project)?.map(|_| streak));
+    };
+    let Some(scheduled) = schedule::scheduled_durations(conn, project, from, today)? else {
+        return Ok(None);
+    };
+
+    let totals = totals.into_iter().collect::<HashMap<_, _>>();
+    for (date, scheduled) in scheduled {
+        if !scheduled.is_positive() {
+            continue;
+        }
+        let logged = totals.get(&date).copied().unwrap_or_default();
+        if logged >= scheduled {
+            streak.current += 1;
+            streak.longest = streak.longest.max(streak.current);
+        } else if date != today {
+            streak.current = 0;
+        }
+    }
+    Ok(Some(streak))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data;
+    use crate::log_entries::{Limits, set_duration};
+    use crate::schedule::WeekBasedSchedule;
+    use time::{Duration, Month, Weekday};
+
+    #[test]
+    fn workday_streaks() {
+        let (mut conn, project) = data::open_memory_with_project();
+        let task = crate::tasks::create(&mut conn, project, "Task", None).unwrap();
+        let limits = Limits::unlimited();
+        assert!(get(&mut conn, project, Date::MAX).unwrap().is_none());
+        let weekdays = [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+        ];
+        let schedule = WeekBasedSchedule::new(&weekdays, true);
+        schedule::set(&mut conn, project, schedule, None, Duration::hours(8), None).unwrap();
+
+        // Monday to Thursday in full, a short Friday, then Monday and Tuesday
+        let day = |day| Date::from_calendar_date(2026, Month::October, day).unwrap();
+        for (date, hours) in [(5, 8), (6, 8), (7, 9), (8, 8), (9, 4), (12, 8), (13, 8)] {
+            set_duration(
+                &mut conn,
+                project,
+                day(date),
+                task,
+                Duration::hours(hours),
+                &limits,
+            )
+            .unwrap();
+        }
+        let streak = get(&mut conn, project, day(13)).unwrap().unwrap();
+        assert_eq!((streak.current, streak.longest), (2, 4));
+        // The current day doesn't break the streak until it's over
+        let streak = get(&mut conn, project, day(14)).unwrap().unwrap();
+        assert_eq!((streak.current, streak.longest), (2, 4));
+        let streak = get(&mut conn, project, day(15)).unwrap().unwrap();
+        assert_eq!((streak.current, streak.longest), (0, 4));
+    }
+}
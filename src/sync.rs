@@ -0,0 +1,459 @@
+use crate::Config;
+use crate::error::WlogError;
+use crate::log_entries::{self, Limits};
+use crate::schema::{self, sync_machine, sync_state};
+use crate::{projects, tasks, webhook};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+
+const JOURNAL_EXTENSION: &str = "jsonl";
+
+/// Log entries are identified by project URL, task name and date, IDs differ between databases
+type Key = (String, String, Date);
+
+/// New duration of a log entry, zero if the entry was removed. Journals consist of one operation
+/// per line.
+#[derive(Debug, Serialize, Deserialize)]
+struct Operation {
+    /// Unix time of the change in milliseconds
+    time: i64,
+    project: String,
+    task: String,
+    issue: Option<i32>,
+    date: String,
+    seconds: i32,
+}
+
+#[derive(Debug, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::sync_state)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+struct SyncedEntry {
+    project_url: String,
+    task_name: String,
+    date: Date,
+    duration_seconds: i32,
+    updated_ms: i64,
+}
+
+/// Append local changes since the last sync to the journal of this machine in the directory and
+/// apply newer changes from journals of other machines, the last change of an entry wins. Tasks
+/// missing locally are created by name. Changes of other machines passed the edit grace period
+/// there, so locked entries are updated regardless.
+pub fn sync_file(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    dir: &Path,
+    limits: &Limits,
+) -> Result<()> {
+    if !dir.is_dir() {
+        eyre::bail!(WlogError::NotFound(format!(
+            "Sync directory {} doesn't exist",
+            dir.display()
+        )));
+    }
+
+    let limits = Limits {
+        locked_before: None,
+        ..*limits
+    };
+    let (exported, imported) = conn.transaction(|conn| {
+        let machine = get_machine_id(conn)?;
+        // Entries changed before timestamps were recorded count as changed now
+        let now_ms = unix_ms(OffsetDateTime::now_utc());
+
+        let mut synced = sync_state::table
+            .select(SyncedEntry::as_select())
+            .load_iter(conn)?
+            .map(|entry| {
+                let entry: SyncedEntry = entry?;
+                let key = (entry.project_url, entry.task_name, entry.date);
+                Ok((key, (entry.duration_seconds, entry.updated_ms)))
+            })
+            .collect::<Result<HashMap<Key, (i32, i64)>>>()?;
+
+        // Local changes are entries that differ from the last synced state, timed by the last
+        // update of the entry
+        let local = get_local_entries(conn)?;
+        let removed = get_removal_times(conn)?;
+        let mut changes = Vec::new();
+        for (key, entry) in &local {
+            if synced
+                .get(key)
+                .is_none_or(|(synced, _)| *synced != entry.seconds)
+            {
+                let time = entry.updated_ms.unwrap_or(now_ms);
+                changes.push(operation(key, entry.issue, entry.seconds, time));
+            }
+        }
+        for (key, (seconds, _)) in &synced {
+            if *seconds != 0 && !local.contains_key(key) {
+                let time = removed.get(key).copied().unwrap_or(now_ms);
+                changes.push(operation(key, None, 0, time));
+            }
+        }
+        for change in &changes {
+            let key = key_of(change)?;
+            save_synced(conn, &key, change.seconds, change.time)?;
+            synced.insert(key, (change.seconds, change.time));
+        }
+
+        // Only the latest remote change of every entry matters
+        let mut latest = HashMap::<Key, Operation>::new();
+        for op in read_journals(dir, &machine)? {
+            match latest.entry(key_of(&op)?) {
+                Entry::Occupied(mut slot) if slot.get().time < op.time => {
+                    slot.insert(op);
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(slot) => {
+                    slot.insert(op);
+                }
+            }
+        }
+        let mut imported = 0;
+        for (key, op) in latest {
+            if synced.get(&key).is_some_and(|(_, time)| *time >= op.time) {
+                continue;
+            }
+            let project = projects::get_or_create_by_url(conn, &op.project)?;
+            let task = tasks::get_or_create(conn, project.id, &op.task, op.issue)?;
+            log_entries::set_duration(
+                conn,
+                project.id,
+                key.2,
+                task,
+                Duration::seconds(op.seconds as i64),
+                &limits,
+            )?;
+            webhook::enqueue(conn, config, key.2, task)?;
+            save_synced(conn, &key, op.seconds, op.time)?;
+            imported += 1;
+        }
+
+        // Exported before the changes count as synced, so that they are never lost if writing the
+        // journal fails. Changes exported again after a rollback are harmless, as they repeat the
+        // local state.
+        append_journal(
+            &dir.join(format!("{machine}.{JOURNAL_EXTENSION}")),
+            &changes,
+        )?;
+
+        eyre::Ok((changes.len(), imported))
+    })?;
+
+    eprintln!(
+        "{} {exported} local changes exported, {imported} entries updated from other machines",
+        "Success:".green().bold(),
+    );
+    Ok(())
+}
+
+/// ID naming the journal of this database, generated on the first sync
+fn get_machine_id(conn: &mut SqliteConnection) -> Result<String> {
+    let id = sync_machine::table
+        .select(sync_machine::machine_id)
+        .first::<String>(conn)
+        .optional()?;
+    if let Some(id) = id {
+        return Ok(id);
+    }
+    let seed = format!(
+        "{}-{}",
+        OffsetDateTime::now_utc().unix_timestamp_nanos(),
+        std::process::id()
+    );
+    let id: String = Sha256::digest(seed)[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    diesel::insert_into(sync_machine::table)
+        .values((sync_machine::id.eq(0), sync_machine::machine_id.eq(&id)))
+        .execute(conn)?;
+    Ok(id)
+}
+
+struct LocalEntry {
+    seconds: i32,
+    issue: Option<i32>,
+    updated_ms: Option<i64>,
+}
+
+/// Durations, issues and update times of all log entries in the database
+fn get_local_entries(conn: &mut SqliteConnection) -> Result<HashMap<Key, LocalEntry>> {
+    schema::log_entries::table
+        .inner_join(schema::tasks::table.inner_join(schema::projects::table))
+        .select((
+            schema::projects::url,
+            schema::tasks::name,
+            schema::tasks::issue,
+            schema::log_entries::date,
+            schema::log_entries::duration_seconds,
+            schema::log_entries::updated_at,
+        ))
+        .load_iter::<(
+            String,
+            String,
+            Option<i32>,
+            Date,
+            i32,
+            Option<PrimitiveDateTime>,
+        ), _>(conn)?
+        .map(|row| {
+            let (url, name, issue, date, seconds, updated_at) = row?;
+            let entry = LocalEntry {
+                seconds,
+                issue,
+                updated_ms: updated_at.map(|at| unix_ms(at.assume_utc())),
+            };
+            Ok(((url, name, date), entry))
+        })
+        .collect()
+}
+
+/// Time of the latest removal of every entry of existing tasks
+fn get_removal_times(conn: &mut SqliteConnection) -> Result<HashMap<Key, i64>> {
+    let mut removed = HashMap::new();
+    let rows = schema::entry_history::table
+        .inner_join(schema::tasks::table.inner_join(schema::projects::table))
+        .filter(schema::entry_history::removed.eq(true))
+        .select((
+            schema::projects::url,
+            schema::tasks::name,
+            schema::entry_history::date,
+            schema::entry_history::replaced_at,
+        ))
+        .load_iter::<(String, String, Date, PrimitiveDateTime), _>(conn)?;
+    for row in rows {
+        let (url, name, date, replaced_at) = row?;
+        let time = unix_ms(replaced_at.assume_utc());
+        removed
+            .entry((url, name, date))
+            .and_modify(|latest: &mut i64| *latest = time.max(*latest))
+            .or_insert(time);
+    }
+    Ok(removed)
+}
+
+fn unix_ms(at: OffsetDateTime) -> i64 {
+    (at.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+fn save_synced(conn: &mut SqliteConnection, key: &Key, seconds: i32, time: i64) -> Result<()> {
+    let entry = SyncedEntry {
+        project_url: key.0.clone(),
+        task_name: key.1.clone(),
+        date: key.2,
+        duration_seconds: seconds,
+        updated_ms: time,
+    };
+    diesel::insert_into(sync_state::table)
+        .values(entry)
+        .on_conflict((
+            sync_state::project_url,
+            sync_state::task_name,
+            sync_state::date,
+        ))
+        .do_update()
+        .set((
+            sync_state::duration_seconds.eq(excluded(sync_state::duration_seconds)),
+            sync_state::updated_ms.eq(excluded(sync_state::updated_ms)),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn operation(key: &Key, issue: Option<i32>, seconds: i32, time: i64) -> Operation {
+    Operation {
+        time,
+        project: key.0.clone(),
+        task: key.1.clone(),
+        issue,
+        date: key.2.to_string(),
+        seconds,
+    }
+}
+
+fn key_of(op: &Operation) -> Result<Key> {
+    Ok((op.project.clone(), op.task.clone(), parse_date(&op.date)?))
+}
+
+/// Operations from journals of all machines except this one
+fn read_journals(dir: &Path, machine: &str) -> Result<Vec<Operation>> {
+    let mut operations = Vec::new();
+    for file in std::fs::read_dir(dir)? {
+        let path = file?.path();
+        if path.extension().is_none_or(|ext| ext != JOURNAL_EXTENSION)
+            || path.file_stem().is_some_and(|stem| stem == machine)
+        {
+            continue;
+        }
+        let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let op = serde_json::from_str(&line).map_err(|e| {
+                WlogError::Validation(format!("Invalid journal {}: {e}", path.display()))
+            })?;
+            operations.push(op);
+        }
+    }
+    Ok(operations)
+}
+
+fn append_journal(path: &Path, operations: &[Operation]) -> Result<()> {
+    if operations.is_empty() {
+        return Ok(());
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut lines = String::new();
+    for op in operations {
+        lines.push_str(&serde_json::to_string(op)?);
+        lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn parse_date(v: &str) -> Result<Date> {
+    Date::parse(v, &time::format_description::well_known::Iso8601::DATE)
+        .map_err(|e| WlogError::Validation(format!("Invalid date \"{v}\": {e}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data;
+    use crate::log_entries::{EntrySource, LogEntry};
+    use time::{Month, Time};
+
+    const URL: &str = "https://example.com";
+
+    fn day() -> Date {
+        Date::from_calendar_date(2026, Month::October, 16).unwrap()
+    }
+
+    fn sync_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("wlog-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn log(conn: &mut SqliteConnection, hours: i64, updated: Time) {
+        let project = projects::get_or_create_by_url(conn, URL).unwrap().id;
+        let task = tasks::get_or_create(conn, project, "Task", None).unwrap();
+        let entry = LogEntry {
+            date: day(),
+            task,
+            duration: Duration::hours(hours),
+            utc_offset: None,
+            start: None,
+            source: EntrySource::Cli,
+        };
+        log_entries::replace_log(conn, project, entry, &Limits::unlimited()).unwrap();
+        // Timestamps are set by triggers, updating only them doesn't record history
+        diesel::update(schema::log_entries::table)
+            .set(schema::log_entries::updated_at.eq(PrimitiveDateTime::new(day(), updated)))
+            .execute(conn)
+            .unwrap();
+    }
+
+    fn logged(conn: &mut SqliteConnection) -> Option<Duration> {
+        get_local_entries(conn)
+            .unwrap()
+            .get(&(URL.to_string(), "Task".to_string(), day()))
+            .map(|entry| Duration::seconds(entry.seconds as i64))
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = sync_dir("sync-round-trip");
+        let mut first = data::open_memory();
+        let mut second = data::open_memory();
+
+        log(&mut first, 3, Time::from_hms(10, 0, 0).unwrap());
+        sync_file(&mut first, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        sync_file(&mut second, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        assert_eq!(logged(&mut second), Some(Duration::hours(3)));
+
+        // Removals travel back
+        diesel::delete(schema::log_entries::table)
+            .execute(&mut second)
+            .unwrap();
+        sync_file(&mut second, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        sync_file(&mut first, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        assert_eq!(logged(&mut first), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn later_change_wins() {
+        let dir = sync_dir("sync-conflict");
+        let mut first = data::open_memory();
+        let mut second = data::open_memory();
+
+        // The second machine changed the entry later but syncs first
+        log(&mut first, 3, Time::from_hms(10, 0, 0).unwrap());
+        log(&mut second, 5, Time::from_hms(11, 0, 0).unwrap());
+        sync_file(&mut second, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        sync_file(&mut first, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        sync_file(&mut second, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        assert_eq!(logged(&mut first), Some(Duration::hours(5)));
+        assert_eq!(logged(&mut second), Some(Duration::hours(5)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    #[test]
+    fn locked_entries_are_synced() {
+        let dir = sync_dir("sync-locked");
+        let mut first = data::open_memory();
+        let mut second = data::open_memory();
+        // As with `edit_grace_days` set and the entry's date past the grace period
+        let locked = Limits {
+            locked_before: day().next_day(),
+            ..Limits::unlimited()
+        };
+
+        log(&mut first, 3, Time::from_hms(10, 0, 0).unwrap());
+        sync_file(&mut first, &Config::default(), &dir, &locked).unwrap();
+        sync_file(&mut second, &Config::default(), &dir, &locked).unwrap();
+        assert_eq!(logged(&mut second), Some(Duration::hours(3)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_export_is_retried() {
+        let dir = sync_dir("sync-failed-export");
+        let mut first = data::open_memory();
+        let mut second = data::open_memory();
+
+        // A directory in place of the journal makes appending fail
+        let journal = dir.join(format!(
+            "{}.{JOURNAL_EXTENSION}",
+            get_machine_id(&mut first).unwrap()
+        ));
+        std::fs::create_dir(&journal).unwrap();
+        log(&mut first, 3, Time::from_hms(10, 0, 0).unwrap());
+        assert!(sync_file(&mut first, &Config::default(), &dir, &Limits::unlimited()).is_err());
+
+        std::fs::remove_dir(&journal).unwrap();
+        sync_file(&mut first, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        sync_file(&mut second, &Config::default(), &dir, &Limits::unlimited()).unwrap();
+        assert_eq!(logged(&mut second), Some(Duration::hours(3)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
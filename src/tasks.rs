@@ -1,8 +1,16 @@
+use crate::Config;
+use crate::error::WlogError;
+use std::collections::HashMap;
 use std::fmt::Write;
 
-use crate::projects::{Project, ProjectId};
-use crate::schema::tasks;
-use crate::utils::{fmt_issue_linked, prompt, prompt_opt, yn_prompt};
+use crate::projects::{self, Project, ProjectId};
+use crate::schema::{
+    log_entries, task_estimates, task_issues, task_labels, task_renames, task_targets, tasks,
+};
+use crate::utils::{
+    History, OutputFormat, escape_like, fmt_timestamp, prompt_opt, prompt_with_initial, yn_prompt,
+};
+use crate::{remote_issues, schedule};
 use diesel::deserialize::{FromSql, FromSqlRow};
 use diesel::expression::AsExpression;
 use diesel::prelude::*;
@@ -10,18 +18,62 @@ use diesel::serialize::ToSql;
 use diesel::sqlite::Sqlite;
 use eyre::Result;
 use owo_colors::OwoColorize;
+use time::{Duration, OffsetDateTime, PrimitiveDateTime};
 
-#[derive(Debug, Eq, PartialEq, Hash, AsExpression, FromSqlRow)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, AsExpression, FromSqlRow)]
 #[diesel(sql_type = diesel::sql_types::Integer)]
 pub struct TaskId(pub i32);
 
+#[derive(Debug, Default, Clone, Copy, clap::ValueEnum)]
+pub enum TaskSort {
+    /// Most recently logged tasks first
+    LastUsed,
+    /// In order of creation
+    #[default]
+    Created,
+    /// Alphabetically by name
+    Name,
+}
+
+/// Selects tasks of log entries to show
+#[derive(Debug, Clone)]
+pub enum TaskFilter {
+    /// Tasks with the issue, either as the main issue or a linked one
+    Issue(i32),
+    Id(TaskId),
+    /// Tasks with the name containing the text
+    Name(String),
+}
+
+impl TaskFilter {
+    /// Subquery selecting IDs of the matching tasks
+    pub fn ids(&self) -> tasks::BoxedQuery<'static, Sqlite, diesel::sql_types::Integer> {
+        let query = tasks::table.select(tasks::id).into_boxed();
+        match self {
+            TaskFilter::Issue(issue) => query.filter(
+                tasks::issue.eq(*issue).or(tasks::id.eq_any(
+                    task_issues::table
+                        .select(task_issues::task_id)
+                        .filter(task_issues::issue.eq(*issue)),
+                )),
+            ),
+            TaskFilter::Id(id) => query.filter(tasks::id.eq(id.0)),
+            TaskFilter::Name(name) => {
+                query.filter(tasks::name.like(like_pattern(name)).escape('\\'))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Queryable, Selectable)]
 #[diesel(table_name = crate::schema::tasks)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Task {
     pub id: TaskId,
+    pub project_id: ProjectId,
     pub name: String,
     pub issue: Option<i32>,
+    pub description: Option<String>,
 }
 
 pub fn get_or_create_interactive(
@@ -30,93 +82,225 @@ pub fn get_or_create_interactive(
     issue: Option<i32>,
     name: Option<&str>,
 ) -> Result<TaskId> {
-    match (issue, name) {
-        (None, None) => create_interactive(conn, project, None),
-        (None, Some(name)) => {
-            if let Some(task) = get_by_name(conn, project, name)? {
-                Ok(task)
-            } else {
-                new_task(
-                    conn,
-                    NewTask {
-                        project_id: project,
-                        issue: None,
-                        name,
-                    },
-                )
-            }
-        }
-        (Some(issue), None) => {
-            if let Some(task) = get_by_issue(conn, project, issue)? {
-                Ok(task)
-            } else {
-                create_interactive(conn, project, Some(issue))
-            }
-        }
-        (Some(issue), Some(name)) => {
-            let task = tasks::table
-                .select(tasks::id)
-                .filter(tasks::project_id.eq(project.0))
-                .filter(tasks::issue.eq(&issue))
-                .filter(tasks::name.eq(name))
-                .first(conn)
-                .optional()?;
-            if let Some(task) = task {
-                Ok(TaskId(task))
-            } else {
-                new_task(
-                    conn,
-                    NewTask {
-                        project_id: project,
-                        name,
-                        issue: Some(issue),
-                    },
-                )
-            }
+    choose_interactive(conn, Some(project), issue, name)?.get_or_create(conn, project)
+}
+
+/// Task to log to, chosen with prompts before anything is written, so that the database isn't
+/// locked while waiting for answers. It's created by [`TaskChoice::get_or_create`].
+#[derive(Debug)]
+pub enum TaskChoice {
+    Existing(TaskId),
+    New { name: String, issue: Option<i32> },
+}
+
+impl TaskChoice {
+    pub fn get_or_create(self, conn: &mut SqliteConnection, project: ProjectId) -> Result<TaskId> {
+        let (name, issue) = match self {
+            TaskChoice::Existing(task) => return Ok(task),
+            TaskChoice::New { name, issue } => (name, issue),
+        };
+        // The task could have been created since it was chosen
+        let task = tasks::table
+            .select(tasks::id)
+            .filter(tasks::project_id.eq(project.0))
+            .filter(tasks::issue.is(issue))
+            .filter(tasks::name.eq(&name))
+            .first(conn)
+            .optional()?;
+        match task {
+            Some(task) => Ok(TaskId(task)),
+            None => new_task(
+                conn,
+                NewTask {
+                    project_id: project,
+                    name: &name,
+                    issue,
+                },
+            ),
         }
     }
 }
 
-pub fn create_interactive(
+/// Find the task by issue and name, or describe a new one with prompts when they aren't enough.
+/// The project is `None` if it's yet to be created and so has no tasks.
+pub fn choose_interactive(
     conn: &mut SqliteConnection,
-    project: ProjectId,
+    project: Option<ProjectId>,
     issue: Option<i32>,
-) -> Result<TaskId> {
-    let task_name: String = prompt("Task name")?;
+    name: Option<&str>,
+) -> Result<TaskChoice> {
+    let task = match (project, issue, name) {
+        (_, None, None) => None,
+        (None, _, _) => None,
+        (Some(project), None, Some(name)) => get_by_name(conn, project, name)?,
+        (Some(project), Some(issue), None) => get_by_issue(conn, project, issue)?,
+        (Some(project), Some(issue), Some(name)) => tasks::table
+            .select(tasks::id)
+            .filter(tasks::project_id.eq(project.0))
+            .filter(tasks::issue.eq(&issue))
+            .filter(tasks::name.eq(name))
+            .first(conn)
+            .optional()?
+            .map(TaskId),
+    };
+    if let Some(task) = task {
+        return Ok(TaskChoice::Existing(task));
+    }
+    match name {
+        Some(name) => Ok(TaskChoice::New {
+            name: name.to_string(),
+            issue,
+        }),
+        None => prompt_new(conn, project, issue),
+    }
+}
+
+/// Ask for the name and issue of a new task and confirm them
+fn prompt_new(
+    conn: &mut SqliteConnection,
+    project: Option<ProjectId>,
+    issue: Option<i32>,
+) -> Result<TaskChoice> {
+    // Suggest the title of the issue from the tracker cache, see `wlog task fetch`
+    let suggestion = match (project, issue) {
+        (Some(project), Some(issue)) => remote_issues::get_title(conn, project, issue)?,
+        _ => None,
+    };
+    let task_name: String = prompt_with_initial(
+        "Task name",
+        suggestion.as_deref().unwrap_or(""),
+        History::TaskName,
+    )?;
     let issue_number = if issue.is_none() {
-        prompt_opt("Issue number")?
+        prompt_opt("Issue number", History::Issue)?
     } else {
         issue
     };
 
-    let task = NewTask {
-        project_id: project,
-        name: task_name.as_ref(),
-        issue: issue_number,
-    };
-
-    let num_confirm = task
-        .issue
+    let num_confirm = issue_number
         .map(|n| format!("issue number {n}"))
         .unwrap_or_else(|| String::from("no issue number"));
     if yn_prompt(&format!(
         "Create a new task with {num_confirm} and name \"{task_name}\"?"
     ))? {
-        new_task(conn, task)
+        Ok(TaskChoice::New {
+            name: task_name,
+            issue: issue_number,
+        })
     } else {
-        eyre::bail!("A task wasn't created")
+        eyre::bail!(WlogError::Canceled("A task wasn't created".into()))
     }
 }
 
-pub fn list(conn: &mut SqliteConnection, project: &Project) -> Result<()> {
-    let tasks = tasks::table
-        .filter(tasks::project_id.eq(project.id.0))
-        .select(Task::as_select())
-        .get_results(conn)?;
+/// Task of the project with the name, created without prompts if there's none. Fails if the issue
+/// is already used by another task of the project.
+pub fn get_or_create(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    name: &str,
+    issue: Option<i32>,
+) -> Result<TaskId> {
+    match get_by_name(conn, project, name)? {
+        Some(task) => Ok(task),
+        None => create(conn, project, name, issue),
+    }
+}
 
-    print_task_list(&project.url, &tasks);
+/// Create a task without prompts. Names and issues already used by a task of the project are
+/// rejected.
+pub fn create(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    name: &str,
+    issue: Option<i32>,
+) -> Result<TaskId> {
+    if let Some(task) = get_by_name(conn, project, name)? {
+        eyre::bail!(WlogError::Validation(format!(
+            "Task {} is already named \"{name}\"",
+            task.0
+        )));
+    }
+    if let Some(issue) = issue
+        && let Some(task) = get_by_issue(conn, project, issue)?
+    {
+        eyre::bail!(WlogError::Validation(format!(
+            "Task {} already has issue {issue}",
+            task.0
+        )));
+    }
+    new_task(
+        conn,
+        NewTask {
+            project_id: project,
+            name,
+            issue,
+        },
+    )
+}
 
-    Ok(())
+/// Create a task for every issue that has none, named after the issue title. Issues whose title
+/// is already taken by another task are skipped. Returns numbers of created and skipped tasks.
+pub fn import_issues(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+    issues: &[(i32, String)],
+) -> Result<(usize, usize)> {
+    conn.transaction(|conn| {
+        let (mut created, mut skipped) = (0, 0);
+        for (issue, title) in issues {
+            if get_by_issue(conn, project, *issue)?.is_some() {
+                continue;
+            }
+            if get_by_name(conn, project, title)?.is_some() {
+                skipped += 1;
+                continue;
+            }
+            new_task(
+                conn,
+                NewTask {
+                    project_id: project,
+                    name: title,
+                    issue: Some(*issue),
+                },
+            )?;
+            created += 1;
+        }
+        Ok((created, skipped))
+    })
+}
+
+/// List tasks of the project, or of every project if `None`
+pub fn list(
+    conn: &mut SqliteConnection,
+    project: Option<&Project>,
+    include_archived: bool,
+    sort: TaskSort,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<Task>> {
+    let mut query = tasks::table
+        .left_join(log_entries::table)
+        .group_by(tasks::id)
+        .select(Task::as_select())
+        .into_boxed();
+    if let Some(project) = project {
+        query = query.filter(tasks::project_id.eq(project.id.0));
+    } else if !include_archived {
+        query = query.filter(tasks::project_id.eq_any(projects::active_ids()));
+    }
+    query = match sort {
+        TaskSort::LastUsed => query.order_by((
+            diesel::dsl::max(log_entries::date.nullable()).desc(),
+            tasks::id.desc(),
+        )),
+        TaskSort::Created => query.order_by(tasks::id),
+        TaskSort::Name => query.order_by((tasks::name, tasks::id)),
+    };
+    if limit.is_some() || offset.is_some() {
+        // SQLite doesn't support OFFSET without LIMIT, negative limit means no limit
+        query = query.limit(limit.unwrap_or(-1)).offset(offset.unwrap_or(0));
+    }
+    query.get_results(conn).map_err(Into::into)
 }
 
 pub fn search_interactive(conn: &mut SqliteConnection, project: &Project) -> Result<()> {
@@ -131,40 +315,454 @@ pub fn search_interactive(conn: &mut SqliteConnection, project: &Project) -> Res
     Ok(())
 }
 
-pub fn search(conn: &mut SqliteConnection, project: &Project, query: String) -> Result<()> {
-    let mut query = query
-        .replace("\\", "\\\\")
-        .replace("%", "\\%")
-        .replace("_", "\\_");
-    query.insert(0, '%');
-    query.push('%');
-
-    let tasks = tasks::table
-        .filter(tasks::project_id.eq(project.id.0))
+/// Search tasks of the project, or of every project if `None`
+pub fn search(
+    conn: &mut SqliteConnection,
+    project: Option<&Project>,
+    include_archived: bool,
+    query: String,
+) -> Result<Vec<Task>> {
+    let mut tasks = tasks::table
         .select(Task::as_select())
-        .filter(tasks::name.like(query))
-        .get_results(conn)?;
+        .filter(tasks::name.like(like_pattern(&query)).escape('\\'))
+        .into_boxed();
+    if let Some(project) = project {
+        tasks = tasks.filter(tasks::project_id.eq(project.id.0));
+    } else if !include_archived {
+        tasks = tasks.filter(tasks::project_id.eq_any(projects::active_ids()));
+    }
+    tasks.get_results(conn).map_err(Into::into)
+}
 
-    print_task_list(&project.url, &tasks);
+pub fn get(conn: &mut SqliteConnection, id: TaskId) -> Result<Task> {
+    tasks::table
+        .find(id.0)
+        .select(Task::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| WlogError::NotFound(format!("Task {} doesn't exist", id.0)).into())
+}
 
-    Ok(())
+/// Number of tasks of the project
+pub fn count(conn: &mut SqliteConnection, project: ProjectId) -> Result<i64> {
+    tasks::table
+        .filter(tasks::project_id.eq(project.0))
+        .count()
+        .get_result(conn)
+        .map_err(Into::into)
 }
 
 pub fn update(
     conn: &mut SqliteConnection,
-    project: &Project,
     id: TaskId,
     name: Option<&str>,
+    mut issue: Option<Option<i32>>,
+    add_issue: Option<i32>,
+    remove_issue: Option<i32>,
+    description: Option<Option<&str>>,
+) -> Result<Task> {
+    let task = get(conn, id)?;
+    let mut changes = Vec::new();
+
+    if let Some(removed) = remove_issue {
+        if task.issue == Some(removed) {
+            issue = Some(None);
+        }
+        let unlinked = diesel::delete(task_issues::table.find((id.0, removed))).execute(conn)?;
+        if unlinked > 0 {
+            changes.push((Change::Unlink, Some(removed.to_string()), None));
+        }
+    }
+    if let Some(added) = add_issue {
+        let linked = diesel::insert_into(task_issues::table)
+            .values((task_issues::task_id.eq(id.0), task_issues::issue.eq(added)))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        if linked > 0 {
+            changes.push((Change::Link, None, Some(added.to_string())));
+        }
+    }
+    if let Some(name) = name.filter(|name| *name != task.name) {
+        changes.push((
+            Change::Name,
+            Some(task.name.clone()),
+            Some(name.to_string()),
+        ));
+    }
+    if let Some(issue) = issue.filter(|issue| *issue != task.issue) {
+        changes.push((
+            Change::Issue,
+            task.issue.map(|i| i.to_string()),
+            issue.map(|i| i.to_string()),
+        ));
+    }
+    let now = OffsetDateTime::now_utc();
+    let now = PrimitiveDateTime::new(now.date(), now.time());
+    for (change, old, new) in changes {
+        diesel::insert_into(task_renames::table)
+            .values((
+                task_renames::task_id.eq(id.0),
+                task_renames::changed_at.eq(now),
+                task_renames::change.eq(change.as_str()),
+                task_renames::old_value.eq(old),
+                task_renames::new_value.eq(new),
+            ))
+            .execute(conn)?;
+    }
+
+    if name.is_some() || issue.is_some() || description.is_some() {
+        diesel::update(tasks::table.find(id.0))
+            .set(TaskUpdate {
+                name,
+                issue,
+                description,
+            })
+            .returning(Task::as_select())
+            .get_result(conn)
+            .map_err(Into::into)
+    } else {
+        Ok(task)
+    }
+}
+
+/// Move the task with all its log entries to another project. The issue is replaced if given,
+/// which also drops linked issues as they refer to the old tracker. Returns the number of moved
+/// entries and whether issue numbers of the old project were kept.
+pub fn move_to_project(
+    conn: &mut SqliteConnection,
+    id: TaskId,
+    to: ProjectId,
     issue: Option<Option<i32>>,
-) -> Result<()> {
-    let task = diesel::update(tasks::table.find(id.0))
-        .set(TaskUpdate { name, issue })
-        .returning(Task::as_select())
-        .get_result(conn)?;
+    limits: &crate::log_entries::Limits,
+) -> Result<(usize, bool)> {
+    let task = get(conn, id)?;
+    if task.project_id == to {
+        eyre::bail!(WlogError::Validation(format!(
+            "Task {} already belongs to project {}",
+            id.0, to.0
+        )));
+    }
+    let project = projects::get(conn, to)?;
+    let new_issue = issue.unwrap_or(task.issue);
+    if let Some(n) = new_issue
+        && let Some(existing) = get_by_issue(conn, to, n)?
+    {
+        eyre::bail!(WlogError::Validation(format!(
+            "Task {} of the target project is already linked to {}, use --issue to pick another \
+             issue or `wlog log move` to merge entries",
+            existing.0,
+            project.issue_key(n)
+        )));
+    }
+    let same_name = tasks::table
+        .filter(tasks::project_id.eq(to.0))
+        .filter(tasks::name.eq(&task.name))
+        .select(tasks::id)
+        .first::<i32>(conn)
+        .optional()?;
+    if let Some(existing) = same_name {
+        eyre::bail!(WlogError::Validation(format!(
+            "Task {existing} of the target project is already named \"{}\"",
+            task.name
+        )));
+    }
+
+    let dates = log_entries::table
+        .filter(log_entries::task_id.eq(id.0))
+        .select(log_entries::date)
+        .order_by(log_entries::date)
+        .load::<time::Date>(conn)?;
+    if let Some(first) = dates.first() {
+        limits.check_date(*first)?;
+    }
+
+    let mut changes = vec![(
+        Change::Project,
+        Some(task.project_id.0.to_string()),
+        Some(to.0.to_string()),
+    )];
+    let kept_issues = issue.is_none()
+        && (task.issue.is_some()
+            || !get_linked_issues(conn, std::slice::from_ref(&task))?.is_empty());
+    if issue.is_some() {
+        let unlinked = diesel::delete(task_issues::table.filter(task_issues::task_id.eq(id.0)))
+            .returning(task_issues::issue)
+            .load::<i32>(conn)?;
+        for removed in unlinked {
+            changes.push((Change::Unlink, Some(removed.to_string()), None));
+        }
+        if new_issue != task.issue {
+            changes.push((
+                Change::Issue,
+                task.issue.map(|i| i.to_string()),
+                new_issue.map(|i| i.to_string()),
+            ));
+        }
+    }
+    let now = OffsetDateTime::now_utc();
+    let now = PrimitiveDateTime::new(now.date(), now.time());
+    for (change, old, new) in changes {
+        diesel::insert_into(task_renames::table)
+            .values((
+                task_renames::task_id.eq(id.0),
+                task_renames::changed_at.eq(now),
+                task_renames::change.eq(change.as_str()),
+                task_renames::old_value.eq(old),
+                task_renames::new_value.eq(new),
+            ))
+            .execute(conn)?;
+    }
+    diesel::update(tasks::table.find(id.0))
+        .set((tasks::project_id.eq(to.0), tasks::issue.eq(new_issue)))
+        .execute(conn)?;
 
-    eprintln!("{} Task has been updated", "Success:".green().bold());
-    print_task_list(&project.url, &[task]);
+    // Months without entries in the target project have no schedule snapshot yet
+    let mut months = dates
+        .iter()
+        .map(|date| date.replace_day(1).unwrap())
+        .collect::<Vec<_>>();
+    months.dedup();
+    for month in months {
+        if schedule::get_log(conn, to, month)?.is_none() {
+            schedule::log(conn, to, month)?;
+        }
+    }
+    Ok((dates.len(), kept_issues))
+}
 
+/// Groups of tasks of the project with near-duplicate names, see [`is_similar_name`]. Tasks are
+/// ordered by ID within a group.
+pub fn find_similar(conn: &mut SqliteConnection, project: ProjectId) -> Result<Vec<Vec<Task>>> {
+    let tasks = tasks::table
+        .filter(tasks::project_id.eq(project.0))
+        .order_by(tasks::id)
+        .select(Task::as_select())
+        .load::<Task>(conn)?;
+    let mut groups: Vec<Vec<Task>> = Vec::new();
+    for task in tasks {
+        let group = groups.iter_mut().find(|group| {
+            group
+                .iter()
+                .any(|other| is_similar_name(&task.name, &other.name))
+        });
+        match group {
+            Some(group) => group.push(task),
+            None => groups.push(vec![task]),
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    Ok(groups)
+}
+
+/// Whether the names differ only in case, whitespace and punctuation, or by a typo. Longer names
+/// allow more edits.
+fn is_similar_name(a: &str, b: &str) -> bool {
+    let key = |name: &str| {
+        name.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (key(a), key(b));
+    if a.is_empty() || b.is_empty() {
+        return a == b;
+    }
+    edit_distance(&a, &b) <= a.len().min(b.len()) / 6
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != cb) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Move all log entries of the task to another task of the same project and delete it. Its issue
+/// and linked issues become linked issues of the other task, and its labels are added to it.
+/// Entries past the edit grace period are only moved if the limits are forced. Returns the number
+/// of moved entries.
+pub fn merge(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    from: TaskId,
+    into: TaskId,
+    limits: &crate::log_entries::Limits,
+) -> Result<usize> {
+    let source = get(conn, from)?;
+    let target = get(conn, into)?;
+    if source.project_id != target.project_id {
+        eyre::bail!(WlogError::Validation(format!(
+            "Tasks {} and {} belong to different projects, use `wlog task move` first",
+            from.0, into.0
+        )));
+    }
+    let moved = crate::log_entries::move_entries(
+        conn,
+        from,
+        into,
+        &crate::log_entries::Period::until(time::Date::MAX),
+        limits,
+    )?;
+    // Entries of the source task are reported as removed while it still exists
+    for &date in &moved {
+        crate::webhook::enqueue(conn, config, date, from)?;
+        crate::webhook::enqueue(conn, config, date, into)?;
+    }
+    let issues = source
+        .issue
+        .into_iter()
+        .chain(
+            get_linked_issues(conn, std::slice::from_ref(&source))?
+                .remove(&from)
+                .into_iter()
+                .flatten(),
+        )
+        .filter(|issue| target.issue != Some(*issue))
+        .collect::<Vec<_>>();
+    let labels = get_labels(conn, &[from])?.remove(&from).unwrap_or_default();
+
+    diesel::delete(tasks::table.find(from.0)).execute(conn)?;
+    for issue in issues {
+        update(conn, into, None, None, Some(issue), None, None)?;
+    }
+    update_labels(conn, into, &labels, &[])?;
+    Ok(moved.len())
+}
+
+/// Kind of a recorded task change
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    Name,
+    Issue,
+    Link,
+    Unlink,
+    Project,
+}
+
+impl Change {
+    fn as_str(self) -> &'static str {
+        match self {
+            Change::Name => "name",
+            Change::Issue => "issue",
+            Change::Link => "link",
+            Change::Unlink => "unlink",
+            Change::Project => "project",
+        }
+    }
+}
+
+/// Print renames and issue changes of the task, oldest first
+pub fn history(conn: &mut SqliteConnection, config: &Config, id: TaskId) -> Result<()> {
+    let task = get(conn, id)?;
+    let project = projects::get(conn, task.project_id)?;
+    let changes = task_renames::table
+        .filter(task_renames::task_id.eq(id.0))
+        .order_by((task_renames::changed_at, task_renames::id))
+        .select((
+            task_renames::changed_at,
+            task_renames::change,
+            task_renames::old_value,
+            task_renames::new_value,
+        ))
+        .load::<(PrimitiveDateTime, String, Option<String>, Option<String>)>(conn)?;
+    if changes.is_empty() {
+        eprintln!("{} Task {} has no recorded changes", "Note:".cyan(), id.0);
+        return Ok(());
+    }
+
+    let offset = config.now()?.offset();
+    let value = |change: &str, value: Option<String>| match (change, value) {
+        (_, None) => "-".to_string(),
+        ("name" | "project", Some(value)) => value,
+        (_, Some(issue)) => match issue.parse() {
+            Ok(issue) => OutputFormat::Table.issue(issue, &project),
+            Err(_) => issue,
+        },
+    };
+    let rows = changes.into_iter().map(|(at, change, old, new)| {
+        [
+            fmt_timestamp(at, offset),
+            change.clone(),
+            value(&change, old),
+            value(&change, new),
+        ]
+    });
+    OutputFormat::Table.print_table(
+        config.table_style(),
+        &["Time", "Change", "From", "To"],
+        rows,
+    )
+}
+
+/// Print details of the task with its latest log entries
+pub fn show(conn: &mut SqliteConnection, config: &Config, id: TaskId, recent: i64) -> Result<()> {
+    let task = get(conn, id)?;
+    let project = projects::get_all(conn, true)?
+        .into_iter()
+        .find(|p| p.id == task.project_id)
+        .ok_or_else(|| WlogError::NotFound(format!("Project of task {} doesn't exist", id.0)))?;
+    let linked = get_linked_issues(conn, std::slice::from_ref(&task))?;
+    let issues = task
+        .issue
+        .iter()
+        .chain(linked.get(&task.id).into_iter().flatten())
+        .map(|&i| OutputFormat::Table.issue(i, &project))
+        .collect::<Vec<_>>();
+
+    let total = log_entries::table
+        .filter(log_entries::task_id.eq(id.0))
+        .select(diesel::dsl::sum(log_entries::duration_seconds))
+        .get_result::<Option<i64>>(conn)?;
+    let entries = log_entries::table
+        .filter(log_entries::task_id.eq(id.0))
+        .select((log_entries::date, log_entries::duration_seconds))
+        .order_by(log_entries::date.desc())
+        .limit(recent)
+        .load::<(time::Date, i32)>(conn)?;
+
+    println!("Task {}: {}", task.id.0, task.name);
+    println!(
+        "Project: {}",
+        project.name.as_deref().unwrap_or(&project.url)
+    );
+    if !issues.is_empty() {
+        println!("Issues: {}", issues.join(", "));
+    }
+    if let Some(labels) = get_labels(conn, &[task.id])?.get(&task.id) {
+        println!("Labels: {}", labels.join(", "));
+    }
+    if let Some(description) = &task.description {
+        println!("Description: {description}");
+    }
+    println!(
+        "Total logged: {}",
+        config.fmt_duration(time::Duration::seconds(total.unwrap_or(0)))
+    );
+    if let Some(estimate) = get_estimate(conn, task.id)? {
+        println!("Estimate: {}", config.fmt_duration(estimate));
+    }
+
+    if !entries.is_empty() {
+        let locale = config.locale();
+        OutputFormat::Table.print_table(
+            config.table_style(),
+            &["Date", "Weekday", "Duration"],
+            entries.iter().map(|(date, seconds)| {
+                [
+                    date.to_string(),
+                    locale.weekday(date.weekday()).to_string(),
+                    config.fmt_duration(time::Duration::seconds(*seconds as i64)),
+                ]
+            }),
+        )?;
+    }
     Ok(())
 }
 
@@ -177,21 +775,218 @@ pub fn new_task(conn: &mut SqliteConnection, new_task: NewTask) -> Result<TaskId
         .map_err(Into::into)
 }
 
-fn get_by_issue(
+/// Weekly time target of the task
+pub fn get_target(conn: &mut SqliteConnection, id: TaskId) -> Result<Option<Duration>> {
+    task_targets::table
+        .find(id.0)
+        .select(task_targets::weekly_seconds)
+        .first::<i32>(conn)
+        .optional()
+        .map(|seconds| seconds.map(|s| Duration::seconds(s as i64)))
+        .map_err(Into::into)
+}
+
+/// Tasks of the project with weekly time targets, ordered by name
+pub fn get_targets(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+) -> Result<Vec<(Task, Duration)>> {
+    let targets = task_targets::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .order_by(tasks::name)
+        .select((Task::as_select(), task_targets::weekly_seconds))
+        .load::<(Task, i32)>(conn)?;
+    Ok(targets
+        .into_iter()
+        .map(|(task, seconds)| (task, Duration::seconds(seconds as i64)))
+        .collect())
+}
+
+/// Set or remove the weekly time target of the task
+pub fn set_target(conn: &mut SqliteConnection, id: TaskId, target: Option<Duration>) -> Result<()> {
+    get(conn, id)?;
+    match target {
+        Some(target) if !target.is_positive() => {
+            eyre::bail!(WlogError::Validation(
+                "Weekly target must be positive".into()
+            ))
+        }
+        Some(target) => {
+            let seconds = target.whole_seconds() as i32;
+            diesel::insert_into(task_targets::table)
+                .values((
+                    task_targets::task_id.eq(id.0),
+                    task_targets::weekly_seconds.eq(seconds),
+                ))
+                .on_conflict(task_targets::task_id)
+                .do_update()
+                .set(task_targets::weekly_seconds.eq(seconds))
+                .execute(conn)?;
+        }
+        None => {
+            diesel::delete(task_targets::table.find(id.0)).execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Estimated total time of the task
+pub fn get_estimate(conn: &mut SqliteConnection, id: TaskId) -> Result<Option<Duration>> {
+    task_estimates::table
+        .find(id.0)
+        .select(task_estimates::seconds)
+        .first::<i32>(conn)
+        .optional()
+        .map(|seconds| seconds.map(|s| Duration::seconds(s as i64)))
+        .map_err(Into::into)
+}
+
+/// Tasks of the project with estimates, ordered by name
+pub fn get_estimates(
+    conn: &mut SqliteConnection,
+    project: ProjectId,
+) -> Result<Vec<(Task, Duration)>> {
+    let estimates = task_estimates::table
+        .inner_join(tasks::table)
+        .filter(tasks::project_id.eq(project.0))
+        .order_by(tasks::name)
+        .select((Task::as_select(), task_estimates::seconds))
+        .load::<(Task, i32)>(conn)?;
+    Ok(estimates
+        .into_iter()
+        .map(|(task, seconds)| (task, Duration::seconds(seconds as i64)))
+        .collect())
+}
+
+/// Set or remove the estimate of the task
+pub fn set_estimate(
+    conn: &mut SqliteConnection,
+    id: TaskId,
+    estimate: Option<Duration>,
+) -> Result<()> {
+    get(conn, id)?;
+    match estimate {
+        Some(estimate) if !estimate.is_positive() => {
+            eyre::bail!(WlogError::Validation("Estimate must be positive".into()))
+        }
+        Some(estimate) => {
+            let seconds = estimate.whole_seconds() as i32;
+            diesel::insert_into(task_estimates::table)
+                .values((
+                    task_estimates::task_id.eq(id.0),
+                    task_estimates::seconds.eq(seconds),
+                ))
+                .on_conflict(task_estimates::task_id)
+                .do_update()
+                .set(task_estimates::seconds.eq(seconds))
+                .execute(conn)?;
+        }
+        None => {
+            diesel::delete(task_estimates::table.find(id.0)).execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Task with the issue as its main or linked issue
+pub fn get_by_issue(
     conn: &mut SqliteConnection,
     project: ProjectId,
     issue: i32,
 ) -> Result<Option<TaskId>> {
-    tasks::table
+    let task = tasks::table
         .select(tasks::id)
         .filter(tasks::project_id.eq(project.0))
         .filter(tasks::issue.eq(&issue))
         .first(conn)
+        .optional()?;
+    if let Some(task) = task {
+        return Ok(Some(TaskId(task)));
+    }
+    task_issues::table
+        .inner_join(tasks::table)
+        .select(tasks::id)
+        .filter(tasks::project_id.eq(project.0))
+        .filter(task_issues::issue.eq(&issue))
+        .first(conn)
         .map(TaskId)
         .optional()
         .map_err(Into::into)
 }
 
+/// LIKE pattern matching names containing the text, with `\` as escape character
+fn like_pattern(text: &str) -> String {
+    let mut pattern = escape_like(text);
+    pattern.insert(0, '%');
+    pattern.push('%');
+    pattern
+}
+
+fn get_linked_issues(
+    conn: &mut SqliteConnection,
+    tasks: &[Task],
+) -> Result<HashMap<TaskId, Vec<i32>>> {
+    task_issues::table
+        .select((task_issues::task_id, task_issues::issue))
+        .filter(task_issues::task_id.eq_any(tasks.iter().map(|task| task.id.0)))
+        .order_by(task_issues::issue)
+        .load_iter::<(i32, i32), _>(conn)?
+        .try_fold(HashMap::new(), |mut acc, row| {
+            let (task_id, issue) = row?;
+            acc.entry(TaskId(task_id))
+                .or_insert_with(Vec::new)
+                .push(issue);
+            Ok(acc)
+        })
+}
+
+/// Labels of the tasks in alphabetical order
+pub fn get_labels(
+    conn: &mut SqliteConnection,
+    tasks: &[TaskId],
+) -> Result<HashMap<TaskId, Vec<String>>> {
+    task_labels::table
+        .select((task_labels::task_id, task_labels::label))
+        .filter(task_labels::task_id.eq_any(tasks.iter().map(|task| task.0)))
+        .order_by(task_labels::label)
+        .load_iter::<(i32, String), _>(conn)?
+        .try_fold(HashMap::new(), |mut acc, row| {
+            let (task_id, label) = row?;
+            acc.entry(TaskId(task_id))
+                .or_insert_with(Vec::new)
+                .push(label);
+            Ok(acc)
+        })
+}
+
+/// Add labels to the task and remove others, labels are trimmed and lowercase
+pub fn update_labels(
+    conn: &mut SqliteConnection,
+    id: TaskId,
+    add: &[String],
+    remove: &[String],
+) -> Result<()> {
+    let normalize = |label: &String| label.trim().to_lowercase();
+    for label in add.iter().map(normalize) {
+        if label.is_empty() {
+            eyre::bail!(WlogError::Validation("Labels can't be empty".into()));
+        }
+        diesel::insert_into(task_labels::table)
+            .values((task_labels::task_id.eq(id.0), task_labels::label.eq(label)))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+    }
+    let remove = remove.iter().map(normalize).collect::<Vec<_>>();
+    diesel::delete(
+        task_labels::table
+            .filter(task_labels::task_id.eq(id.0))
+            .filter(task_labels::label.eq_any(remove)),
+    )
+    .execute(conn)?;
+    Ok(())
+}
+
 fn get_by_name(
     conn: &mut SqliteConnection,
     project: ProjectId,
@@ -207,20 +1002,55 @@ fn get_by_name(
         .map_err(Into::into)
 }
 
-fn print_task_list(project_url: &str, tasks: &[Task]) {
-    let mut table = comfy_table::Table::new();
-    table.load_preset(crate::utils::TABLE_STYLE);
-    table.set_header(["ID", "Issue", "Name"]);
-    table.add_rows(tasks.iter().map(|task| {
-        [
-            task.id.0.to_string(),
-            task.issue
-                .map(|i| fmt_issue_linked(i, project_url))
-                .unwrap_or("-".to_string()),
-            task.name.clone(),
-        ]
-    }));
-    println!("{table}");
+/// Print tasks of a single project, or tasks of any project with an extra project column if
+/// `project` is `None`
+pub fn print_task_list(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    project: Option<&Project>,
+    tasks: &[Task],
+    format: OutputFormat,
+) -> Result<()> {
+    let linked = get_linked_issues(conn, tasks)?;
+    let projects = match project {
+        Some(_) => Vec::new(),
+        None => projects::get_all(conn, true)?,
+    };
+    let header: &[&str] = if project.is_some() {
+        &["ID", "Issue", "Name"]
+    } else {
+        &["ID", "Project", "Issue", "Name"]
+    };
+    let mut rows = Vec::new();
+    for task in tasks {
+        let task_project = project.or_else(|| projects.iter().find(|p| p.id == task.project_id));
+        let issues = task
+            .issue
+            .iter()
+            .chain(linked.get(&task.id).into_iter().flatten())
+            .map(|&i| match task_project {
+                Some(p) => format.issue(i, p),
+                None => format!("#{i}"),
+            })
+            .collect::<Vec<_>>();
+        let issues = if issues.is_empty() {
+            "-".to_string()
+        } else {
+            issues.join(", ")
+        };
+        let mut row = vec![task.id.0.to_string()];
+        if project.is_none() {
+            row.push(
+                task_project
+                    .map(|p| p.name.clone().unwrap_or_else(|| p.url.clone()))
+                    .unwrap_or_default(),
+            );
+        }
+        row.extend([issues, format.task_name(&task.name)]);
+        rows.push(row);
+    }
+    format.print_table(config.table_style(), header, rows)?;
+    Ok(())
 }
 
 fn pick_task_list(tasks: Vec<Task>) -> eyre::Result<skim::SkimOutput> {
@@ -245,6 +1075,7 @@ impl skim::SkimItem for Task {
 pub struct TaskUpdate<'a> {
     pub name: Option<&'a str>,
     pub issue: Option<Option<i32>>,
+    pub description: Option<Option<&'a str>>,
 }
 
 #[derive(Insertable)]
@@ -272,3 +1103,26 @@ impl ToSql<diesel::sql_types::Integer, Sqlite> for TaskId {
         <i32 as ToSql<diesel::sql_types::Integer, Sqlite>>::to_sql(&self.0, out)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_names() {
+        let data = [
+            ("Standup", "standup", true),
+            ("Standup", "Stand-up", true),
+            ("Code review", "code  review", true),
+            ("Code review", "Code reviw", true),
+            ("Deployment pipeline", "Deploymnt pipeline", true),
+            ("Alpha", "Beta", false),
+            ("Bug 12", "Bug 13", false),
+            ("Standup", "Standups and planning", false),
+            ("", "-", true),
+        ];
+        for (a, b, similar) in data {
+            assert_eq!(is_similar_name(a, b), similar, "{a:?} and {b:?}");
+        }
+    }
+}
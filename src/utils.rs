@@ -1,12 +1,132 @@
+use crate::config::TableStyle;
+use crate::error::WlogError;
+use crate::projects::Project;
 use console::Term;
 use eyre::{Error, Result, bail};
 use owo_colors::OwoColorize;
+use std::path::PathBuf;
 use std::str::FromStr;
+use time::{Duration, PrimitiveDateTime, UtcOffset};
 
 const MAX_ATTEMPTS: u32 = 3;
 
 pub const TABLE_STYLE: &str = "┃┃━━┣━┿┫│─┼┠┨┯┷┏┓┗┛";
 
+#[derive(Debug, Default, Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Terminal table with hyperlinks
+    #[default]
+    Table,
+    /// GitHub-flavored markdown table without escape sequences
+    Markdown,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// Array of objects keyed by column names
+    Json,
+}
+
+impl OutputFormat {
+    /// Issue key linked to the issue in the project's tracker, where the format supports links
+    pub fn issue(self, issue: i32, project: &Project) -> String {
+        match self {
+            OutputFormat::Table if crate::config::hyperlinks() => format!(
+                "\u{1b}]8;;{}\u{1b}\\{}\u{1b}]8;;\u{1b}\\",
+                project.issue_url(issue),
+                project.issue_key(issue)
+            ),
+            OutputFormat::Table => project.issue_key(issue),
+            OutputFormat::Markdown => format!(
+                "[{}]({})",
+                project.issue_key(issue),
+                project.issue_url(issue)
+            ),
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Json => project.issue_key(issue),
+        }
+    }
+
+    /// Task name, truncated in plain terminal tables
+    pub fn task_name(self, name: &str) -> String {
+        match self {
+            OutputFormat::Table if crate::config::plain_output() => truncate(name, TASK_NAME_WIDTH),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Whether subtotal and total rows are added to tables. Formats meant for other programs only
+    /// contain data rows.
+    pub fn has_totals(self) -> bool {
+        matches!(self, OutputFormat::Table | OutputFormat::Markdown)
+    }
+
+    /// Highlighted cell text, used for total rows
+    pub fn strong(self, text: &str) -> String {
+        match self {
+            OutputFormat::Table => text.bold().to_string(),
+            OutputFormat::Markdown => format!("**{text}**"),
+            OutputFormat::Csv | OutputFormat::Tsv | OutputFormat::Json => text.to_string(),
+        }
+    }
+
+    pub fn print_table<I, R>(self, style: TableStyle, header: &[&str], rows: I) -> Result<()>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = String>,
+    {
+        match self {
+            OutputFormat::Table => {
+                let mut table = style.table();
+                table.set_header(header);
+                for row in rows {
+                    table.add_row(row);
+                }
+                println!("{table}");
+            }
+            OutputFormat::Markdown => {
+                println!("| {} |", header.join(" | "));
+                println!("|{}", " --- |".repeat(header.len()));
+                for row in rows {
+                    let cells = row
+                        .into_iter()
+                        .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+                        .collect::<Vec<_>>();
+                    println!("| {} |", cells.join(" | "));
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let delimiter = if matches!(self, OutputFormat::Csv) {
+                    b','
+                } else {
+                    b'\t'
+                };
+                let mut writer = csv::WriterBuilder::new()
+                    .delimiter(delimiter)
+                    .from_writer(std::io::stdout());
+                writer.write_record(header)?;
+                for row in rows {
+                    writer.write_record(row)?;
+                }
+                writer.flush()?;
+            }
+            OutputFormat::Json => {
+                let rows = rows
+                    .into_iter()
+                    .map(|row| {
+                        header
+                            .iter()
+                            .map(|name| name.to_string())
+                            .zip(row.into_iter().map(serde_json::Value::String))
+                            .collect::<serde_json::Map<_, _>>()
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&rows)?);
+            }
+        }
+        Ok(())
+    }
+}
+
 pub fn yn_prompt(msg: &str) -> Result<bool> {
     eprintln!("{msg} [Y/n]");
     let term = Term::stderr();
@@ -26,37 +146,120 @@ pub fn yn_prompt(msg: &str) -> Result<bool> {
         }
         attempt += 1;
         if attempt > MAX_ATTEMPTS {
-            bail!("Unable to parse response in {MAX_ATTEMPTS} attempts");
+            bail!(WlogError::Validation(format!(
+                "Unable to parse response in {MAX_ATTEMPTS} attempts"
+            )));
+        }
+    }
+}
+
+/// Kind of the prompted value. Every kind keeps its own input history, so that the arrow keys
+/// only recall answers to the same question.
+#[derive(Debug, Clone, Copy)]
+pub enum History {
+    TaskName,
+    Issue,
+    ProjectId,
+    ProjectName,
+    ProjectUrl,
+    Choice,
+}
+
+impl History {
+    fn file_name(self) -> &'static str {
+        match self {
+            History::TaskName => "task_name",
+            History::Issue => "issue",
+            History::ProjectId => "project_id",
+            History::ProjectName => "project_name",
+            History::ProjectUrl => "project_url",
+            History::Choice => "choice",
+        }
+    }
+}
+
+/// Line editor with the history of the prompt kind loaded
+struct Editor {
+    rl: rustyline::DefaultEditor,
+    history: Option<PathBuf>,
+}
+
+impl Editor {
+    fn new(history: History) -> Result<Self> {
+        let mut rl = rustyline::DefaultEditor::new()?;
+        let history = crate::config::history_dir().map(|dir| dir.join(history.file_name()));
+        if let Some(path) = &history {
+            // There's no history before the first answer
+            let _ = rl.load_history(path);
+        }
+        Ok(Self { rl, history })
+    }
+
+    fn readline(&mut self, initial: &str) -> Result<String> {
+        Ok(self.rl.readline_with_initial("", (initial, ""))?)
+    }
+
+    /// Remember the answer. History is a convenience, failing to write it doesn't fail the prompt.
+    fn save(&mut self, answer: &str) {
+        let Some(path) = &self.history else {
+            return;
+        };
+        if self.rl.add_history_entry(answer).is_err() {
+            return;
+        }
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
         }
+        let _ = self.rl.save_history(path);
     }
 }
 
-pub fn prompt_opt<T>(msg: &str) -> Result<Option<T>>
+pub fn prompt_opt<T>(msg: &str, history: History) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Into<Error>,
+{
+    prompt_opt_with_initial(msg, "", history)
+}
+
+/// Same as [`prompt_opt`], with the input prefilled with an editable suggestion
+pub fn prompt_opt_with_initial<T>(msg: &str, initial: &str, history: History) -> Result<Option<T>>
 where
     T: FromStr,
     T::Err: Into<Error>,
 {
     eprintln!("{msg} (leave empty for none):");
-    let mut rl = rustyline::DefaultEditor::new()?;
-    let buffer = rl.readline("")?;
+    let mut rl = Editor::new(history)?;
+    let buffer = rl.readline(initial)?;
     let str = buffer.trim();
     if str.is_empty() {
         Ok(None)
     } else {
-        str.parse().map(Some).map_err(Into::into)
+        let value = str.parse().map_err(Into::into)?;
+        rl.save(str);
+        Ok(Some(value))
     }
 }
 
-pub fn prompt<T>(msg: &str) -> Result<T>
+pub fn prompt<T>(msg: &str, history: History) -> Result<T>
 where
     T: FromStr,
     T::Err: Into<Error>,
 {
+    prompt_with_initial(msg, "", history)
+}
+
+/// Same as [`prompt`], with the input prefilled with an editable suggestion
+pub fn prompt_with_initial<T>(msg: &str, initial: &str, history: History) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Into<Error>,
+{
+    let mut rl = Editor::new(history)?;
     let mut attempt = 1;
     loop {
         eprintln!("{msg}:");
-        let mut rl = rustyline::DefaultEditor::new()?;
-        let buffer = rl.readline("")?;
+        let buffer = rl.readline(initial)?;
         let str = buffer.trim();
         if str.is_empty() {
             eprintln!(
@@ -65,18 +268,66 @@ where
             );
         } else {
             match str.parse().map_err(Into::into) {
-                Ok(v) => break Ok(v),
+                Ok(v) => {
+                    rl.save(str);
+                    break Ok(v);
+                }
                 Err(e) => eprintln!("{} Unable to parse: {e}", "Error:".red().bold()),
             }
         }
         attempt += 1;
         if attempt > 3 {
-            bail!("Unable to parse response in {MAX_ATTEMPTS} attempts");
+            bail!(WlogError::Validation(format!(
+                "Unable to parse response in {MAX_ATTEMPTS} attempts"
+            )));
         }
         eprintln!("{} Attempt {attempt}/{MAX_ATTEMPTS}", "Info:".cyan())
     }
 }
 
-pub fn fmt_issue_linked(issue: i32, project_url: &str) -> String {
-    format!("\u{1b}]8;;{project_url}/issues/{issue}\u{1b}\\#{issue}\u{1b}]8;;\u{1b}\\")
+/// Maximum width of task names in plain output
+const TASK_NAME_WIDTH: usize = 32;
+
+/// Text cut to at most `width` characters, ending with `...` if cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(3)).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Format a UTC timestamp from the database in the local offset, to the second
+/// Escape wildcards of LIKE patterns, to be matched with `\` as escape character
+pub fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+pub fn fmt_timestamp(at: PrimitiveDateTime, offset: UtcOffset) -> String {
+    let at = at.assume_utc().to_offset(offset);
+    format!(
+        "{} {:02}:{:02}:{:02}",
+        at.date(),
+        at.hour(),
+        at.minute(),
+        at.second()
+    )
+}
+
+/// Format duration in hours, minutes and seconds without rolling over into days
+pub fn fmt_duration(duration: Duration) -> String {
+    let sign = if duration.is_negative() { "-" } else { "" };
+    let seconds = duration.whole_seconds().unsigned_abs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match (hours, minutes, seconds) {
+        (h, 0, 0) => format!("{sign}{h}h"),
+        (0, m, 0) => format!("{sign}{m}m"),
+        (h, m, 0) => format!("{sign}{h}h{m}m"),
+        (0, 0, s) => format!("{sign}{s}s"),
+        (0, m, s) => format!("{sign}{m}m{s}s"),
+        (h, m, s) => format!("{sign}{h}h{m}m{s}s"),
+    }
 }
@@ -0,0 +1,160 @@
+use crate::Config;
+use crate::projects::Project;
+use crate::schema::{log_entries, projects, tasks, webhook_outbox};
+use crate::tasks::{Task, TaskId};
+use diesel::prelude::*;
+use eyre::Result;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use time::Date;
+
+/// Delivery attempts of an entry per command before it's left for the next one
+const RETRIES: u32 = 3;
+
+/// Longest time a command waits between retries in total, deliveries must not hold up commands
+const RETRY_BUDGET: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Longest time to wait for a server to respond
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    project: &'a str,
+    project_name: Option<&'a str>,
+    task_id: i32,
+    task: &'a str,
+    issue: Option<i32>,
+    date: String,
+    /// Total duration of the task on the date, zero if the entry was removed
+    duration_seconds: i32,
+}
+
+/// Queue the current state of the task's entry on the date, if a webhook is configured
+pub fn enqueue(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    date: Date,
+    task: TaskId,
+) -> Result<()> {
+    if config.on_log.is_none() {
+        return Ok(());
+    }
+    let (task, project) = tasks::table
+        .inner_join(projects::table)
+        .filter(tasks::id.eq(task.0))
+        .select((Task::as_select(), Project::as_select()))
+        .first::<(Task, Project)>(conn)?;
+    let seconds = log_entries::table
+        .find((date, task.id.0))
+        .select(log_entries::duration_seconds)
+        .first::<i32>(conn)
+        .optional()?;
+    let payload = Payload {
+        project: &project.url,
+        project_name: project.name.as_deref(),
+        task_id: task.id.0,
+        task: &task.name,
+        issue: task.issue,
+        date: date.to_string(),
+        duration_seconds: seconds.unwrap_or(0),
+    };
+    diesel::insert_into(webhook_outbox::table)
+        .values(webhook_outbox::payload.eq(serde_json::to_string(&payload)?))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Deliver queued entries in order. Stops at the first entry that can't be delivered, so that it's
+/// retried by a later command, entries rejected by the server are dropped.
+pub fn flush(conn: &mut SqliteConnection, config: &Config) -> Result<()> {
+    let Some(url) = config.on_log.as_deref() else {
+        return Ok(());
+    };
+    let queued = webhook_outbox::table
+        .select((webhook_outbox::id, webhook_outbox::payload))
+        .order_by(webhook_outbox::id)
+        .load::<(i32, String)>(conn)?;
+    let mut sender = Sender::new(&[]);
+    for (i, (id, payload)) in queued.iter().enumerate() {
+        let request = |agent: &ureq::Agent| agent.post(url).set("Content-Type", "application/json");
+        match sender.send(request, payload) {
+            Ok(Delivery::Delivered) => {}
+            Ok(Delivery::Rejected(code)) => {
+                eprintln!(
+                    "{} Webhook rejected a log entry with status {code}, dropping it",
+                    "Warning:".yellow().bold()
+                );
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} Webhook delivery failed, {} log entries queued: {e}",
+                    "Warning:".yellow().bold(),
+                    queued.len() - i
+                );
+                return Ok(());
+            }
+        }
+        diesel::delete(webhook_outbox::table.find(id)).execute(conn)?;
+    }
+    Ok(())
+}
+
+pub enum Delivery {
+    Delivered,
+    /// Client error status, sending the item again won't help
+    Rejected(u16),
+}
+
+/// Sends queued items, retrying transient failures within a time budget shared by all items
+pub struct Sender {
+    agent: ureq::Agent,
+    /// Client error statuses that leave the item queued instead of rejecting it, e.g. failed
+    /// authentication
+    keep: &'static [u16],
+    retry_budget: std::time::Duration,
+}
+
+impl Sender {
+    pub fn new(keep: &'static [u16]) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+            keep,
+            retry_budget: RETRY_BUDGET,
+        }
+    }
+
+    /// Send the body with the request. Errors leave the item queued for a later command.
+    pub fn send(
+        &mut self,
+        request: impl Fn(&ureq::Agent) -> ureq::Request,
+        body: &str,
+    ) -> Result<Delivery> {
+        let mut attempt = 1;
+        loop {
+            let error = match request(&self.agent).send_string(body) {
+                Ok(_) => return Ok(Delivery::Delivered),
+                Err(ureq::Error::Status(code, _))
+                    if code < 500 && !is_transient(code) && !self.keep.contains(&code) =>
+                {
+                    return Ok(Delivery::Rejected(code));
+                }
+                Err(e @ ureq::Error::Status(code, _)) if !is_transient(code) => {
+                    return Err(e.into());
+                }
+                Err(e) => e,
+            };
+            let backoff = std::time::Duration::from_millis(250 << attempt);
+            if attempt == RETRIES || backoff > self.retry_budget {
+                return Err(error.into());
+            }
+            std::thread::sleep(backoff);
+            self.retry_budget -= backoff;
+            attempt += 1;
+        }
+    }
+}
+
+/// Server errors, timeouts and rate limits, which may succeed when sent again
+fn is_transient(code: u16) -> bool {
+    code >= 500 || code == 408 || code == 429
+}